@@ -0,0 +1,49 @@
+//! Authentication of the oracle's admin methods.
+//!
+//! Admin methods (key management, reference lists, events and the operational
+//! reports) require an `Authorization: Bearer <token>` header matching
+//! `ORACLE_ADMIN_TOKEN`. When no admin token is configured every admin call is
+//! refused, so a deployment cannot expose them by omission.
+
+use jsonrpc_core::{Error, ErrorCode};
+use std::env;
+
+/// JSON-RPC error code of a refused admin call.
+pub const UNAUTHORIZED_CODE: i64 = -32001;
+
+tokio::task_local! {
+    /// The `Authorization` header of the HTTP request being served, if it had one.
+    pub static AUTHORIZATION: Option<String>;
+}
+
+/// Fails unless the current request carries the configured admin token.
+pub fn require_admin() -> Result<(), Error> {
+    let expected = env::var("ORACLE_ADMIN_TOKEN").unwrap_or_default();
+    if expected.is_empty() {
+        return Err(unauthorized());
+    }
+    let authorization = AUTHORIZATION.try_with(Clone::clone).ok().flatten();
+    let presented = authorization
+        .as_deref()
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match presented {
+        Some(presented) if constant_time_eq(presented.as_bytes(), expected.as_bytes()) => Ok(()),
+        _ => Err(unauthorized()),
+    }
+}
+
+fn unauthorized() -> Error {
+    Error {
+        code: ErrorCode::ServerError(UNAUTHORIZED_CODE),
+        message: "Unauthorized: this method requires the admin token".to_string(),
+        data: None,
+    }
+}
+
+/// Compares two byte strings in time that depends only on their lengths.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
@@ -0,0 +1,131 @@
+use futures::future::{BoxFuture, FutureExt, Shared};
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Number of entries a [`TtlCache`] holds unless built with [`TtlCache::with_capacity`].
+pub const DEFAULT_CACHE_CAPACITY: usize = 10_000;
+
+/// A small in-process cache whose entries expire after a fixed time-to-live.
+///
+/// Expired entries are dropped when they are looked up, and all of them are
+/// purged once the cache is full; if it is still full after that, the oldest
+/// entry makes room for the new one.
+pub struct TtlCache<K, V> {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<HashMap<K, (Instant, V)>>,
+}
+
+impl<K, V> TtlCache<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_capacity(ttl, DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Builds a cache that holds at most `capacity` entries.
+    pub fn with_capacity(ttl: Duration, capacity: usize) -> Self {
+        TtlCache {
+            ttl,
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cached value for `key` if it has not expired yet.
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(key) {
+            Some((stored_at, value)) if stored_at.elapsed() < self.ttl => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if !entries.contains_key(&key) && entries.len() >= self.capacity {
+            entries.retain(|_, (stored_at, _)| stored_at.elapsed() < self.ttl);
+            if entries.len() >= self.capacity {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, (stored_at, _))| *stored_at)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(key, (Instant::now(), value));
+    }
+
+    /// Number of entries held, including expired ones not yet purged.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Coalesces concurrent work with the same key into a single run.
+///
+/// While a run for a key is in flight, further callers await its result instead
+/// of starting their own. The entry is removed once the run completes, so later
+/// callers start afresh; nothing is cached beyond the run itself.
+pub struct PendingRequestCache<K, V> {
+    pending: Mutex<HashMap<K, Shared<BoxFuture<'static, V>>>>,
+}
+
+impl<K, V> Default for PendingRequestCache<K, V> {
+    fn default() -> Self {
+        PendingRequestCache {
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<K, V> PendingRequestCache<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone + Send + Sync + 'static,
+{
+    /// Awaits the in-flight run for `key`, or starts one with `run` if there is none.
+    ///
+    /// If the caller driving a run is dropped, the next waiter keeps driving it.
+    pub async fn get_or_run<F, Fut>(&self, key: K, run: F) -> V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V> + Send + 'static,
+    {
+        let shared = {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            pending
+                .entry(key.clone())
+                .or_insert_with(|| run().boxed().shared())
+                .clone()
+        };
+        let value = shared.clone().await;
+
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        if pending
+            .get(&key)
+            .is_some_and(|current| current.ptr_eq(&shared))
+        {
+            pending.remove(&key);
+        }
+        value
+    }
+}
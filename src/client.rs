@@ -0,0 +1,1081 @@
+//! Typed async client for the oracle's JSON-RPC surface over HTTP.
+//!
+//! Enabled with the `client` feature. Claim inputs are encoded with
+//! [`crate::encoding`], the same module the server decodes them with.
+
+use chrono::NaiveDate;
+use jsonrpc_core::Error as RpcError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::encoding::{encode_number, encode_str};
+use crate::types::{
+    KeyUsage, ListeningEvolution, TimeRange, CAN_CLAIM_ALBUM_N_MARKETS,
+    CAN_CLAIM_ALBUM_PLAYTHROUGH, CAN_CLAIM_ARTIST_ALL_RANGES, CAN_CLAIM_ARTIST_BIO_LENGTH,
+    CAN_CLAIM_ARTIST_COLLAB_COUNT, CAN_CLAIM_ARTIST_MARKET_COUNT,
+    CAN_CLAIM_ARTIST_NOT_RECENTLY_PLAYED, CAN_CLAIM_ARTIST_N_FOLLOWERS, CAN_CLAIM_ARTIST_ON_TOUR,
+    CAN_CLAIM_ARTIST_TOP_TRACK_IN_HISTORY, CAN_CLAIM_AUDIOBOOK_CHAPTER, CAN_CLAIM_AUDIOBOOK_SAVED,
+    CAN_CLAIM_CONSECUTIVE_ARTIST_PLAYS, CAN_CLAIM_CURATOR_CONTRIBUTION_COUNT,
+    CAN_CLAIM_DEVICE_LOCATION_HINT, CAN_CLAIM_DISCOGRAPHY_COMPLETED, CAN_CLAIM_DISJOINT_ARTISTS,
+    CAN_CLAIM_DISTINCT_ALBUMS_IN_HISTORY, CAN_CLAIM_EPISODE_COMPLETED, CAN_CLAIM_EPISODE_IN_SHOW,
+    CAN_CLAIM_EVENT_LOCAL, CAN_CLAIM_FOLLOWS_N_ARTISTS, CAN_CLAIM_GENRE_ENTROPY,
+    CAN_CLAIM_GENRE_NOT_IN_TOP, CAN_CLAIM_IS_PODCAST_EPISODE, CAN_CLAIM_LIBRARY_AGE,
+    CAN_CLAIM_LIBRARY_OVERLAP, CAN_CLAIM_LISTENING_EVOLVED, CAN_CLAIM_MORNING_TOP_TRACK,
+    CAN_CLAIM_N_ARTISTS_TOTAL, CAN_CLAIM_N_FOLLOWED_PODCASTS, CAN_CLAIM_N_PLAYLISTS,
+    CAN_CLAIM_N_SAVED_ALBUMS, CAN_CLAIM_N_SAVED_TRACKS, CAN_CLAIM_N_TRACKS_TOTAL,
+    CAN_CLAIM_PLAYED_DURING_EVENT, CAN_CLAIM_PLAYLIST_CLEAN, CAN_CLAIM_PLAYLIST_CO_CURATED,
+    CAN_CLAIM_PLAYLIST_CREATED_BEFORE, CAN_CLAIM_PLAYLIST_FOLLOWER_THRESHOLD,
+    CAN_CLAIM_PLAYLIST_RECENTLY_UPDATED, CAN_CLAIM_PREVIEW_ACCESSIBLE,
+    CAN_CLAIM_RECENTLY_PLAYED_TRACK, CAN_CLAIM_RECENT_PLAY_ON_RELEASE_DAY,
+    CAN_CLAIM_REFERENCE_SEQUENCE, CAN_CLAIM_ROYALTY_FREE, CAN_CLAIM_SAME_RECORDING,
+    CAN_CLAIM_SAVED_SHOW_COUNT, CAN_CLAIM_SESSION_TRACK_COUNT, CAN_CLAIM_SHARED_TOP_TRACK,
+    CAN_CLAIM_SHOW_SAVED, CAN_CLAIM_TOP_ARTISTS, CAN_CLAIM_TOP_TRACKS,
+    CAN_CLAIM_TOP_TRACKS_ALL_EXPLICIT, CAN_CLAIM_TOP_TRACKS_DECADE,
+    CAN_CLAIM_TOP_TRACKS_MONOARTIST, CAN_CLAIM_TOP_TRACKS_TEMPORAL_DIVERSITY,
+    CAN_CLAIM_TOP_TRACK_STABILITY, CAN_CLAIM_TRACK_ADDED_BY, CAN_CLAIM_TRACK_ALL_RANGES,
+    CAN_CLAIM_TRACK_ANALYSIS_SUMMARY, CAN_CLAIM_TRACK_BARS_COUNT, CAN_CLAIM_TRACK_BEATS_COUNT,
+    CAN_CLAIM_TRACK_FADE_IN, CAN_CLAIM_TRACK_FADE_OUT, CAN_CLAIM_TRACK_HAS_MUSIC_VIDEO,
+    CAN_CLAIM_TRACK_IN_TV_SHOW, CAN_CLAIM_TRACK_IS_COVER, CAN_CLAIM_TRACK_NOT_RECENTLY_PLAYED,
+    CAN_CLAIM_TRACK_N_MARKETS, CAN_CLAIM_TRACK_PLAYLIST_COUNT, CAN_CLAIM_TRACK_REPEAT_COUNT,
+    CAN_CLAIM_TRACK_SAMPLING, CAN_CLAIM_TRACK_SEGMENTS_COUNT, CAN_CLAIM_TRACK_SPOTIFY_EXCLUSIVE,
+    CAN_CLAIM_TRACK_TATUMS_COUNT, CAN_CLAIM_WORKOUT_LISTENER,
+};
+use crate::usage::{Health, ProviderFailures, UsageRecord};
+
+/// Errors returned by [`OracleClient`].
+#[derive(Debug)]
+pub enum ClientError {
+    /// The HTTP request could not be made or its body could not be read.
+    Transport(reqwest::Error),
+    /// The oracle answered with a JSON-RPC error.
+    Rpc(RpcError),
+    /// The oracle answered with something that is not the expected result.
+    InvalidResponse(String),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Transport(e) => write!(f, "transport error: {}", e),
+            ClientError::Rpc(e) => write!(f, "oracle error {}: {}", e.code.code(), e.message),
+            ClientError::InvalidResponse(message) => write!(f, "invalid response: {}", message),
+        }
+    }
+}
+
+impl Error for ClientError {}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(e: reqwest::Error) -> Self {
+        ClientError::Transport(e)
+    }
+}
+
+/// Parameters of a claim, one variant per claim function.
+///
+/// Unused inputs are sent as zero, the way circuits pad them.
+#[derive(Clone, Debug)]
+pub enum ClaimParams {
+    TopTrack {
+        track_id: String,
+        time_range: TimeRange,
+        list_range: u8,
+    },
+    TopArtist {
+        artist_id: String,
+        time_range: TimeRange,
+        list_range: u8,
+    },
+    RecentlyPlayedTrack {
+        track_id: String,
+        after: u64,
+        played_times: u8,
+    },
+    /// `reference` is either `list:<id>` or an inline comma-separated list of IDs.
+    LibraryOverlap {
+        reference: String,
+        threshold: u8,
+    },
+    TrackSegmentsCount {
+        track_id: String,
+        threshold: u64,
+    },
+    TrackBeatsCount {
+        track_id: String,
+        threshold: u64,
+    },
+    TrackBarsCount {
+        track_id: String,
+        threshold: u64,
+    },
+    TrackTatumsCount {
+        track_id: String,
+        threshold: u64,
+    },
+    TrackAnalysisSummary {
+        track_id: String,
+    },
+    TrackFadeOut {
+        track_id: String,
+        min_seconds: u16,
+    },
+    TrackFadeIn {
+        track_id: String,
+        max_seconds: u16,
+    },
+    RecentPlayOnReleaseDay {
+        track_id: String,
+        allow_journal: bool,
+    },
+    /// A `max_gap_minutes` of zero uses the server's configured default.
+    AlbumPlaythrough {
+        album_id: String,
+        after: u64,
+        max_gap_minutes: u8,
+    },
+    SavedShowCount {
+        threshold: u64,
+    },
+    DistinctAlbumsInHistory {
+        after: u64,
+        threshold: u8,
+    },
+    PlayedDuringEvent {
+        event_id: String,
+    },
+    TrackInAllTopRanges {
+        track_id: String,
+        list_range: u8,
+    },
+    ArtistInAllTopRanges {
+        artist_id: String,
+        list_range: u8,
+    },
+    ArtistTopTrackInHistory {
+        artist_id: String,
+        after: u64,
+    },
+    TracksTotal {
+        threshold: u64,
+    },
+    ArtistsTotal {
+        threshold: u64,
+    },
+    ConsecutiveArtistPlays {
+        artist_id: String,
+        after: u64,
+        threshold: u8,
+    },
+    TrackOnPlaylists {
+        track_id: String,
+        threshold: u8,
+    },
+    FollowsArtists {
+        threshold: u64,
+    },
+    PlaylistFollowers {
+        playlist_id: String,
+        threshold: u64,
+        require_owner: bool,
+    },
+    SavedTracksCount {
+        threshold: u32,
+    },
+    SavedAlbumsCount {
+        threshold: u32,
+    },
+    ReferenceSequence {
+        /// `list:<id>` of a stored list, or comma-separated track IDs, in play order.
+        reference: String,
+        after: u64,
+        max_gap_plays: u8,
+    },
+    PlaylistsCount {
+        threshold: u32,
+    },
+    FollowedPodcastsCount {
+        threshold: u32,
+    },
+    /// Takes three inputs; the fourth is not sent.
+    ArtistFollowers {
+        artist_id: String,
+        threshold: u64,
+    },
+    PodcastEpisode {
+        episode_id: String,
+    },
+    EpisodeInShow {
+        episode_id: String,
+        show_id: String,
+    },
+    ShowSaved {
+        show_id: String,
+    },
+    EpisodeCompleted {
+        episode_id: String,
+    },
+    AudiobookSaved {
+        audiobook_id: String,
+    },
+    AudiobookChapterListened {
+        audiobook_id: String,
+        chapter_id: String,
+        min_percent: u8,
+    },
+    /// How many top tracks count is configured on the server.
+    TopTrackStability {
+        track_id: String,
+        time_ranges: (TimeRange, TimeRange),
+    },
+    /// Takes three inputs; the fourth is not sent.
+    TrackMarkets {
+        track_id: String,
+        threshold: u8,
+    },
+    /// Takes three inputs; the fourth is not sent.
+    AlbumMarkets {
+        album_id: String,
+        threshold: u8,
+    },
+    /// Takes three inputs; the fourth is not sent.
+    ArtistMarkets {
+        artist_id: String,
+        threshold: u8,
+    },
+    TrackNotRecentlyPlayed {
+        track_id: String,
+        after: u64,
+        played_times: u8,
+    },
+    ArtistNotRecentlyPlayed {
+        artist_id: String,
+        after: u64,
+        played_times: u8,
+    },
+    GenreNotInTop {
+        genre: String,
+        time_range: TimeRange,
+        list_range: u8,
+    },
+    /// Takes three inputs; the fourth is not sent.
+    PlaylistRecentlyUpdated {
+        playlist_id: String,
+        within_hours: u64,
+    },
+    /// Takes three inputs; the fourth is not sent.
+    PlaylistCreatedBefore {
+        playlist_id: String,
+        before: u64,
+    },
+    FirstSavedTrackBefore {
+        before: u64,
+    },
+    /// A heuristic over device names, not a location proof.
+    DeviceLocationHint {
+        location: String,
+    },
+    TrackRoyaltyFree {
+        track_id: String,
+    },
+    /// Takes three inputs; the fourth is not sent.
+    TrackCoversOriginal {
+        track_id: String,
+        /// MusicBrainz ID of the original recording.
+        original_id: String,
+    },
+    /// Takes three inputs; the fourth is not sent.
+    TrackSameRecording {
+        track_id: String,
+        /// The recording on another service, as `musicbrainz:<recording MBID>`.
+        other: String,
+    },
+    /// Takes three inputs; the fourth is not sent.
+    TrackSampling {
+        sampling_id: String,
+        sampled_id: String,
+    },
+    PlaylistCoCurated {
+        playlist_id: String,
+    },
+    TrackAddedBy {
+        playlist_id: String,
+        track_id: String,
+        /// Spotify user ID of the curator.
+        curator_id: String,
+    },
+    /// Answers with a count rather than 0 or 1.
+    CuratorTrackCount {
+        playlist_id: String,
+    },
+    PlaylistClean {
+        playlist_id: String,
+    },
+    TopTracksMonoArtist {
+        artist_id: String,
+        time_range: TimeRange,
+        list_range: u8,
+    },
+    /// The threshold is sent as the second input.
+    TopTracksDecades {
+        threshold: u8,
+        time_range: TimeRange,
+        list_range: u8,
+    },
+    /// Takes three inputs; the fourth is not sent.
+    TopTracksAllExplicit {
+        time_range: TimeRange,
+        list_range: u8,
+    },
+    /// The time range is sent as the second input and the decade as the third.
+    TopTracksDecadeCount {
+        time_range: TimeRange,
+        decade: u16,
+        list_range: u8,
+    },
+    /// Takes three inputs; the fourth is not sent.
+    ArtistCollaborations {
+        artist_id: String,
+        threshold: u8,
+    },
+    /// The time range is sent as the second input and the threshold, in
+    /// sixteenths of a bit, as the third.
+    GenreEntropy {
+        time_range: TimeRange,
+        threshold: u8,
+        list_range: u8,
+    },
+    DiscographyCompleted {
+        artist_id: String,
+    },
+    ListeningEvolved {
+        artist_id: String,
+        list_range: u8,
+        mode: ListeningEvolution,
+    },
+    /// Takes three inputs; the fourth is not sent.
+    TrackRepeatCount {
+        track_id: String,
+        threshold: u8,
+    },
+    MultiTrackSession {
+        threshold: u8,
+        after: u64,
+    },
+    MorningTopTrack {
+        track_id: String,
+        after: u64,
+        list_range: u8,
+    },
+    /// An empty `track_id` matches plays of any track.
+    WorkoutContext {
+        track_id: String,
+        after: u64,
+    },
+    /// Sends the keys of the other users after the track, one input each.
+    SharedTopTrack {
+        track_id: String,
+        other_keys: Vec<String>,
+    },
+    /// `other_key` is the API key of the second user.
+    DisjointTopArtists {
+        other_key: String,
+        time_range: TimeRange,
+        list_range: u8,
+    },
+    TrackSpotifyExclusive {
+        track_id: String,
+    },
+    TrackPreviewAccessible {
+        track_id: String,
+    },
+    /// Takes three inputs; the fourth is not sent. `concert_artist_id` is the
+    /// Songkick or Bandsintown ID of the same artist, looked up by name when empty.
+    ArtistOnTour {
+        artist_id: String,
+        concert_artist_id: String,
+    },
+    /// Takes three inputs; the fourth is not sent. `artist_id` is the Songkick ID.
+    EventInUserCity {
+        artist_id: String,
+        city: String,
+    },
+    /// Takes three inputs; the fourth is not sent.
+    TrackInTvShow {
+        track_id: String,
+        show: String,
+    },
+    TrackHasMusicVideo {
+        track_id: String,
+    },
+    /// Takes three inputs; the fourth is not sent.
+    ArtistBiographyLength {
+        artist_id: String,
+        threshold: u16,
+    },
+}
+
+impl ClaimParams {
+    /// The claim function these parameters are for.
+    pub fn function(&self) -> &'static str {
+        match self {
+            ClaimParams::TopTrack { .. } => CAN_CLAIM_TOP_TRACKS,
+            ClaimParams::TopArtist { .. } => CAN_CLAIM_TOP_ARTISTS,
+            ClaimParams::RecentlyPlayedTrack { .. } => CAN_CLAIM_RECENTLY_PLAYED_TRACK,
+            ClaimParams::LibraryOverlap { .. } => CAN_CLAIM_LIBRARY_OVERLAP,
+            ClaimParams::TrackSegmentsCount { .. } => CAN_CLAIM_TRACK_SEGMENTS_COUNT,
+            ClaimParams::TrackBeatsCount { .. } => CAN_CLAIM_TRACK_BEATS_COUNT,
+            ClaimParams::TrackBarsCount { .. } => CAN_CLAIM_TRACK_BARS_COUNT,
+            ClaimParams::TrackTatumsCount { .. } => CAN_CLAIM_TRACK_TATUMS_COUNT,
+            ClaimParams::TrackAnalysisSummary { .. } => CAN_CLAIM_TRACK_ANALYSIS_SUMMARY,
+            ClaimParams::TrackFadeOut { .. } => CAN_CLAIM_TRACK_FADE_OUT,
+            ClaimParams::TrackFadeIn { .. } => CAN_CLAIM_TRACK_FADE_IN,
+            ClaimParams::RecentPlayOnReleaseDay { .. } => CAN_CLAIM_RECENT_PLAY_ON_RELEASE_DAY,
+            ClaimParams::AlbumPlaythrough { .. } => CAN_CLAIM_ALBUM_PLAYTHROUGH,
+            ClaimParams::SavedShowCount { .. } => CAN_CLAIM_SAVED_SHOW_COUNT,
+            ClaimParams::DistinctAlbumsInHistory { .. } => CAN_CLAIM_DISTINCT_ALBUMS_IN_HISTORY,
+            ClaimParams::PlayedDuringEvent { .. } => CAN_CLAIM_PLAYED_DURING_EVENT,
+            ClaimParams::TrackInAllTopRanges { .. } => CAN_CLAIM_TRACK_ALL_RANGES,
+            ClaimParams::ArtistInAllTopRanges { .. } => CAN_CLAIM_ARTIST_ALL_RANGES,
+            ClaimParams::ArtistTopTrackInHistory { .. } => CAN_CLAIM_ARTIST_TOP_TRACK_IN_HISTORY,
+            ClaimParams::TracksTotal { .. } => CAN_CLAIM_N_TRACKS_TOTAL,
+            ClaimParams::ArtistsTotal { .. } => CAN_CLAIM_N_ARTISTS_TOTAL,
+            ClaimParams::ConsecutiveArtistPlays { .. } => CAN_CLAIM_CONSECUTIVE_ARTIST_PLAYS,
+            ClaimParams::TrackOnPlaylists { .. } => CAN_CLAIM_TRACK_PLAYLIST_COUNT,
+            ClaimParams::FollowsArtists { .. } => CAN_CLAIM_FOLLOWS_N_ARTISTS,
+            ClaimParams::PlaylistFollowers { .. } => CAN_CLAIM_PLAYLIST_FOLLOWER_THRESHOLD,
+            ClaimParams::SavedTracksCount { .. } => CAN_CLAIM_N_SAVED_TRACKS,
+            ClaimParams::SavedAlbumsCount { .. } => CAN_CLAIM_N_SAVED_ALBUMS,
+            ClaimParams::ReferenceSequence { .. } => CAN_CLAIM_REFERENCE_SEQUENCE,
+            ClaimParams::PlaylistsCount { .. } => CAN_CLAIM_N_PLAYLISTS,
+            ClaimParams::FollowedPodcastsCount { .. } => CAN_CLAIM_N_FOLLOWED_PODCASTS,
+            ClaimParams::ArtistFollowers { .. } => CAN_CLAIM_ARTIST_N_FOLLOWERS,
+            ClaimParams::TopTrackStability { .. } => CAN_CLAIM_TOP_TRACK_STABILITY,
+            ClaimParams::PodcastEpisode { .. } => CAN_CLAIM_IS_PODCAST_EPISODE,
+            ClaimParams::EpisodeInShow { .. } => CAN_CLAIM_EPISODE_IN_SHOW,
+            ClaimParams::ShowSaved { .. } => CAN_CLAIM_SHOW_SAVED,
+            ClaimParams::EpisodeCompleted { .. } => CAN_CLAIM_EPISODE_COMPLETED,
+            ClaimParams::AudiobookSaved { .. } => CAN_CLAIM_AUDIOBOOK_SAVED,
+            ClaimParams::AudiobookChapterListened { .. } => CAN_CLAIM_AUDIOBOOK_CHAPTER,
+            ClaimParams::TrackMarkets { .. } => CAN_CLAIM_TRACK_N_MARKETS,
+            ClaimParams::AlbumMarkets { .. } => CAN_CLAIM_ALBUM_N_MARKETS,
+            ClaimParams::ArtistMarkets { .. } => CAN_CLAIM_ARTIST_MARKET_COUNT,
+            ClaimParams::TrackNotRecentlyPlayed { .. } => CAN_CLAIM_TRACK_NOT_RECENTLY_PLAYED,
+            ClaimParams::ArtistNotRecentlyPlayed { .. } => CAN_CLAIM_ARTIST_NOT_RECENTLY_PLAYED,
+            ClaimParams::GenreNotInTop { .. } => CAN_CLAIM_GENRE_NOT_IN_TOP,
+            ClaimParams::PlaylistRecentlyUpdated { .. } => CAN_CLAIM_PLAYLIST_RECENTLY_UPDATED,
+            ClaimParams::PlaylistCreatedBefore { .. } => CAN_CLAIM_PLAYLIST_CREATED_BEFORE,
+            ClaimParams::FirstSavedTrackBefore { .. } => CAN_CLAIM_LIBRARY_AGE,
+            ClaimParams::DeviceLocationHint { .. } => CAN_CLAIM_DEVICE_LOCATION_HINT,
+            ClaimParams::TrackRoyaltyFree { .. } => CAN_CLAIM_ROYALTY_FREE,
+            ClaimParams::TrackCoversOriginal { .. } => CAN_CLAIM_TRACK_IS_COVER,
+            ClaimParams::TrackSameRecording { .. } => CAN_CLAIM_SAME_RECORDING,
+            ClaimParams::TrackSampling { .. } => CAN_CLAIM_TRACK_SAMPLING,
+            ClaimParams::PlaylistCoCurated { .. } => CAN_CLAIM_PLAYLIST_CO_CURATED,
+            ClaimParams::TrackAddedBy { .. } => CAN_CLAIM_TRACK_ADDED_BY,
+            ClaimParams::CuratorTrackCount { .. } => CAN_CLAIM_CURATOR_CONTRIBUTION_COUNT,
+            ClaimParams::PlaylistClean { .. } => CAN_CLAIM_PLAYLIST_CLEAN,
+            ClaimParams::TopTracksMonoArtist { .. } => CAN_CLAIM_TOP_TRACKS_MONOARTIST,
+            ClaimParams::TopTracksDecades { .. } => CAN_CLAIM_TOP_TRACKS_TEMPORAL_DIVERSITY,
+            ClaimParams::TopTracksAllExplicit { .. } => CAN_CLAIM_TOP_TRACKS_ALL_EXPLICIT,
+            ClaimParams::TopTracksDecadeCount { .. } => CAN_CLAIM_TOP_TRACKS_DECADE,
+            ClaimParams::ArtistCollaborations { .. } => CAN_CLAIM_ARTIST_COLLAB_COUNT,
+            ClaimParams::GenreEntropy { .. } => CAN_CLAIM_GENRE_ENTROPY,
+            ClaimParams::DiscographyCompleted { .. } => CAN_CLAIM_DISCOGRAPHY_COMPLETED,
+            ClaimParams::ListeningEvolved { .. } => CAN_CLAIM_LISTENING_EVOLVED,
+            ClaimParams::TrackRepeatCount { .. } => CAN_CLAIM_TRACK_REPEAT_COUNT,
+            ClaimParams::MultiTrackSession { .. } => CAN_CLAIM_SESSION_TRACK_COUNT,
+            ClaimParams::MorningTopTrack { .. } => CAN_CLAIM_MORNING_TOP_TRACK,
+            ClaimParams::WorkoutContext { .. } => CAN_CLAIM_WORKOUT_LISTENER,
+            ClaimParams::SharedTopTrack { .. } => CAN_CLAIM_SHARED_TOP_TRACK,
+            ClaimParams::DisjointTopArtists { .. } => CAN_CLAIM_DISJOINT_ARTISTS,
+            ClaimParams::TrackSpotifyExclusive { .. } => CAN_CLAIM_TRACK_SPOTIFY_EXCLUSIVE,
+            ClaimParams::TrackPreviewAccessible { .. } => CAN_CLAIM_PREVIEW_ACCESSIBLE,
+            ClaimParams::ArtistOnTour { .. } => CAN_CLAIM_ARTIST_ON_TOUR,
+            ClaimParams::EventInUserCity { .. } => CAN_CLAIM_EVENT_LOCAL,
+            ClaimParams::TrackInTvShow { .. } => CAN_CLAIM_TRACK_IN_TV_SHOW,
+            ClaimParams::TrackHasMusicVideo { .. } => CAN_CLAIM_TRACK_HAS_MUSIC_VIDEO,
+            ClaimParams::ArtistBiographyLength { .. } => CAN_CLAIM_ARTIST_BIO_LENGTH,
+        }
+    }
+
+    /// Encodes the claim inputs for the API key `key`: four, or three for
+    /// claims that take no fourth input.
+    pub fn inputs(&self, key: &str) -> Vec<Vec<String>> {
+        let (subject, third, fourth): (&str, u64, u64) = match self {
+            ClaimParams::TopTrack {
+                track_id,
+                time_range,
+                list_range,
+            } => (track_id, *time_range as u64, *list_range as u64),
+            ClaimParams::TopArtist {
+                artist_id,
+                time_range,
+                list_range,
+            } => (artist_id, *time_range as u64, *list_range as u64),
+            ClaimParams::RecentlyPlayedTrack {
+                track_id,
+                after,
+                played_times,
+            } => (track_id, *after, *played_times as u64),
+            ClaimParams::LibraryOverlap {
+                reference,
+                threshold,
+            } => (reference, 0, *threshold as u64),
+            ClaimParams::TrackSegmentsCount {
+                track_id,
+                threshold,
+            }
+            | ClaimParams::TrackBeatsCount {
+                track_id,
+                threshold,
+            }
+            | ClaimParams::TrackBarsCount {
+                track_id,
+                threshold,
+            }
+            | ClaimParams::TrackTatumsCount {
+                track_id,
+                threshold,
+            } => (track_id, *threshold, 0),
+            ClaimParams::TrackAnalysisSummary { track_id } => (track_id, 0, 0),
+            ClaimParams::PodcastEpisode { episode_id }
+            | ClaimParams::EpisodeCompleted { episode_id } => (episode_id, 0, 0),
+            ClaimParams::ShowSaved { show_id } => (show_id, 0, 0),
+            ClaimParams::AudiobookSaved { audiobook_id } => (audiobook_id, 0, 0),
+            ClaimParams::TrackFadeOut {
+                track_id,
+                min_seconds,
+            } => (track_id, *min_seconds as u64, 0),
+            ClaimParams::TrackFadeIn {
+                track_id,
+                max_seconds,
+            } => (track_id, *max_seconds as u64, 0),
+            ClaimParams::RecentPlayOnReleaseDay {
+                track_id,
+                allow_journal,
+            } => (track_id, 0, *allow_journal as u64),
+            ClaimParams::AlbumPlaythrough {
+                album_id,
+                after,
+                max_gap_minutes,
+            } => (album_id, *after, *max_gap_minutes as u64),
+            ClaimParams::SavedShowCount { threshold }
+            | ClaimParams::TracksTotal { threshold }
+            | ClaimParams::ArtistsTotal { threshold }
+            | ClaimParams::FollowsArtists { threshold } => ("", *threshold, 0),
+            ClaimParams::DistinctAlbumsInHistory { after, threshold } => {
+                ("", *after, *threshold as u64)
+            }
+            ClaimParams::MultiTrackSession { threshold, after } => ("", *threshold as u64, *after),
+            ClaimParams::MorningTopTrack {
+                track_id,
+                after,
+                list_range,
+            } => (track_id, *after, *list_range as u64),
+            ClaimParams::WorkoutContext { track_id, after } => (track_id, *after, 0),
+            ClaimParams::DisjointTopArtists {
+                other_key,
+                time_range,
+                list_range,
+            } => (other_key, *time_range as u64, *list_range as u64),
+            ClaimParams::PlayedDuringEvent { event_id } => (event_id, 0, 0),
+            ClaimParams::TrackInAllTopRanges {
+                track_id,
+                list_range,
+            } => (track_id, 0, *list_range as u64),
+            ClaimParams::ArtistInAllTopRanges {
+                artist_id,
+                list_range,
+            } => (artist_id, 0, *list_range as u64),
+            ClaimParams::ArtistTopTrackInHistory { artist_id, after } => (artist_id, *after, 0),
+            ClaimParams::ConsecutiveArtistPlays {
+                artist_id,
+                after,
+                threshold,
+            } => (artist_id, *after, *threshold as u64),
+            ClaimParams::TrackOnPlaylists {
+                track_id,
+                threshold,
+            } => (track_id, *threshold as u64, 0),
+            ClaimParams::PlaylistFollowers {
+                playlist_id,
+                threshold,
+                require_owner,
+            } => (playlist_id, *threshold, *require_owner as u64),
+            ClaimParams::SavedTracksCount { threshold } => ("", *threshold as u64, 0),
+            ClaimParams::SavedAlbumsCount { threshold } => ("", *threshold as u64, 0),
+            ClaimParams::ReferenceSequence {
+                reference,
+                after,
+                max_gap_plays,
+            } => (reference, *after, *max_gap_plays as u64),
+            ClaimParams::PlaylistsCount { threshold } => ("", *threshold as u64, 0),
+            ClaimParams::FollowedPodcastsCount { threshold } => ("", *threshold as u64, 0),
+            ClaimParams::TopTrackStability {
+                track_id,
+                time_ranges,
+            } => (track_id, time_ranges.0 as u64, time_ranges.1 as u64),
+            ClaimParams::AudiobookChapterListened {
+                audiobook_id,
+                chapter_id,
+                min_percent,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_str(audiobook_id),
+                    encode_str(chapter_id),
+                    encode_number(*min_percent as u64),
+                ]
+            }
+            ClaimParams::EpisodeInShow {
+                episode_id,
+                show_id,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_str(episode_id),
+                    encode_str(show_id),
+                    encode_number(0),
+                ]
+            }
+            ClaimParams::ArtistFollowers {
+                artist_id,
+                threshold,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_str(artist_id),
+                    encode_number(*threshold),
+                ]
+            }
+            ClaimParams::TrackMarkets {
+                track_id,
+                threshold,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_str(track_id),
+                    encode_number(*threshold as u64),
+                ]
+            }
+            ClaimParams::AlbumMarkets {
+                album_id,
+                threshold,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_str(album_id),
+                    encode_number(*threshold as u64),
+                ]
+            }
+            ClaimParams::ArtistMarkets {
+                artist_id,
+                threshold,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_str(artist_id),
+                    encode_number(*threshold as u64),
+                ]
+            }
+            ClaimParams::TrackNotRecentlyPlayed {
+                track_id,
+                after,
+                played_times,
+            } => (track_id, *after, *played_times as u64),
+            ClaimParams::ArtistNotRecentlyPlayed {
+                artist_id,
+                after,
+                played_times,
+            } => (artist_id, *after, *played_times as u64),
+            ClaimParams::GenreNotInTop {
+                genre,
+                time_range,
+                list_range,
+            } => (genre, *time_range as u64, *list_range as u64),
+            ClaimParams::PlaylistRecentlyUpdated {
+                playlist_id,
+                within_hours,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_str(playlist_id),
+                    encode_number(*within_hours),
+                ]
+            }
+            ClaimParams::PlaylistCreatedBefore {
+                playlist_id,
+                before,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_str(playlist_id),
+                    encode_number(*before),
+                ]
+            }
+            ClaimParams::FirstSavedTrackBefore { before } => ("", *before, 0),
+            ClaimParams::DeviceLocationHint { location } => (location, 0, 0),
+            ClaimParams::TrackRoyaltyFree { track_id } => (track_id, 0, 0),
+            ClaimParams::PlaylistCoCurated { playlist_id } => (playlist_id, 0, 0),
+            ClaimParams::CuratorTrackCount { playlist_id } => (playlist_id, 0, 0),
+            ClaimParams::PlaylistClean { playlist_id } => (playlist_id, 0, 0),
+            ClaimParams::DiscographyCompleted { artist_id } => (artist_id, 0, 0),
+            ClaimParams::TrackSpotifyExclusive { track_id } => (track_id, 0, 0),
+            ClaimParams::TrackPreviewAccessible { track_id } => (track_id, 0, 0),
+            ClaimParams::TrackHasMusicVideo { track_id } => (track_id, 0, 0),
+            ClaimParams::ListeningEvolved {
+                artist_id,
+                list_range,
+                mode,
+            } => (artist_id, *list_range as u64, *mode as u64),
+            ClaimParams::TopTracksMonoArtist {
+                artist_id,
+                time_range,
+                list_range,
+            } => (artist_id, *time_range as u64, *list_range as u64),
+            ClaimParams::TrackCoversOriginal {
+                track_id,
+                original_id,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_str(track_id),
+                    encode_str(original_id),
+                ]
+            }
+            ClaimParams::TrackSameRecording { track_id, other } => {
+                return vec![encode_str(key), encode_str(track_id), encode_str(other)]
+            }
+            ClaimParams::TopTracksDecades {
+                threshold,
+                time_range,
+                list_range,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_number(*threshold as u64),
+                    encode_number(*time_range as u64),
+                    encode_number(*list_range as u64),
+                ]
+            }
+            ClaimParams::TopTracksAllExplicit {
+                time_range,
+                list_range,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_number(*time_range as u64),
+                    encode_number(*list_range as u64),
+                ]
+            }
+            ClaimParams::TopTracksDecadeCount {
+                time_range,
+                decade,
+                list_range,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_number(*time_range as u64),
+                    encode_number(*decade as u64),
+                    encode_number(*list_range as u64),
+                ]
+            }
+            ClaimParams::ArtistCollaborations {
+                artist_id,
+                threshold,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_str(artist_id),
+                    encode_number(*threshold as u64),
+                ]
+            }
+            ClaimParams::GenreEntropy {
+                time_range,
+                threshold,
+                list_range,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_number(*time_range as u64),
+                    encode_number(*threshold as u64),
+                    encode_number(*list_range as u64),
+                ]
+            }
+            ClaimParams::TrackRepeatCount {
+                track_id,
+                threshold,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_str(track_id),
+                    encode_number(*threshold as u64),
+                ]
+            }
+            ClaimParams::SharedTopTrack {
+                track_id,
+                other_keys,
+            } => {
+                let mut inputs = vec![encode_str(key), encode_str(track_id)];
+                inputs.extend(other_keys.iter().map(|other| encode_str(other)));
+                return inputs;
+            }
+            ClaimParams::ArtistOnTour {
+                artist_id,
+                concert_artist_id,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_str(artist_id),
+                    encode_str(concert_artist_id),
+                ]
+            }
+            ClaimParams::EventInUserCity { artist_id, city } => {
+                return vec![encode_str(key), encode_str(artist_id), encode_str(city)]
+            }
+            ClaimParams::TrackInTvShow { track_id, show } => {
+                return vec![encode_str(key), encode_str(track_id), encode_str(show)]
+            }
+            ClaimParams::ArtistBiographyLength {
+                artist_id,
+                threshold,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_str(artist_id),
+                    encode_number(*threshold as u64),
+                ]
+            }
+            ClaimParams::TrackAddedBy {
+                playlist_id,
+                track_id,
+                curator_id,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_str(playlist_id),
+                    encode_str(track_id),
+                    encode_str(curator_id),
+                ]
+            }
+            ClaimParams::TrackSampling {
+                sampling_id,
+                sampled_id,
+            } => {
+                return vec![
+                    encode_str(key),
+                    encode_str(sampling_id),
+                    encode_str(sampled_id),
+                ]
+            }
+        };
+        vec![
+            encode_str(key),
+            encode_str(subject),
+            encode_number(third),
+            encode_number(fourth),
+        ]
+    }
+}
+
+/// The answer to a claim.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ClaimResponse {
+    pub values: Vec<String>,
+    /// Unix seconds.
+    pub evaluated_at: i64,
+    /// Unix seconds.
+    pub valid_until: i64,
+    /// Present when the oracle signs its answers; see [`crate::signing`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Any further fields the claim reports, such as a resolved event.
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// An entry of the oracle's claim registry, as returned by `list_claims`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ClaimInfo {
+    pub id: u16,
+    pub name: String,
+    pub version: u8,
+    pub deprecated: bool,
+    pub validity_secs: i64,
+}
+
+/// The answer to `rotate_token`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TokenRotated {
+    pub id: String,
+    /// Seconds the replaced token had left, `None` if it did not expire.
+    pub old_token_expires_in: Option<u64>,
+}
+
+/// A JSON-RPC client for one oracle instance.
+pub struct OracleClient {
+    url: String,
+    http: reqwest::Client,
+    next_id: AtomicU64,
+    admin_token: Option<String>,
+}
+
+impl OracleClient {
+    /// Creates a client for the oracle listening at `url`, e.g. `http://localhost:3000`.
+    pub fn new(url: impl Into<String>) -> Self {
+        OracleClient {
+            url: url.into(),
+            http: reqwest::Client::new(),
+            next_id: AtomicU64::new(1),
+            admin_token: None,
+        }
+    }
+
+    /// Sends `token` as the bearer token admin methods require.
+    pub fn with_admin_token(mut self, token: impl Into<String>) -> Self {
+        self.admin_token = Some(token.into());
+        self
+    }
+
+    async fn call<T>(&self, method: &str, params: Value) -> Result<T, ClientError>
+    where
+        T: DeserializeOwned,
+    {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        });
+        let mut http_request = self.http.post(&self.url).json(&request);
+        if let Some(token) = &self.admin_token {
+            http_request = http_request.bearer_auth(token);
+        }
+        let mut response: Value = http_request.send().await?.json().await?;
+
+        if let Some(error) = response.get_mut("error") {
+            let error = serde_json::from_value::<RpcError>(error.take())
+                .map_err(|e| ClientError::InvalidResponse(e.to_string()))?;
+            return Err(ClientError::Rpc(error));
+        }
+        let result = response
+            .get_mut("result")
+            .map(Value::take)
+            .ok_or_else(|| ClientError::InvalidResponse("missing result".to_string()))?;
+        serde_json::from_value(result).map_err(|e| ClientError::InvalidResponse(e.to_string()))
+    }
+
+    pub async fn store_key(&self, id: &str, token: &str) -> Result<String, ClientError> {
+        self.call("store_key", json!([id, token])).await
+    }
+
+    /// Replaces the token of `id` if the stored one, without its `Bearer ` scheme,
+    /// starts with `old_token_prefix` (at least its first 8 characters).
+    pub async fn rotate_token(
+        &self,
+        id: &str,
+        old_token_prefix: &str,
+        new_token: &str,
+    ) -> Result<TokenRotated, ClientError> {
+        self.call("rotate_token", json!([id, old_token_prefix, new_token]))
+            .await
+    }
+
+    /// Binds the token of Spotify account `account` to `id`; see
+    /// [`OracleClient::resolve_account_claim`].
+    pub async fn store_key_account(
+        &self,
+        id: &str,
+        account: &str,
+        token: &str,
+    ) -> Result<String, ClientError> {
+        self.call("store_key_account", json!([id, account, token]))
+            .await
+    }
+
+    pub async fn delete_key(&self, id: &str) -> Result<String, ClientError> {
+        self.call("delete_key", json!([id])).await
+    }
+
+    pub async fn store_reference_list(
+        &self,
+        id: &str,
+        track_ids: &[String],
+    ) -> Result<String, ClientError> {
+        self.call("store_reference_list", json!([id, track_ids]))
+            .await
+    }
+
+    /// Creates or replaces an event; `start` and `end` are Unix milliseconds.
+    pub async fn upsert_event(
+        &self,
+        id: &str,
+        track_id: &str,
+        start: i64,
+        end: i64,
+        ttl_secs: u64,
+    ) -> Result<String, ClientError> {
+        self.call("upsert_event", json!([id, track_id, start, end, ttl_secs]))
+            .await
+    }
+
+    pub async fn list_claims(&self) -> Result<Vec<ClaimInfo>, ClientError> {
+        self.call("list_claims", json!([])).await
+    }
+
+    pub async fn storage_usage(&self) -> Result<HashMap<String, KeyUsage>, ClientError> {
+        self.call("storage_usage", json!([])).await
+    }
+
+    pub async fn provider_usage(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<UsageRecord>, ClientError> {
+        self.call("provider_usage", json!([from.to_string(), to.to_string()]))
+            .await
+    }
+
+    /// Returns up to `limit` recent failures per provider, newest first.
+    pub async fn recent_provider_errors(
+        &self,
+        limit: usize,
+    ) -> Result<HashMap<String, ProviderFailures>, ClientError> {
+        self.call("recent_provider_errors", json!([limit])).await
+    }
+
+    pub async fn health(&self) -> Result<Health, ClientError> {
+        self.call("health", json!([])).await
+    }
+
+    /// Resolves a claim for the API key `key`, encoding its inputs as a circuit would.
+    pub async fn resolve_claim(
+        &self,
+        key: &str,
+        claim: &ClaimParams,
+    ) -> Result<ClaimResponse, ClientError> {
+        let params = json!([{"function": claim.function(), "inputs": claim.inputs(key)}]);
+        self.call("resolve_foreign_call", params).await
+    }
+
+    /// Resolves a claim with the token of account `account` bound to `key`.
+    ///
+    /// The key input then holds two arrays, the key's characters and the
+    /// account name's.
+    pub async fn resolve_account_claim(
+        &self,
+        key: &str,
+        account: &str,
+        claim: &ClaimParams,
+    ) -> Result<ClaimResponse, ClientError> {
+        let mut inputs: Vec<Value> = claim.inputs(key).into_iter().map(Value::from).collect();
+        inputs[0] = json!([encode_str(key), encode_str(account)]);
+        let params = json!([{"function": claim.function(), "inputs": inputs}]);
+        self.call("resolve_foreign_call", params).await
+    }
+}
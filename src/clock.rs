@@ -0,0 +1,30 @@
+//! The time claim answers are stamped with.
+//!
+//! Claim evaluation reads the time through [`Clock`] so that validity windows
+//! can be pinned in tests; the server itself runs on [`SystemClock`].
+
+use chrono::Utc;
+
+/// A source of the current time.
+pub trait Clock: Send + Sync {
+    /// The current time in Unix seconds.
+    fn now(&self) -> i64;
+}
+
+/// The system's wall clock.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        Utc::now().timestamp()
+    }
+}
+
+/// A clock stopped at a given Unix second.
+pub struct FixedClock(pub i64);
+
+impl Clock for FixedClock {
+    fn now(&self) -> i64 {
+        self.0
+    }
+}
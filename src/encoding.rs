@@ -0,0 +1,64 @@
+//! Encoding of claim inputs as Noir passes them to the oracle.
+//!
+//! Every input is an array of `0x`-prefixed hex fields: strings carry one field per
+//! character, numbers a single field. The server decodes with the `hex_to_*`
+//! functions and the client encodes with `encode_*`, so both sides share one
+//! definition of the format.
+
+use serde_json::Value;
+use std::num::ParseIntError;
+
+use crate::leniency::{self, Leniency};
+
+/// Encodes a string as one hex field per character.
+pub fn encode_str(value: &str) -> Vec<String> {
+    value
+        .chars()
+        .map(|c| format!("0x{:02x}", c as u32))
+        .collect()
+}
+
+/// Encodes a number as a single hex field.
+pub fn encode_number(value: u64) -> Vec<String> {
+    vec![format!("0x{:x}", value)]
+}
+
+/// Parses a `0x`-prefixed hex field with `parse`.
+///
+/// Anything else decodes as zero, reported as [`Leniency::InvalidHexField`].
+fn hex_field<T: Default>(
+    hex_string: &Value,
+    parse: fn(&str, u32) -> Result<T, ParseIntError>,
+) -> T {
+    hex_string
+        .as_str()
+        .and_then(|s| s.strip_prefix("0x"))
+        .and_then(|digits| parse(digits, 16).ok())
+        .unwrap_or_else(|| {
+            leniency::note(Leniency::InvalidHexField);
+            T::default()
+        })
+}
+
+pub fn hex_to_u8(hex_string: &Value) -> u8 {
+    hex_field(hex_string, u8::from_str_radix)
+}
+
+pub fn hex_to_u16(hex_string: &Value) -> u16 {
+    hex_field(hex_string, u16::from_str_radix)
+}
+
+pub fn hex_to_u32(hex_string: &Value) -> u32 {
+    hex_field(hex_string, u32::from_str_radix)
+}
+
+pub fn hex_to_u64(hex_string: &Value) -> u64 {
+    hex_field(hex_string, u64::from_str_radix)
+}
+
+pub fn hex_to_char(hex_string: &Value) -> char {
+    char::from_u32(hex_field(hex_string, u32::from_str_radix)).unwrap_or_else(|| {
+        leniency::note(Leniency::InvalidHexField);
+        '\0'
+    })
+}
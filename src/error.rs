@@ -0,0 +1,60 @@
+//! Errors the oracle reports with a machine-readable reason.
+//!
+//! Each variant becomes a JSON-RPC error whose `data.reason` names it, so
+//! callers can tell a refusal apart from a failed lookup without parsing the
+//! message.
+
+use jsonrpc_core::{Error, ErrorCode};
+use serde_json::json;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OracleError {
+    /// The user is in a market listed in `ORACLE_BLOCKED_MARKETS`.
+    GeoRestricted { market: String },
+    /// Markets are blocked but Spotify withheld the user's market, which it
+    /// only returns with the `user-read-private` scope.
+    MarketUnknown,
+}
+
+impl OracleError {
+    /// The `data.reason` of the JSON-RPC error.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            OracleError::GeoRestricted { .. } => "geo_restricted",
+            OracleError::MarketUnknown => "market_unknown",
+        }
+    }
+}
+
+impl fmt::Display for OracleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OracleError::GeoRestricted { market } => {
+                write!(f, "Oracle access is not available in market {}", market)
+            }
+            OracleError::MarketUnknown => write!(
+                f,
+                "The user's market is unknown; the user-read-private scope is required"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OracleError {}
+
+impl From<OracleError> for Error {
+    fn from(error: OracleError) -> Self {
+        let data = match &error {
+            OracleError::GeoRestricted { market } => {
+                json!({ "reason": error.reason(), "market": market })
+            }
+            OracleError::MarketUnknown => json!({ "reason": error.reason() }),
+        };
+        Error {
+            code: ErrorCode::InvalidParams,
+            message: error.to_string(),
+            data: Some(data),
+        }
+    }
+}
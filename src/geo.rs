@@ -0,0 +1,66 @@
+//! Geographic restriction of claims by the user's Spotify market.
+//!
+//! Markets listed in `ORACLE_BLOCKED_MARKETS` (comma-separated ISO 3166-1 alpha-2
+//! codes) may not resolve claims, for music licensing compliance. The market is
+//! the `country` of the user's `GET /v1/me` profile, which Spotify only returns
+//! with the `user-read-private` scope. The check fails closed: while any market
+//! is blocked, a token without that scope cannot resolve claims at all, and is
+//! refused with [`OracleError::MarketUnknown`]. With no blocklist nothing is
+//! checked or fetched.
+//!
+//! Every user a claim is about is checked, once, before the claim's handler runs.
+
+use jsonrpc_core::Error;
+use std::collections::HashSet;
+use std::env;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use crate::cache::TtlCache;
+use crate::error::OracleError;
+use crate::query_builder::current_user_query_builder;
+
+/// How long a session's market is reused; it only changes when the user moves.
+const USER_MARKET_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// The market of each session, keyed by token; `None` when Spotify withheld it.
+static USER_MARKETS: LazyLock<TtlCache<String, Option<String>>> =
+    LazyLock::new(|| TtlCache::new(USER_MARKET_TTL));
+
+/// The markets configured in `ORACLE_BLOCKED_MARKETS`, upper-cased.
+pub fn blocked_markets() -> HashSet<String> {
+    env::var("ORACLE_BLOCKED_MARKETS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|market| market.trim().to_uppercase())
+        .filter(|market| !market.is_empty())
+        .collect()
+}
+
+/// Fails unless the user of `authorization` is outside every blocked market.
+pub async fn check_market(authorization: String) -> Result<(), Error> {
+    let blocked = blocked_markets();
+    if blocked.is_empty() {
+        return Ok(());
+    }
+
+    let market = match USER_MARKETS.get(&authorization) {
+        Some(market) => market,
+        None => {
+            let user = current_user_query_builder(authorization.clone())
+                .await
+                .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+            let market = user.country.map(|country| country.to_uppercase());
+            USER_MARKETS.insert(authorization, market.clone());
+            market
+        }
+    };
+
+    let Some(market) = market else {
+        return Err(OracleError::MarketUnknown.into());
+    };
+    if blocked.contains(&market) {
+        return Err(OracleError::GeoRestricted { market }.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,179 @@
+//! Strict mode: every silent fallback the oracle applies, and the policy that
+//! decides whether a fallback may apply or must fail the claim instead.
+//!
+//! The fallbacks below are right in production but hide assumptions during circuit
+//! development. With `STRICT_MODE=on` every claim runs strictly; with
+//! `STRICT_MODE=allow` a `resolve_foreign_call` request opts in with `"strict": true`.
+//! A strict claim that hits any [`Leniency`] fails with an error naming it.
+//!
+//! Code that applies a fallback reports it through [`check`] where it can return an
+//! error, or [`note`] where it cannot; the claim is failed once its handler returns.
+
+use jsonrpc_core::{Error, ErrorCode};
+use serde_json::json;
+use std::env;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// A silent fallback the oracle applies outside of strict mode.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Leniency {
+    /// A claim input field that is not `0x`-prefixed hex is decoded as 0 (or `\0`).
+    InvalidHexField,
+    /// A zero playthrough gap is replaced by the configured default gap.
+    DefaultPlaythroughGap,
+    /// Empty entries of an inline reference list are dropped.
+    EmptyReferenceEntries,
+    /// Unavailable tracks and local files on a playlist are skipped.
+    UnavailablePlaylistItems,
+    /// A play without a primary artist is treated as a play by another artist.
+    MissingPrimaryArtist,
+    /// A response is judged not to be Spotify's JSON API from its content type or
+    /// an HTML-looking body.
+    ResponseFormatHeuristic,
+    /// An unparsable `CLAIM_VALIDITY_SECS_<FUNCTION>` override is ignored.
+    InvalidValidityOverride,
+    /// A playlist a track was played from that the user cannot read is treated as
+    /// not matching.
+    UnreadableContextPlaylist,
+}
+
+/// Every leniency, so the list can be audited in one place.
+pub const LENIENCIES: &[Leniency] = &[
+    Leniency::InvalidHexField,
+    Leniency::DefaultPlaythroughGap,
+    Leniency::EmptyReferenceEntries,
+    Leniency::UnavailablePlaylistItems,
+    Leniency::MissingPrimaryArtist,
+    Leniency::ResponseFormatHeuristic,
+    Leniency::InvalidValidityOverride,
+    Leniency::UnreadableContextPlaylist,
+];
+
+impl Leniency {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Leniency::InvalidHexField => "invalid_hex_field",
+            Leniency::DefaultPlaythroughGap => "default_playthrough_gap",
+            Leniency::EmptyReferenceEntries => "empty_reference_entries",
+            Leniency::UnavailablePlaylistItems => "unavailable_playlist_items",
+            Leniency::MissingPrimaryArtist => "missing_primary_artist",
+            Leniency::ResponseFormatHeuristic => "response_format_heuristic",
+            Leniency::InvalidValidityOverride => "invalid_validity_override",
+            Leniency::UnreadableContextPlaylist => "unreadable_context_playlist",
+        }
+    }
+}
+
+/// Returned by [`check`] when a strict claim would have applied `leniency`.
+#[derive(Debug)]
+pub struct LeniencyError {
+    pub leniency: Leniency,
+}
+
+impl fmt::Display for LeniencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Strict mode: {} would have applied",
+            self.leniency.name()
+        )
+    }
+}
+
+impl std::error::Error for LeniencyError {}
+
+/// How `STRICT_MODE` lets claims run.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StrictMode {
+    Off,
+    /// Requests may opt in with `"strict": true`.
+    Allow,
+    On,
+}
+
+impl StrictMode {
+    pub fn from_env() -> Self {
+        match env::var("STRICT_MODE").as_deref() {
+            Ok("on") => StrictMode::On,
+            Ok("allow") => StrictMode::Allow,
+            _ => StrictMode::Off,
+        }
+    }
+}
+
+/// The leniency policy of one claim evaluation.
+#[derive(Debug)]
+pub struct Policy {
+    strict: bool,
+    applied: Mutex<Vec<Leniency>>,
+}
+
+tokio::task_local! {
+    /// The policy of the claim being resolved by the current task.
+    pub static POLICY: Arc<Policy>;
+}
+
+impl Policy {
+    /// Builds the policy for a request that asked for strict mode or not.
+    ///
+    /// Asking for strict mode when the configuration does not allow it is an
+    /// error rather than being ignored.
+    pub fn for_request(mode: StrictMode, requested: bool) -> Result<Self, Error> {
+        let strict = match (mode, requested) {
+            (StrictMode::On, _) | (StrictMode::Allow, true) => true,
+            (StrictMode::Off, true) => {
+                return Err(Error::invalid_params(
+                    "Strict mode is not enabled on this oracle",
+                ))
+            }
+            _ => false,
+        };
+        Ok(Policy {
+            strict,
+            applied: Mutex::default(),
+        })
+    }
+
+    /// Whether a claim evaluated under this policy may use `leniency`.
+    fn apply(&self, leniency: Leniency) -> Result<(), LeniencyError> {
+        let mut applied = self.applied.lock().unwrap_or_else(|e| e.into_inner());
+        if !applied.contains(&leniency) {
+            applied.push(leniency);
+        }
+        if self.strict {
+            return Err(LeniencyError { leniency });
+        }
+        Ok(())
+    }
+
+    /// The error a strict claim fails with, if it applied any leniency.
+    pub fn violation(&self) -> Option<Error> {
+        if !self.strict {
+            return None;
+        }
+        let applied = self.applied.lock().unwrap_or_else(|e| e.into_inner());
+        let leniency = *applied.first()?;
+        Some(Error {
+            code: ErrorCode::InvalidParams,
+            message: LeniencyError { leniency }.to_string(),
+            data: Some(json!({ "leniency": leniency.name() })),
+        })
+    }
+}
+
+/// Reports that `leniency` is about to apply; fails in strict mode.
+///
+/// Outside of a claim evaluation the fallback always applies.
+pub fn check(leniency: Leniency) -> Result<(), LeniencyError> {
+    POLICY
+        .try_with(|policy| policy.apply(leniency))
+        .unwrap_or(Ok(()))
+}
+
+/// Reports that `leniency` applied where no error can be returned; a strict
+/// claim fails once its handler returns.
+pub fn note(leniency: Leniency) {
+    // The violation is picked up through `Policy::violation`.
+    let _ = check(leniency);
+}
@@ -0,0 +1,19 @@
+pub mod admin;
+pub mod cache;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod clock;
+pub mod encoding;
+pub mod error;
+pub mod geo;
+pub mod leniency;
+pub mod middleware;
+pub mod providers;
+pub mod query_builder;
+pub mod redis;
+pub mod rpc_methods;
+pub mod server;
+pub mod signing;
+pub mod telemetry;
+pub mod types;
+pub mod usage;
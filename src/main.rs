@@ -1,17 +1,30 @@
 use dotenv::dotenv;
-use rpc_methods::create_io;
-use server::create_server;
-
-pub mod middleware;
-pub mod query_builder;
-pub mod redis;
-pub mod rpc_methods;
-pub mod server;
-pub mod types;
+use noir_oracles::redis::{check_encryption_config, check_redis_hostname};
+use noir_oracles::rpc_methods::create_io;
+use noir_oracles::server::create_server;
+use noir_oracles::signing::check_signing_config;
+use noir_oracles::usage::spawn_flusher;
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
+    let redis_host_name = std::env::var("REDIS_HOSTNAME").unwrap_or_default();
+    if let Err(e) = check_redis_hostname(&redis_host_name) {
+        eprintln!("Invalid Redis configuration: {}", e);
+        std::process::exit(1);
+    }
+    let encryption_key = std::env::var("ORACLE_ENCRYPTION_KEY").ok();
+    let allow_plaintext = std::env::var("ORACLE_ALLOW_PLAINTEXT").ok();
+    if let Err(e) = check_encryption_config(encryption_key.as_deref(), allow_plaintext.as_deref()) {
+        eprintln!("Invalid token encryption configuration: {}", e);
+        std::process::exit(1);
+    }
+    let signing_key = std::env::var("ORACLE_SIGNING_KEY").ok();
+    if let Err(e) = check_signing_config(signing_key.as_deref()) {
+        eprintln!("Invalid answer signing configuration: {}", e);
+        std::process::exit(1);
+    }
+    spawn_flusher();
     let io = create_io();
     create_server(io);
 }
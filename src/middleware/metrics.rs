@@ -0,0 +1,39 @@
+use jsonrpc_http_server::{
+    hyper::{header, Body, Method, Request, Response},
+    RequestMiddleware, RequestMiddlewareAction,
+};
+
+use crate::usage::prometheus_metrics;
+
+/// Path Prometheus scrapes.
+pub const METRICS_PATH: &str = "/metrics";
+
+/// Answers `GET /metrics` with the oracle's Prometheus metrics; every other
+/// request goes through `inner`.
+///
+/// The metrics carry no user identifiers, so scraping needs no token.
+pub struct MetricsMiddleware<M> {
+    inner: M,
+}
+
+impl<M> MetricsMiddleware<M> {
+    pub fn new(inner: M) -> Self {
+        MetricsMiddleware { inner }
+    }
+}
+
+impl<M: RequestMiddleware> RequestMiddleware for MetricsMiddleware<M> {
+    fn on_request(&self, request: Request<Body>) -> RequestMiddlewareAction {
+        if request.method() != Method::GET || request.uri().path() != METRICS_PATH {
+            return self.inner.on_request(request);
+        }
+        let response = Response::builder()
+            .header(header::CONTENT_TYPE, "text/plain; version=0.0.4")
+            .body(Body::from(prometheus_metrics()))
+            .unwrap_or_default();
+        RequestMiddlewareAction::Respond {
+            should_validate_hosts: true,
+            response: Box::pin(async move { Ok(response) }),
+        }
+    }
+}
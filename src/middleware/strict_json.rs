@@ -0,0 +1,251 @@
+use jsonrpc_core::IoHandler;
+use jsonrpc_http_server::{
+    hyper::{self, body::HttpBody, header, Body, Method, Request, Response, StatusCode},
+    RequestMiddleware, RequestMiddlewareAction,
+};
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{error::Category, json};
+use std::collections::HashSet;
+use std::env;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::admin::AUTHORIZATION;
+use crate::telemetry::{TraceContext, TRACE};
+use crate::usage::REQUEST_ID;
+
+static NEXT_REQUEST: AtomicU64 = AtomicU64::new(0);
+
+/// Largest request body read, in bytes; the same default jsonrpc-http-server uses.
+pub const MAX_REQUEST_BODY_SIZE: usize = 5 * 1024 * 1024;
+
+/// Reads POST bodies itself and rejects those containing an object with the
+/// same key twice.
+///
+/// serde_json keeps the last of duplicate keys, so `{"function": "a", "function": "b"}`
+/// would run `b` while a frontend that signed or logged the body may have seen `a`.
+/// Every object in the body is checked, at any depth, before it reaches jsonrpc-core.
+/// The check is on by default; set `STRICT_JSON_PARSING=false` to turn it off.
+///
+/// POST requests are handed to `io` directly, so jsonrpc-http-server's own body
+/// limit does not apply to them; bodies over `max_body_size` are refused and
+/// bodies that are not UTF-8 are parse errors. Every POST gets an ID, returned
+/// in `x-request-id` and attached to any provider failures it causes, and
+/// continues the trace of its `traceparent` header; its `Authorization` header
+/// is what admin methods check. Everything else goes through `inner` as before.
+pub struct StrictJsonMiddleware<M> {
+    io: IoHandler,
+    inner: M,
+    strict: bool,
+    max_body_size: usize,
+}
+
+impl<M> StrictJsonMiddleware<M> {
+    pub fn new(io: IoHandler, inner: M) -> Self {
+        let strict = !matches!(
+            env::var("STRICT_JSON_PARSING").as_deref(),
+            Ok("false") | Ok("0")
+        );
+        StrictJsonMiddleware {
+            io,
+            inner,
+            strict,
+            max_body_size: MAX_REQUEST_BODY_SIZE,
+        }
+    }
+
+    /// Overrides whether duplicate keys are rejected, regardless of `STRICT_JSON_PARSING`.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets the largest request body read, in bytes.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+}
+
+impl<M: RequestMiddleware> RequestMiddleware for StrictJsonMiddleware<M> {
+    fn on_request(&self, request: Request<Body>) -> RequestMiddlewareAction {
+        let request = match self.inner.on_request(request) {
+            RequestMiddlewareAction::Proceed { request, .. }
+                if request.method() == Method::POST =>
+            {
+                request
+            }
+            action => return action,
+        };
+
+        let io = self.io.clone();
+        let strict = self.strict;
+        let max_body_size = self.max_body_size;
+        let header = |name: &str| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let trace = TraceContext::from_headers(
+            header("traceparent").as_deref(),
+            header("tracestate").as_deref(),
+        );
+        let authorization = header("authorization");
+        let request_id = format!(
+            "{:x}-{:x}",
+            chrono::Utc::now().timestamp_millis(),
+            NEXT_REQUEST.fetch_add(1, Ordering::Relaxed)
+        );
+        RequestMiddlewareAction::Respond {
+            should_validate_hosts: true,
+            response: Box::pin(async move {
+                let Some(body) = read_body(request.into_body(), max_body_size).await? else {
+                    return Ok(error_response(
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                        "Request body is too large",
+                        &request_id,
+                    ));
+                };
+                let Ok(body) = String::from_utf8(body) else {
+                    let reply = rpc_error(-32700, "Parse error".to_string());
+                    return Ok(json_response(reply, &request_id));
+                };
+
+                let duplicate = if strict {
+                    find_duplicate_key(&body)
+                } else {
+                    None
+                };
+                let reply = match duplicate {
+                    Some(duplicate) => {
+                        Some(rpc_error(-32600, format!("Invalid request: {}", duplicate)))
+                    }
+                    None => {
+                        let handled =
+                            REQUEST_ID.scope(request_id.clone(), io.handle_request(&body));
+                        let handled = AUTHORIZATION.scope(authorization, handled);
+                        TRACE.scope(trace, handled).await
+                    }
+                };
+                Ok(json_response(reply.unwrap_or_default(), &request_id))
+            }),
+        }
+    }
+}
+
+/// Reads `body` up to `limit` bytes; `None` when it is longer.
+async fn read_body(mut body: Body, limit: usize) -> hyper::Result<Option<Vec<u8>>> {
+    let announced = body.size_hint().lower();
+    if usize::try_from(announced).map_or(true, |announced| announced > limit) {
+        return Ok(None);
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if bytes.len() + chunk.len() > limit {
+            return Ok(None);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(Some(bytes))
+}
+
+/// A JSON-RPC error reply to a request whose ID could not be read.
+fn rpc_error(code: i64, message: String) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "error": {"code": code, "message": message},
+        "id": null,
+    })
+    .to_string()
+}
+
+fn json_response(body: String, request_id: &str) -> Response<Body> {
+    Response::builder()
+        .header("x-request-id", request_id)
+        .header(header::CONTENT_TYPE, "application/json; charset=utf-8")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(Body::from(body))
+        .unwrap_or_default()
+}
+
+fn error_response(status: StatusCode, message: &str, request_id: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("x-request-id", request_id)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(Body::from(message.to_string()))
+        .unwrap_or_default()
+}
+
+/// Returns a description of the first duplicate key in `body`, if any.
+///
+/// Bodies that are not valid JSON pass; jsonrpc-core reports those as parse errors.
+pub fn find_duplicate_key(body: &str) -> Option<String> {
+    match serde_json::from_str::<UniqueKeys>(body) {
+        Err(e) if e.classify() == Category::Data => Some(e.to_string()),
+        _ => None,
+    }
+}
+
+/// A JSON value whose objects have unique keys; only used for validation.
+struct UniqueKeys;
+
+impl<'de> Deserialize<'de> for UniqueKeys {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(UniqueKeysVisitor)
+    }
+}
+
+struct UniqueKeysVisitor;
+
+impl<'de> Visitor<'de> for UniqueKeysVisitor {
+    type Value = UniqueKeys;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<UniqueKeys, E> {
+        Ok(UniqueKeys)
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<UniqueKeys, E> {
+        Ok(UniqueKeys)
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<UniqueKeys, E> {
+        Ok(UniqueKeys)
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<UniqueKeys, E> {
+        Ok(UniqueKeys)
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<UniqueKeys, E> {
+        Ok(UniqueKeys)
+    }
+
+    fn visit_unit<E>(self) -> Result<UniqueKeys, E> {
+        Ok(UniqueKeys)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<UniqueKeys, A::Error> {
+        while seq.next_element::<UniqueKeys>()?.is_some() {}
+        Ok(UniqueKeys)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<UniqueKeys, A::Error> {
+        let mut keys = HashSet::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if !keys.insert(key.clone()) {
+                return Err(de::Error::custom(format!("duplicate key `{}`", key)));
+            }
+            map.next_value::<UniqueKeys>()?;
+        }
+        Ok(UniqueKeys)
+    }
+}
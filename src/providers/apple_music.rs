@@ -0,0 +1,93 @@
+use reqwest::Client;
+use serde::Deserialize;
+use std::env;
+use std::error::Error;
+use std::sync::LazyLock;
+
+use crate::usage::{record, Outcome};
+
+const DEFAULT_API_URL: &str = "https://api.music.apple.com";
+const DEFAULT_STOREFRONT: &str = "us";
+
+/// Shared so lookups reuse connections.
+static CLIENT: LazyLock<Client> = LazyLock::new(Client::new);
+
+#[derive(Deserialize)]
+struct SongsResponse {
+    data: Vec<SongRef>,
+}
+
+#[derive(Deserialize)]
+struct SongRef {
+    id: String,
+}
+
+/// Looks up songs in the Apple Music catalog.
+///
+/// Requests are signed with a developer token from `APPLE_MUSIC_DEVELOPER_TOKEN`,
+/// a JWT issued for the oracle's MusicKit key. The catalog searched is the
+/// `APPLE_MUSIC_STOREFRONT` storefront (`us` by default), and the base URL is
+/// `APPLE_MUSIC_API_URL`, `https://api.music.apple.com` by default.
+pub struct AppleMusicProvider {
+    base_url: String,
+    storefront: String,
+    developer_token: String,
+}
+
+impl AppleMusicProvider {
+    /// Fails when no developer token is configured.
+    pub fn from_env() -> Result<Self, String> {
+        let developer_token = env::var("APPLE_MUSIC_DEVELOPER_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| String::from("No Apple Music developer token is configured"))?;
+        let setting = |name: &str, default: &str| {
+            env::var(name)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .unwrap_or_else(|| default.to_string())
+        };
+        Ok(AppleMusicProvider {
+            base_url: setting("APPLE_MUSIC_API_URL", DEFAULT_API_URL)
+                .trim_end_matches('/')
+                .to_string(),
+            storefront: setting("APPLE_MUSIC_STOREFRONT", DEFAULT_STOREFRONT),
+            developer_token,
+        })
+    }
+
+    /// IDs of the catalog songs with an ISRC.
+    pub async fn songs_by_isrc(&self, isrc: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let template = "/v1/catalog/{storefront}/songs";
+        let url = format!(
+            "{}/v1/catalog/{}/songs?filter[isrc]={}",
+            self.base_url, self.storefront, isrc
+        );
+        let response = match CLIENT
+            .get(&url)
+            .bearer_auth(&self.developer_token)
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                record("apple_music", template, Outcome::Transport, None);
+                return Err(format!("Apple Music request failed: {}", e).into());
+            }
+        };
+
+        let status = response.status();
+        record(
+            "apple_music",
+            template,
+            Outcome::from_status(status),
+            Some(status.as_u16()),
+        );
+
+        if !status.is_success() {
+            return Err(format!("Apple Music request failed with status: {}", status).into());
+        }
+        let songs = response.json::<SongsResponse>().await?;
+        Ok(songs.data.into_iter().map(|song| song.id).collect())
+    }
+}
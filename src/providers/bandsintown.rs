@@ -0,0 +1,134 @@
+use reqwest::{Client, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::env;
+use std::error::Error;
+use std::sync::LazyLock;
+
+use crate::usage::{record, Outcome};
+
+const DEFAULT_API_URL: &str = "https://rest.bandsintown.com";
+
+/// Shared so lookups reuse connections.
+static CLIENT: LazyLock<Client> = LazyLock::new(Client::new);
+
+#[derive(Deserialize)]
+struct ArtistLookup {
+    id: Option<String>,
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct EventRef {}
+
+/// Looks up artists and their events on the Bandsintown API (v3).
+///
+/// Requests carry the `BANDSINTOWN_APP_ID` the oracle was registered with. The
+/// base URL is `BANDSINTOWN_API_URL`, `https://rest.bandsintown.com` by default.
+pub struct BandsintownProvider {
+    base_url: String,
+    app_id: String,
+}
+
+impl BandsintownProvider {
+    /// `None` when no app ID is configured.
+    pub fn from_env() -> Option<Self> {
+        let app_id = env::var("BANDSINTOWN_APP_ID")
+            .ok()
+            .filter(|app_id| !app_id.is_empty())?;
+        let base_url = env::var("BANDSINTOWN_API_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| DEFAULT_API_URL.to_string());
+        Some(BandsintownProvider {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            app_id,
+        })
+    }
+
+    /// Fetches the `segments` under the base URL as JSON; a 404 yields `None`.
+    ///
+    /// Calls are recorded under `template` so names and IDs do not end up in
+    /// usage labels.
+    async fn get<T: DeserializeOwned>(
+        &self,
+        segments: &[&str],
+        query: &[(&str, &str)],
+        template: &str,
+    ) -> Result<Option<T>, Box<dyn Error>> {
+        let mut url = Url::parse(&self.base_url)?;
+        url.path_segments_mut()
+            .map_err(|_| "Invalid Bandsintown API URL")?
+            .pop_if_empty()
+            .extend(segments);
+        url.query_pairs_mut()
+            .append_pair("app_id", &self.app_id)
+            .extend_pairs(query);
+
+        let response = match CLIENT.get(url).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                record("bandsintown", template, Outcome::Transport, None);
+                return Err(format!("Bandsintown request failed: {}", e).into());
+            }
+        };
+
+        let status = response.status();
+        record(
+            "bandsintown",
+            template,
+            Outcome::from_status(status),
+            Some(status.as_u16()),
+        );
+
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(format!("Bandsintown request failed with status: {}", status).into());
+        }
+        Ok(Some(response.json::<T>().await?))
+    }
+
+    /// The Bandsintown ID of the artist with exactly this name, ignoring case.
+    pub async fn artist_id_by_name(&self, name: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let artist = self
+            .get::<ArtistLookup>(&["artists", name], &[], "/artists/{name}")
+            .await?;
+        Ok(artist
+            .filter(|artist| {
+                artist
+                    .name
+                    .as_deref()
+                    .is_some_and(|found| found.eq_ignore_ascii_case(name))
+            })
+            .and_then(|artist| artist.id))
+    }
+
+    /// The name of the artist with a Bandsintown ID, or `None` for an unknown ID.
+    pub async fn artist_name(&self, artist_id: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let artist = self
+            .get::<ArtistLookup>(
+                &["artists", &format!("id_{}", artist_id)],
+                &[],
+                "/artists/{id}",
+            )
+            .await?;
+        Ok(artist.and_then(|artist| artist.name))
+    }
+
+    /// The number of upcoming events of an artist, or `None` for an unknown ID.
+    pub async fn upcoming_event_count(
+        &self,
+        artist_id: &str,
+    ) -> Result<Option<usize>, Box<dyn Error>> {
+        let events = self
+            .get::<Vec<EventRef>>(
+                &["artists", &format!("id_{}", artist_id), "events"],
+                &[("date", "upcoming")],
+                "/artists/{id}/events",
+            )
+            .await?;
+        Ok(events.map(|events| events.len()))
+    }
+}
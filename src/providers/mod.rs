@@ -0,0 +1,8 @@
+//! Clients for metadata providers other than Spotify.
+
+pub mod apple_music;
+pub mod bandsintown;
+pub mod musicbrainz;
+pub mod songkick;
+pub mod tunefind;
+pub mod youtube;
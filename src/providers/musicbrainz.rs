@@ -0,0 +1,237 @@
+use reqwest::{Client, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::env;
+use std::error::Error;
+use std::sync::LazyLock;
+
+use crate::usage::{record, Outcome};
+
+const DEFAULT_API_URL: &str = "https://musicbrainz.org";
+
+/// MusicBrainz rejects requests without an identifying user agent.
+const USER_AGENT: &str = concat!(
+    "noir-oracles/",
+    env!("CARGO_PKG_VERSION"),
+    " ( https://github.com/Beat-Bridge/noir-oracles- )"
+);
+
+/// Shared so lookups reuse connections; MusicBrainz allows about one request per second.
+static CLIENT: LazyLock<Client> = LazyLock::new(|| {
+    Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .unwrap_or_default()
+});
+
+/// Whether `id` has the form of an MBID, a UUID.
+pub fn is_mbid(id: &str) -> bool {
+    id.len() == 36
+        && id.chars().enumerate().all(|(i, c)| {
+            matches!(i, 8 | 13 | 18 | 23) == (c == '-') && (c == '-' || c.is_ascii_hexdigit())
+        })
+}
+
+/// A work a recording is a performance of.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PerformedWork {
+    /// MBID of the work.
+    pub work_id: String,
+    /// Whether MusicBrainz marks the performance as a cover.
+    pub cover: bool,
+}
+
+#[derive(Deserialize)]
+struct IsrcLookup {
+    recordings: Vec<RecordingRef>,
+}
+
+#[derive(Deserialize)]
+struct RecordingRef {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct RecordingLookup {
+    #[serde(default)]
+    relations: Vec<Relation>,
+}
+
+#[derive(Deserialize)]
+struct Relation {
+    r#type: String,
+    #[serde(default)]
+    attributes: Vec<String>,
+    work: Option<WorkRef>,
+}
+
+#[derive(Deserialize)]
+struct WorkRef {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct UrlLookup {
+    #[serde(default)]
+    relations: Vec<UrlRelation>,
+}
+
+#[derive(Deserialize)]
+struct UrlRelation {
+    artist: Option<ArtistRef>,
+}
+
+#[derive(Deserialize)]
+struct ArtistRef {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct AnnotationSearch {
+    #[serde(default)]
+    annotations: Vec<Annotation>,
+}
+
+#[derive(Deserialize)]
+struct Annotation {
+    entity: String,
+    r#type: String,
+    text: String,
+}
+
+/// Looks up recordings and works on the MusicBrainz web service (API v2).
+///
+/// The base URL is `MUSICBRAINZ_API_URL`, `https://musicbrainz.org` by default.
+pub struct MusicBrainzProvider {
+    base_url: String,
+}
+
+impl MusicBrainzProvider {
+    pub fn from_env() -> Self {
+        let base_url = env::var("MUSICBRAINZ_API_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| DEFAULT_API_URL.to_string());
+        MusicBrainzProvider {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Fetches `path` with `query` as JSON; a 404 yields `None`.
+    ///
+    /// Calls are recorded under `template` so IDs do not end up in usage labels.
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        template: &str,
+    ) -> Result<Option<T>, Box<dyn Error>> {
+        let mut url = Url::parse(&format!("{}{}", self.base_url, path))?;
+        url.query_pairs_mut()
+            .extend_pairs(query)
+            .append_pair("fmt", "json");
+        let response = match CLIENT.get(url).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                record("musicbrainz", template, Outcome::Transport, None);
+                return Err(format!("MusicBrainz request failed: {}", e).into());
+            }
+        };
+
+        let status = response.status();
+        record(
+            "musicbrainz",
+            template,
+            Outcome::from_status(status),
+            Some(status.as_u16()),
+        );
+
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(format!("MusicBrainz request failed with status: {}", status).into());
+        }
+        Ok(Some(response.json::<T>().await?))
+    }
+
+    /// MBIDs of the recordings MusicBrainz lists for an ISRC.
+    pub async fn recordings_by_isrc(&self, isrc: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let lookup = self
+            .get::<IsrcLookup>(&format!("/ws/2/isrc/{}", isrc), &[], "/ws/2/isrc/{isrc}")
+            .await?;
+        Ok(lookup
+            .map(|lookup| lookup.recordings.into_iter().map(|r| r.id).collect())
+            .unwrap_or_default())
+    }
+
+    /// The works a recording performs, or `None` for an unknown recording.
+    pub async fn performed_works(
+        &self,
+        recording_id: &str,
+    ) -> Result<Option<Vec<PerformedWork>>, Box<dyn Error>> {
+        let lookup = self
+            .get::<RecordingLookup>(
+                &format!("/ws/2/recording/{}", recording_id),
+                &[("inc", "work-rels")],
+                "/ws/2/recording/{mbid}",
+            )
+            .await?;
+        Ok(lookup.map(|lookup| {
+            lookup
+                .relations
+                .into_iter()
+                .filter(|relation| relation.r#type == "performance")
+                .filter_map(|relation| {
+                    Some(PerformedWork {
+                        work_id: relation.work?.id,
+                        cover: relation.attributes.iter().any(|a| a == "cover"),
+                    })
+                })
+                .collect()
+        }))
+    }
+
+    /// MBIDs of the artists whose MusicBrainz entry links to a Spotify artist.
+    pub async fn artists_by_spotify_id(
+        &self,
+        spotify_artist_id: &str,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let resource = format!("https://open.spotify.com/artist/{}", spotify_artist_id);
+        let lookup = self
+            .get::<UrlLookup>(
+                "/ws/2/url",
+                &[("resource", &resource), ("inc", "artist-rels")],
+                "/ws/2/url",
+            )
+            .await?;
+        Ok(lookup
+            .map(|lookup| {
+                lookup
+                    .relations
+                    .into_iter()
+                    .filter_map(|relation| Some(relation.artist?.id))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// The annotation text of an artist, or `None` when it has none.
+    pub async fn artist_annotation(
+        &self,
+        artist_id: &str,
+    ) -> Result<Option<String>, Box<dyn Error>> {
+        let query = format!("entity:{} AND type:artist", artist_id);
+        let search = self
+            .get::<AnnotationSearch>("/ws/2/annotation", &[("query", &query)], "/ws/2/annotation")
+            .await?;
+        // The search also returns fuzzy matches, so the entity is checked.
+        Ok(search.and_then(|search| {
+            search
+                .annotations
+                .into_iter()
+                .find(|annotation| annotation.r#type == "artist" && annotation.entity == artist_id)
+                .map(|annotation| annotation.text)
+        }))
+    }
+}
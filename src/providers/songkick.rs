@@ -0,0 +1,305 @@
+use reqwest::{Client, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::env;
+use std::error::Error;
+use std::sync::LazyLock;
+
+use crate::usage::{record, Outcome};
+
+const DEFAULT_API_URL: &str = "https://api.songkick.com";
+
+/// Shared so lookups reuse connections.
+static CLIENT: LazyLock<Client> = LazyLock::new(Client::new);
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResultsPage<T> {
+    results_page: T,
+}
+
+#[derive(Deserialize)]
+struct ArtistSearch {
+    results: ArtistResults,
+}
+
+/// Songkick leaves `artist` out when nothing matches.
+#[derive(Deserialize)]
+struct ArtistResults {
+    #[serde(default)]
+    artist: Vec<ArtistRef>,
+}
+
+#[derive(Deserialize)]
+struct ArtistLookup {
+    results: ArtistResult,
+}
+
+#[derive(Deserialize)]
+struct ArtistResult {
+    artist: ArtistRef,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtistRef {
+    id: u64,
+    display_name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Calendar {
+    total_entries: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventCalendar {
+    total_entries: usize,
+    results: EventResults,
+}
+
+/// Songkick leaves `event` out of an empty page.
+#[derive(Deserialize)]
+struct EventResults {
+    #[serde(default)]
+    event: Vec<EventRef>,
+}
+
+#[derive(Deserialize)]
+struct EventRef {
+    location: EventLocation,
+}
+
+#[derive(Deserialize)]
+struct EventLocation {
+    city: String,
+}
+
+/// Events fetched per calendar page, Songkick's maximum.
+const EVENTS_PER_PAGE: usize = 50;
+
+/// Maximum number of calendar pages followed before giving up.
+pub const MAX_PAGES: usize = 20;
+
+/// The names Songkick gives countries in event locations, by ISO 3166-1
+/// alpha-2 code. Songkick uses English short names, with "UK" and "US"
+/// abbreviated and US states and Canadian provinces between city and country.
+const COUNTRY_NAMES: &[(&str, &str)] = &[
+    ("AR", "Argentina"),
+    ("AT", "Austria"),
+    ("AU", "Australia"),
+    ("BE", "Belgium"),
+    ("BR", "Brazil"),
+    ("CA", "Canada"),
+    ("CH", "Switzerland"),
+    ("CL", "Chile"),
+    ("CO", "Colombia"),
+    ("CZ", "Czech Republic"),
+    ("DE", "Germany"),
+    ("DK", "Denmark"),
+    ("ES", "Spain"),
+    ("FI", "Finland"),
+    ("FR", "France"),
+    ("GB", "UK"),
+    ("HU", "Hungary"),
+    ("IE", "Ireland"),
+    ("IT", "Italy"),
+    ("JP", "Japan"),
+    ("MX", "Mexico"),
+    ("NL", "Netherlands"),
+    ("NO", "Norway"),
+    ("NZ", "New Zealand"),
+    ("PL", "Poland"),
+    ("PT", "Portugal"),
+    ("SE", "Sweden"),
+    ("US", "US"),
+];
+
+/// The country name Songkick uses for a market, if it is one Songkick is known
+/// to name that way.
+pub fn country_name(code: &str) -> Option<&'static str> {
+    COUNTRY_NAMES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(code))
+        .map(|(_, name)| *name)
+}
+
+/// Where an upcoming event takes place, split from Songkick's "City, Country"
+/// location (with a region between them for some countries).
+#[derive(Debug, PartialEq, Eq)]
+pub struct EventCity {
+    pub city: String,
+    pub country: String,
+}
+
+impl EventCity {
+    fn parse(location: &str) -> Option<Self> {
+        let (rest, country) = location.rsplit_once(',')?;
+        let city = rest.split(',').next().map(str::trim).unwrap_or_default();
+        if city.is_empty() {
+            return None;
+        }
+        Some(EventCity {
+            city: city.to_string(),
+            country: country.trim().to_string(),
+        })
+    }
+}
+
+/// Looks up artists and their calendars on the Songkick API (3.0).
+///
+/// Requests carry the `SONGKICK_API_KEY` issued to the oracle. The base URL is
+/// `SONGKICK_API_URL`, `https://api.songkick.com` by default.
+pub struct SongkickProvider {
+    base_url: String,
+    api_key: String,
+}
+
+impl SongkickProvider {
+    /// `None` when no API key is configured.
+    pub fn from_env() -> Option<Self> {
+        let api_key = env::var("SONGKICK_API_KEY")
+            .ok()
+            .filter(|api_key| !api_key.is_empty())?;
+        let base_url = env::var("SONGKICK_API_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| DEFAULT_API_URL.to_string());
+        Some(SongkickProvider {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+        })
+    }
+
+    /// Fetches `path` with `query` as JSON; a 404 yields `None`.
+    ///
+    /// Calls are recorded under `template` so names and IDs do not end up in
+    /// usage labels.
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        template: &str,
+    ) -> Result<Option<T>, Box<dyn Error>> {
+        let mut url = Url::parse(&format!("{}{}", self.base_url, path))?;
+        url.query_pairs_mut()
+            .append_pair("apikey", &self.api_key)
+            .extend_pairs(query);
+
+        let response = match CLIENT.get(url).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                record("songkick", template, Outcome::Transport, None);
+                return Err(format!("Songkick request failed: {}", e).into());
+            }
+        };
+
+        let status = response.status();
+        record(
+            "songkick",
+            template,
+            Outcome::from_status(status),
+            Some(status.as_u16()),
+        );
+
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(format!("Songkick request failed with status: {}", status).into());
+        }
+        Ok(Some(response.json::<T>().await?))
+    }
+
+    /// The Songkick ID of the first artist with exactly this name, ignoring case.
+    pub async fn artist_id_by_name(&self, name: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let search = self
+            .get::<ResultsPage<ArtistSearch>>(
+                "/api/3.0/search/artists.json",
+                &[("query", name)],
+                "/api/3.0/search/artists.json",
+            )
+            .await?;
+        Ok(search.and_then(|search| {
+            search
+                .results_page
+                .results
+                .artist
+                .into_iter()
+                .find(|artist| artist.display_name.eq_ignore_ascii_case(name))
+                .map(|artist| artist.id.to_string())
+        }))
+    }
+
+    /// The display name of the artist with a Songkick ID, or `None` for an
+    /// unknown ID.
+    pub async fn artist_name(&self, artist_id: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let lookup = self
+            .get::<ResultsPage<ArtistLookup>>(
+                &format!("/api/3.0/artists/{}.json", artist_id),
+                &[],
+                "/api/3.0/artists/{id}.json",
+            )
+            .await?;
+        Ok(lookup.map(|lookup| lookup.results_page.results.artist.display_name))
+    }
+
+    /// The cities of all upcoming events of an artist, or `None` for an
+    /// unknown ID. Locations that cannot be split into a city and a country
+    /// are skipped.
+    ///
+    /// A calendar of more than `MAX_PAGES` pages is an error rather than a
+    /// partial list, which could miss the city asked about.
+    pub async fn upcoming_event_cities(
+        &self,
+        artist_id: &str,
+    ) -> Result<Option<Vec<EventCity>>, Box<dyn Error>> {
+        let path = format!("/api/3.0/artists/{}/calendar.json", artist_id);
+        let per_page = EVENTS_PER_PAGE.to_string();
+        let mut cities = Vec::new();
+        let mut seen = 0;
+        for page in 1..=MAX_PAGES {
+            let page = page.to_string();
+            let Some(calendar) = self
+                .get::<ResultsPage<EventCalendar>>(
+                    &path,
+                    &[("per_page", &per_page), ("page", &page)],
+                    "/api/3.0/artists/{id}/calendar.json",
+                )
+                .await?
+            else {
+                return Ok(None);
+            };
+            let calendar = calendar.results_page;
+            let events = calendar.results.event;
+            seen += events.len();
+            let done = events.is_empty() || seen >= calendar.total_entries;
+            cities.extend(
+                events
+                    .iter()
+                    .filter_map(|event| EventCity::parse(&event.location.city)),
+            );
+            if done {
+                return Ok(Some(cities));
+            }
+        }
+        Err(format!("Event calendar spans more than {} pages", MAX_PAGES).into())
+    }
+
+    /// The number of upcoming events of an artist, or `None` for an unknown ID.
+    pub async fn upcoming_event_count(
+        &self,
+        artist_id: &str,
+    ) -> Result<Option<usize>, Box<dyn Error>> {
+        let calendar = self
+            .get::<ResultsPage<Calendar>>(
+                &format!("/api/3.0/artists/{}/calendar.json", artist_id),
+                &[("per_page", "1")],
+                "/api/3.0/artists/{id}/calendar.json",
+            )
+            .await?;
+        Ok(calendar.map(|calendar| calendar.results_page.total_entries))
+    }
+}
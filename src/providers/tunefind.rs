@@ -0,0 +1,111 @@
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use std::env;
+use std::error::Error;
+use std::sync::LazyLock;
+
+use crate::usage::{record, Outcome};
+
+const DEFAULT_API_URL: &str = "https://www.tunefind.com";
+
+/// Shared so lookups reuse connections.
+static CLIENT: LazyLock<Client> = LazyLock::new(Client::new);
+
+// No field has a default: the API contract is unconfirmed, so a response of
+// any other shape must fail to decode rather than read as "no appearances".
+#[derive(Deserialize)]
+struct SongSearch {
+    songs: Vec<Song>,
+}
+
+#[derive(Deserialize)]
+struct Song {
+    appearances: Vec<Appearance>,
+}
+
+#[derive(Deserialize)]
+struct Appearance {
+    production: Production,
+}
+
+#[derive(Deserialize)]
+struct Production {
+    name: String,
+}
+
+/// Looks up where songs were synced to TV shows and films on the TuneFind
+/// partner API (v2).
+///
+/// Requests are authenticated with `TUNEFIND_API_USERNAME` and
+/// `TUNEFIND_API_PASSWORD`. The base URL is `TUNEFIND_API_URL`,
+/// `https://www.tunefind.com` by default.
+pub struct TuneFindProvider {
+    base_url: String,
+    username: String,
+    password: String,
+}
+
+impl TuneFindProvider {
+    /// Fails when no credentials are configured.
+    pub fn from_env() -> Result<Self, String> {
+        let setting = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+        let (Some(username), Some(password)) = (
+            setting("TUNEFIND_API_USERNAME"),
+            setting("TUNEFIND_API_PASSWORD"),
+        ) else {
+            return Err(String::from("No TuneFind API credentials are configured"));
+        };
+        let base_url = setting("TUNEFIND_API_URL").unwrap_or_else(|| DEFAULT_API_URL.to_string());
+        Ok(TuneFindProvider {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            username,
+            password,
+        })
+    }
+
+    /// Names of the shows and films a song by `artist` titled `title` appears
+    /// in; empty when TuneFind lists no such song. Any unsuccessful status
+    /// (including a 404) or unexpected response shape is an error.
+    pub async fn productions_featuring(
+        &self,
+        artist: &str,
+        title: &str,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let template = "/api/v2/song";
+        let mut url = Url::parse(&format!("{}{}", self.base_url, template))?;
+        url.query_pairs_mut()
+            .append_pair("artist", artist)
+            .append_pair("name", title);
+        let response = match CLIENT
+            .get(url)
+            .basic_auth(&self.username, Some(&self.password))
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                record("tunefind", template, Outcome::Transport, None);
+                return Err(format!("TuneFind request failed: {}", e).into());
+            }
+        };
+
+        let status = response.status();
+        record(
+            "tunefind",
+            template,
+            Outcome::from_status(status),
+            Some(status.as_u16()),
+        );
+
+        if !status.is_success() {
+            return Err(format!("TuneFind request failed with status: {}", status).into());
+        }
+        let search = response.json::<SongSearch>().await?;
+        Ok(search
+            .songs
+            .into_iter()
+            .flat_map(|song| song.appearances)
+            .map(|appearance| appearance.production.name)
+            .collect())
+    }
+}
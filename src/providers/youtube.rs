@@ -0,0 +1,97 @@
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use std::env;
+use std::error::Error;
+use std::sync::LazyLock;
+
+use crate::usage::{record, Outcome};
+
+const DEFAULT_API_URL: &str = "https://www.googleapis.com";
+
+/// Search results read per query; official videos rank near the top.
+const SEARCH_RESULTS: &str = "10";
+
+/// Shared so searches reuse connections.
+static CLIENT: LazyLock<Client> = LazyLock::new(Client::new);
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    items: Vec<SearchResult>,
+}
+
+#[derive(Deserialize)]
+struct SearchResult {
+    snippet: Snippet,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Snippet {
+    channel_title: String,
+}
+
+/// Searches videos on the YouTube Data API (v3).
+///
+/// Requests carry the `YOUTUBE_API_KEY` of the oracle's Google Cloud project;
+/// each search costs 100 units of its daily quota. The base URL is
+/// `YOUTUBE_API_URL`, `https://www.googleapis.com` by default.
+pub struct YouTubeProvider {
+    base_url: String,
+    api_key: String,
+}
+
+impl YouTubeProvider {
+    /// Fails when no API key is configured.
+    pub fn from_env() -> Result<Self, String> {
+        let api_key = env::var("YOUTUBE_API_KEY")
+            .ok()
+            .filter(|api_key| !api_key.is_empty())
+            .ok_or_else(|| String::from("No YouTube API key is configured"))?;
+        let base_url = env::var("YOUTUBE_API_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| DEFAULT_API_URL.to_string());
+        Ok(YouTubeProvider {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+        })
+    }
+
+    /// The channel titles of the top video results for `query`.
+    pub async fn video_channels(&self, query: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let template = "/youtube/v3/search";
+        let mut url = Url::parse(&format!("{}{}", self.base_url, template))?;
+        url.query_pairs_mut()
+            .append_pair("part", "snippet")
+            .append_pair("type", "video")
+            .append_pair("maxResults", SEARCH_RESULTS)
+            .append_pair("q", query)
+            .append_pair("key", &self.api_key);
+        let response = match CLIENT.get(url).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                record("youtube", template, Outcome::Transport, None);
+                return Err(format!("YouTube request failed: {}", e).into());
+            }
+        };
+
+        let status = response.status();
+        record(
+            "youtube",
+            template,
+            Outcome::from_status(status),
+            Some(status.as_u16()),
+        );
+
+        if !status.is_success() {
+            return Err(format!("YouTube request failed with status: {}", status).into());
+        }
+        let search = response.json::<SearchResponse>().await?;
+        Ok(search
+            .items
+            .into_iter()
+            .map(|item| item.snippet.channel_title)
+            .collect())
+    }
+}
@@ -0,0 +1,222 @@
+use std::error::Error;
+
+use super::{spotify_api_base, spotify_api_request};
+use crate::types::AudioAnalysis;
+
+/// Fetches Spotify's audio analysis for a track.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to analyse.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn audio_analysis_query_builder(
+    authorization: String,
+    track_id: &str,
+) -> Result<AudioAnalysis, Box<dyn Error>> {
+    let endpoint = format!("{}/v1/audio-analysis/{}", spotify_api_base(), track_id);
+    spotify_api_request::<AudioAnalysis>(endpoint, authorization).await
+}
+
+/// Checks if a track's audio analysis has at least `threshold` segments.
+///
+/// Segments are the smallest timbral units in the analysis, so a high count
+/// correlates with musical complexity.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to check.
+/// * `threshold` - The minimum number of segments.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_track_analysis_segments_count(
+    authorization: String,
+    track_id: String,
+    threshold: u64,
+) -> Result<String, Box<dyn Error>> {
+    count_at_least(authorization, &track_id, threshold, |analysis| {
+        analysis.segments.len()
+    })
+    .await
+}
+
+/// Checks if a track's audio analysis has at least `threshold` beats.
+///
+/// Combined with the track duration this gives a BPM estimate that does not
+/// depend on the analysis' own `tempo` field.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to check.
+/// * `threshold` - The minimum number of beats.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_track_analysis_beats_count(
+    authorization: String,
+    track_id: String,
+    threshold: u64,
+) -> Result<String, Box<dyn Error>> {
+    count_at_least(authorization, &track_id, threshold, |analysis| {
+        analysis.beats.len()
+    })
+    .await
+}
+
+/// Checks if a track's audio analysis has at least `threshold` bars.
+///
+/// A high bar count points to a long-form piece, such as classical or extended
+/// electronic tracks.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to check.
+/// * `threshold` - The minimum number of bars.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_track_analysis_bars_count(
+    authorization: String,
+    track_id: String,
+    threshold: u64,
+) -> Result<String, Box<dyn Error>> {
+    count_at_least(authorization, &track_id, threshold, |analysis| {
+        analysis.bars.len()
+    })
+    .await
+}
+
+/// Checks if a track's audio analysis has at least `threshold` tatums.
+///
+/// Tatums are the smallest rhythmic subdivisions Spotify detects.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to check.
+/// * `threshold` - The minimum number of tatums.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_track_analysis_tatums_count(
+    authorization: String,
+    track_id: String,
+    threshold: u64,
+) -> Result<String, Box<dyn Error>> {
+    count_at_least(authorization, &track_id, threshold, |analysis| {
+        analysis.tatums.len()
+    })
+    .await
+}
+
+/// Returns the segment, beat, bar and tatum counts of a track's audio analysis.
+///
+/// The counts are returned in that order as a four-value output, from a single
+/// audio analysis request. Each count saturates at 255, so it fits its byte.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to analyse.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_track_analysis_summary(
+    authorization: String,
+    track_id: String,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let analysis = audio_analysis_query_builder(authorization, &track_id).await?;
+    let counts = [
+        analysis.segments.len(),
+        analysis.beats.len(),
+        analysis.bars.len(),
+        analysis.tatums.len(),
+    ];
+    Ok(counts
+        .iter()
+        .map(|&count| u8::try_from(count).unwrap_or(u8::MAX).to_string())
+        .collect())
+}
+
+/// Checks if a track's fade-out starts after `min_seconds`.
+///
+/// Proves a track is not abruptly cut short.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to check.
+/// * `min_seconds` - The fade-out must start strictly after this many seconds.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_track_analysis_start_of_fade_out(
+    authorization: String,
+    track_id: String,
+    min_seconds: u16,
+) -> Result<String, Box<dyn Error>> {
+    let analysis = audio_analysis_query_builder(authorization, &track_id).await?;
+    if analysis.track.start_of_fade_out > min_seconds as f64 {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if a track's fade-in ends before `max_seconds`.
+///
+/// Proves a track has a quick start rather than a long intro.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to check.
+/// * `max_seconds` - The fade-in must end strictly before this many seconds.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_track_analysis_end_of_fade_in(
+    authorization: String,
+    track_id: String,
+    max_seconds: u16,
+) -> Result<String, Box<dyn Error>> {
+    let analysis = audio_analysis_query_builder(authorization, &track_id).await?;
+    if analysis.track.end_of_fade_in < max_seconds as f64 {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+async fn count_at_least(
+    authorization: String,
+    track_id: &str,
+    threshold: u64,
+    count: impl Fn(&AudioAnalysis) -> usize,
+) -> Result<String, Box<dyn Error>> {
+    let analysis = audio_analysis_query_builder(authorization, track_id).await?;
+    if count(&analysis) as u64 >= threshold {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
@@ -0,0 +1,128 @@
+use reqwest::StatusCode;
+use std::error::Error;
+
+use super::markets::unknown_item;
+use super::{failed_status, fetch_all_pages, spotify_api_base, spotify_api_request};
+use crate::types::{SimplifiedAudiobook, SimplifiedChapter};
+
+/// Looks up an audiobook in the catalog, so an unknown ID is reported as one
+/// instead of as an unsupported market.
+async fn lookup_audiobook(
+    authorization: &str,
+    audiobook_id: &str,
+) -> Result<SimplifiedAudiobook, Box<dyn Error>> {
+    let endpoint = format!("{}/v1/audiobooks/{}", spotify_api_base(), audiobook_id);
+    spotify_api_request(endpoint, authorization.to_string())
+        .await
+        .map_err(unknown_item("audiobook", audiobook_id))
+}
+
+/// Maps the errors Spotify answers with where audiobooks are not sold, once the
+/// audiobook itself was found.
+fn unavailable_in_market(e: Box<dyn Error>) -> Box<dyn Error> {
+    match failed_status(e.as_ref()) {
+        Some(StatusCode::FORBIDDEN | StatusCode::NOT_FOUND) => {
+            "Audiobooks are not available in this user's market".into()
+        }
+        _ => e,
+    }
+}
+
+/// Checks if an audiobook is among the user's saved audiobooks.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `audiobook_id` - The ID of the audiobook.
+///
+/// # Errors
+///
+/// This function will return an error if an API request fails, if a response
+/// is not in the expected format, if the audiobook is unknown, if the saved
+/// audiobooks span more than `MAX_PAGES` pages, or if audiobooks are not
+/// available in the user's market.
+pub async fn can_claim_audiobook_saved(
+    authorization: String,
+    audiobook_id: String,
+) -> Result<String, Box<dyn Error>> {
+    lookup_audiobook(&authorization, &audiobook_id).await?;
+    let endpoint = format!("{}/v1/me/audiobooks?limit=50", spotify_api_base());
+    let saved = fetch_all_pages::<SimplifiedAudiobook>(endpoint, authorization)
+        .await
+        .map_err(unavailable_in_market)?;
+    if saved.iter().any(|audiobook| audiobook.id == audiobook_id) {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if the user has listened to at least `min_percent` of an audiobook chapter.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `audiobook_id` - The ID of the audiobook.
+/// * `chapter_id` - The ID of the chapter.
+/// * `min_percent` - The minimum progress, from 0 (started) to 100 (completed).
+///
+/// Progress is the chapter's resume position relative to its duration; a chapter
+/// Spotify marks `fully_played` counts as 100%. A chapter never started does not
+/// satisfy even `0`.
+///
+/// # Errors
+///
+/// This function will return an error if an API request fails, if the audiobook
+/// is unknown, if the chapter is not part of the audiobook, if `min_percent` is
+/// above 100, if Spotify returns no resume point (the
+/// `user-read-playback-position` scope is missing), or if audiobooks are not
+/// available in the user's market.
+pub async fn can_claim_audiobook_chapter_listened(
+    authorization: String,
+    audiobook_id: String,
+    chapter_id: String,
+    min_percent: u8,
+) -> Result<String, Box<dyn Error>> {
+    if min_percent > 100 {
+        return Err(format!("Invalid progress percentage: {}", min_percent).into());
+    }
+
+    lookup_audiobook(&authorization, &audiobook_id).await?;
+    let endpoint = format!(
+        "{}/v1/audiobooks/{}/chapters?limit=50",
+        spotify_api_base(),
+        audiobook_id
+    );
+    let chapters = fetch_all_pages::<SimplifiedChapter>(endpoint, authorization)
+        .await
+        .map_err(unavailable_in_market)?;
+    let Some(chapter) = chapters
+        .into_iter()
+        .find(|chapter| chapter.id == chapter_id)
+    else {
+        return Err(format!(
+            "Chapter {} is not part of audiobook {}",
+            chapter_id, audiobook_id
+        )
+        .into());
+    };
+    let Some(resume_point) = chapter.resume_point else {
+        return Err(format!(
+            "No resume point for chapter {}; the user-read-playback-position scope is required",
+            chapter_id
+        )
+        .into());
+    };
+
+    let listened = if resume_point.fully_played {
+        true
+    } else if resume_point.resume_position_ms == 0 {
+        false
+    } else {
+        // Compare in integers: position * 100 >= duration * min_percent.
+        resume_point.resume_position_ms * 100 >= chapter.duration_ms * min_percent as u64
+    };
+    if listened {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
@@ -0,0 +1,62 @@
+use reqwest::StatusCode;
+use std::error::Error;
+
+use super::{failed_status, spotify_api_base, spotify_api_request};
+use crate::providers::musicbrainz::MusicBrainzProvider;
+use crate::types::Artist;
+
+/// Checks if an artist's biography is at least `threshold` characters long.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `artist_id` - The Spotify ID of the artist.
+/// * `threshold` - The minimum length, in characters.
+///
+/// The Spotify for Artists biography is not exposed by the Web API, so the
+/// artist's MusicBrainz annotation stands in for it. The artist is found on
+/// MusicBrainz through its link to the Spotify artist page; an artist without
+/// an annotation has a biography of length zero.
+///
+/// # Errors
+///
+/// This function will return an error if a request fails or if the artist is
+/// unknown to Spotify. The answer is indeterminate when no MusicBrainz artist,
+/// or more than one, links to the Spotify artist.
+pub async fn can_claim_artist_biography_length(
+    authorization: String,
+    artist_id: String,
+    threshold: u16,
+) -> Result<String, Box<dyn Error>> {
+    let endpoint = format!("{}/v1/artists/{}", spotify_api_base(), artist_id);
+    spotify_api_request::<Artist>(endpoint, authorization)
+        .await
+        .map_err(|e| -> Box<dyn Error> {
+            match failed_status(e.as_ref()) {
+                Some(StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND) => {
+                    format!("Unknown artist: {}", artist_id).into()
+                }
+                _ => e,
+            }
+        })?;
+
+    let musicbrainz = MusicBrainzProvider::from_env();
+    let mbids = musicbrainz.artists_by_spotify_id(&artist_id).await?;
+    let [mbid] = mbids.as_slice() else {
+        return Err(format!(
+            "indeterminate: {} MusicBrainz artists link to artist {}",
+            mbids.len(),
+            artist_id
+        )
+        .into());
+    };
+
+    let length = musicbrainz
+        .artist_annotation(mbid)
+        .await?
+        .map_or(0, |text| text.trim().chars().count());
+    if length >= usize::from(threshold) {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
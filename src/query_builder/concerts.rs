@@ -0,0 +1,260 @@
+use futures::future::BoxFuture;
+use reqwest::StatusCode;
+use std::error::Error;
+use std::sync::LazyLock;
+
+use super::{current_user_query_builder, failed_status, spotify_api_base, spotify_api_request};
+use crate::providers::bandsintown::BandsintownProvider;
+use crate::providers::songkick::{country_name, SongkickProvider};
+use crate::types::Artist;
+
+/// A source of concert listings. Each keeps its own artist IDs, which do not
+/// map to Spotify's.
+pub trait ConcertDataProvider: Send + Sync {
+    /// The provider's ID for the artist with this name, if it lists one.
+    fn find_artist<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Option<String>, String>>;
+    /// The name of the artist with a provider ID, or `None` for an unknown ID.
+    fn artist_name<'a>(
+        &'a self,
+        artist_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<String>, String>>;
+    /// The number of upcoming events of an artist, or `None` for an unknown ID.
+    fn upcoming_events<'a>(
+        &'a self,
+        artist_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<usize>, String>>;
+}
+
+impl ConcertDataProvider for SongkickProvider {
+    fn find_artist<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Option<String>, String>> {
+        Box::pin(async move {
+            self.artist_id_by_name(name)
+                .await
+                .map_err(|e| e.to_string())
+        })
+    }
+
+    fn artist_name<'a>(
+        &'a self,
+        artist_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<String>, String>> {
+        Box::pin(async move { self.artist_name(artist_id).await.map_err(|e| e.to_string()) })
+    }
+
+    fn upcoming_events<'a>(
+        &'a self,
+        artist_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<usize>, String>> {
+        Box::pin(async move {
+            self.upcoming_event_count(artist_id)
+                .await
+                .map_err(|e| e.to_string())
+        })
+    }
+}
+
+impl ConcertDataProvider for BandsintownProvider {
+    fn find_artist<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Option<String>, String>> {
+        Box::pin(async move {
+            self.artist_id_by_name(name)
+                .await
+                .map_err(|e| e.to_string())
+        })
+    }
+
+    fn artist_name<'a>(
+        &'a self,
+        artist_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<String>, String>> {
+        Box::pin(async move { self.artist_name(artist_id).await.map_err(|e| e.to_string()) })
+    }
+
+    fn upcoming_events<'a>(
+        &'a self,
+        artist_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<usize>, String>> {
+        Box::pin(async move {
+            self.upcoming_event_count(artist_id)
+                .await
+                .map_err(|e| e.to_string())
+        })
+    }
+}
+
+/// Songkick when `SONGKICK_API_KEY` is set, otherwise Bandsintown when
+/// `BANDSINTOWN_APP_ID` is, chosen on first use.
+static PROVIDER: LazyLock<Result<Box<dyn ConcertDataProvider>, String>> = LazyLock::new(|| {
+    if let Some(provider) = SongkickProvider::from_env() {
+        return Ok(Box::new(provider));
+    }
+    if let Some(provider) = BandsintownProvider::from_env() {
+        return Ok(Box::new(provider));
+    }
+    Err(String::from("No concert data provider is configured"))
+});
+
+/// Fetches the name of a Spotify artist.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or the artist is
+/// unknown.
+async fn spotify_artist_name(
+    authorization: String,
+    artist_id: &str,
+) -> Result<String, Box<dyn Error>> {
+    let endpoint = format!("{}/v1/artists/{}", spotify_api_base(), artist_id);
+    let artist = spotify_api_request::<Artist>(endpoint, authorization)
+        .await
+        .map_err(|e| -> Box<dyn Error> {
+            match failed_status(e.as_ref()) {
+                Some(StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND) => {
+                    format!("Unknown artist: {}", artist_id).into()
+                }
+                _ => e,
+            }
+        })?;
+    Ok(artist.name)
+}
+
+/// Finds the concert provider's ID for a Spotify artist by its name.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `artist_id` - The Spotify ID of the artist.
+///
+/// Only an exact, case-insensitive name match is accepted, so artists sharing a
+/// name may resolve to the wrong listing; pass the provider ID when it matters.
+///
+/// # Errors
+///
+/// This function will return an error if no provider is configured, if a
+/// request fails, if the Spotify artist is unknown, or if the provider lists no
+/// artist by that name (an indeterminate answer rather than zero).
+pub async fn concert_artist_id(
+    authorization: String,
+    artist_id: &str,
+) -> Result<String, Box<dyn Error>> {
+    let provider = PROVIDER.as_ref().map_err(|e| e.clone())?;
+    let name = spotify_artist_name(authorization, artist_id).await?;
+    find_concert_artist(provider.as_ref(), &name).await
+}
+
+async fn find_concert_artist(
+    provider: &dyn ConcertDataProvider,
+    name: &str,
+) -> Result<String, Box<dyn Error>> {
+    match provider.find_artist(name).await? {
+        Some(id) => Ok(id),
+        None => Err(format!(
+            "indeterminate: no concert listing found for artist {}",
+            name
+        )
+        .into()),
+    }
+}
+
+/// Checks if an artist has upcoming tour dates.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `artist_id` - The Spotify ID of the artist.
+/// * `concert_artist_id` - The artist's Songkick or Bandsintown ID, whichever
+///   provider is configured; when empty it is looked up with
+///   [`concert_artist_id`].
+///
+/// A given provider ID must name the same artist as `artist_id` (compared
+/// case-insensitively), so another artist's tour cannot stand in. Any upcoming
+/// event counts, including one-off festival appearances.
+///
+/// # Errors
+///
+/// This function will return an error if no provider is configured, if a
+/// request fails, if either artist is unknown, if their names differ, or if the
+/// lookup by name finds no match.
+pub async fn can_claim_artist_tour_announced(
+    authorization: String,
+    artist_id: String,
+    concert_artist_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let provider = PROVIDER.as_ref().map_err(|e| e.clone())?;
+    let name = spotify_artist_name(authorization, &artist_id).await?;
+    let concert_artist_id = if concert_artist_id.is_empty() {
+        find_concert_artist(provider.as_ref(), &name).await?
+    } else {
+        let listed = provider
+            .artist_name(&concert_artist_id)
+            .await?
+            .ok_or_else(|| format!("Unknown concert artist: {}", concert_artist_id))?;
+        if !listed.trim().eq_ignore_ascii_case(name.trim()) {
+            return Err(format!(
+                "Concert artist {} is {}, not {}",
+                concert_artist_id, listed, name
+            )
+            .into());
+        }
+        concert_artist_id
+    };
+
+    match provider.upcoming_events(&concert_artist_id).await? {
+        Some(count) if count > 0 => Ok(String::from("1")),
+        Some(_) => Ok(String::from("0")),
+        None => Err(format!("Unknown concert artist: {}", concert_artist_id).into()),
+    }
+}
+
+/// Checks if an artist has an upcoming event in a city of the user's country.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `artist_id` - The Songkick ID of the artist.
+/// * `city` - The city name, matched case-insensitively against Songkick's.
+///
+/// City names repeat across countries (Paris, France and Paris, Texas), so an
+/// event only counts when it is also in the user's Spotify `country`. Songkick
+/// is used whichever provider [`can_claim_artist_tour_announced`] is on, since
+/// it reports where each event takes place.
+///
+/// # Errors
+///
+/// This function will return an error if Songkick is not configured, if a
+/// request fails, or if Songkick does not know the artist. The answer is
+/// indeterminate when Spotify withholds the user's country or Songkick's name
+/// for it is unknown.
+pub async fn can_claim_event_in_user_city(
+    authorization: String,
+    artist_id: String,
+    city: String,
+) -> Result<String, Box<dyn Error>> {
+    let provider = SongkickProvider::from_env()
+        .ok_or_else(|| String::from("No Songkick API key is configured"))?;
+    let user = current_user_query_builder(authorization).await?;
+    let Some(market) = user.country else {
+        return Err(
+            "indeterminate: the user's country is unknown; the user-read-private scope is required"
+                .into(),
+        );
+    };
+    let Some(country) = country_name(&market) else {
+        return Err(format!(
+            "indeterminate: no Songkick country name is known for market {}",
+            market
+        )
+        .into());
+    };
+
+    let cities = provider
+        .upcoming_event_cities(&artist_id)
+        .await?
+        .ok_or_else(|| format!("Unknown concert artist: {}", artist_id))?;
+    let local = cities.iter().any(|event| {
+        event.country.eq_ignore_ascii_case(country) && event.city.eq_ignore_ascii_case(city.trim())
+    });
+    if local {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
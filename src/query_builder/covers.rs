@@ -0,0 +1,121 @@
+use reqwest::StatusCode;
+use std::collections::HashSet;
+use std::error::Error;
+
+use super::{failed_status, track_query_builder};
+use crate::providers::musicbrainz::MusicBrainzProvider;
+
+/// Fetches the ISRC of a Spotify track.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails, if the track is
+/// unknown, or if it has no ISRC (an indeterminate answer for claims).
+pub(super) async fn track_isrc(
+    authorization: String,
+    track_id: &str,
+) -> Result<String, Box<dyn Error>> {
+    let track =
+        track_query_builder(authorization, track_id)
+            .await
+            .map_err(|e| -> Box<dyn Error> {
+                match failed_status(e.as_ref()) {
+                    Some(StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND) => {
+                        format!("Unknown track: {}", track_id).into()
+                    }
+                    _ => e,
+                }
+            })?;
+    track
+        .external_ids
+        .isrc
+        .ok_or_else(|| format!("indeterminate: track {} has no ISRC", track_id).into())
+}
+
+/// Checks if a track is a cover of the work performed by an original recording.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The Spotify ID of the track.
+/// * `original_id` - The MusicBrainz ID (MBID) of the original recording.
+///
+/// The track is matched to MusicBrainz recordings by its ISRC. It is a cover when
+/// one of those recordings is marked as a cover performance of a work that the
+/// original recording performs.
+///
+/// # Errors
+///
+/// This function will return an error if a request fails, if the track or the
+/// original recording is unknown, or if the track has no ISRC or no MusicBrainz
+/// recording (an indeterminate answer rather than zero).
+pub async fn can_claim_track_covers_original(
+    authorization: String,
+    track_id: String,
+    original_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let isrc = track_isrc(authorization, &track_id).await?;
+    let musicbrainz = MusicBrainzProvider::from_env();
+    let original_works: HashSet<String> = musicbrainz
+        .performed_works(&original_id)
+        .await?
+        .ok_or_else(|| format!("Unknown recording: {}", original_id))?
+        .into_iter()
+        .map(|work| work.work_id)
+        .collect();
+
+    let recordings = musicbrainz.recordings_by_isrc(&isrc).await?;
+    if recordings.is_empty() {
+        return Err(format!(
+            "indeterminate: no MusicBrainz recording has the ISRC {} of track {}",
+            isrc, track_id
+        )
+        .into());
+    }
+    for recording in recordings {
+        // The original itself is not a cover of it.
+        if recording == original_id {
+            continue;
+        }
+        let works = musicbrainz
+            .performed_works(&recording)
+            .await?
+            .unwrap_or_default();
+        if works
+            .iter()
+            .any(|work| work.cover && original_works.contains(&work.work_id))
+        {
+            return Ok(String::from("1"));
+        }
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if a Spotify track and a MusicBrainz recording are the same recording.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The Spotify ID of the track.
+/// * `recording_id` - The MusicBrainz ID (MBID) of the recording.
+///
+/// They match when MusicBrainz lists the recording under the track's ISRC.
+///
+/// # Errors
+///
+/// This function will return an error if a request fails, if the track is
+/// unknown, or if it has no ISRC (an indeterminate answer rather than zero).
+pub async fn can_claim_track_same_recording(
+    authorization: String,
+    track_id: String,
+    recording_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let isrc = track_isrc(authorization, &track_id).await?;
+    let recordings = MusicBrainzProvider::from_env()
+        .recordings_by_isrc(&isrc)
+        .await?;
+    if recordings.contains(&recording_id) {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
@@ -0,0 +1,61 @@
+use reqwest::StatusCode;
+use std::error::Error;
+
+use super::{failed_status, spotify_api_base, spotify_api_request};
+use crate::types::Devices;
+
+/// Checks if any of the user's devices is named after `location`, as in
+/// "iPhone in Berlin" for `Berlin`.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `location` - The city or country name to look for.
+///
+/// This is a heuristic and NOT a location proof: device names are chosen by the
+/// user and can be changed at any time, and say nothing about where a device is
+/// used. `location` matches case-insensitively as a whole word or phrase of a
+/// device name, so `Rome` does not match "Romeo's Mac".
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format. Authorization failures name the scope required.
+pub async fn can_claim_device_location_hint(
+    authorization: String,
+    location: String,
+) -> Result<String, Box<dyn Error>> {
+    let endpoint = format!("{}/v1/me/player/devices", spotify_api_base());
+    let devices = spotify_api_request::<Devices>(endpoint, authorization)
+        .await
+        .map_err(|e| match failed_status(e.as_ref()) {
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                format!("{}; the user-read-playback-state scope is required", e).into()
+            }
+            _ => e,
+        })?;
+
+    let hinted = devices
+        .devices
+        .iter()
+        .any(|device| names_location(&device.name, &location));
+    if hinted {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Whether `location` occurs in `name` as whole words, ignoring case.
+fn names_location(name: &str, location: &str) -> bool {
+    let words = |text: &str| -> Vec<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let (name, location) = (words(name), words(location));
+    !location.is_empty()
+        && name
+            .windows(location.len())
+            .any(|window| window == location.as_slice())
+}
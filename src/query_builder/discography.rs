@@ -0,0 +1,156 @@
+use futures::stream::{self, StreamExt};
+use std::collections::HashSet;
+use std::error::Error;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use super::history::listened_ids;
+use super::{
+    album_tracks_query_builder, fetch_all_pages, spotify_api_base, MAX_CONCURRENT_REQUESTS,
+};
+use crate::cache::TtlCache;
+use crate::types::{AlbumTrackCredits, ArtistAlbum};
+
+/// How long an artist's studio albums are reused; collecting them takes one
+/// request per album, and discographies rarely change within a day.
+const STUDIO_ALBUMS_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The track IDs of each studio album of an artist, keyed by artist ID.
+static STUDIO_ALBUMS: LazyLock<TtlCache<String, Vec<Vec<String>>>> =
+    LazyLock::new(|| TtlCache::new(STUDIO_ALBUMS_TTL));
+
+/// Checks if an artist has worked with at least `threshold` other artists.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `artist_id` - The ID of the artist.
+/// * `threshold` - The minimum number of distinct collaborators.
+///
+/// The discography is the artist's albums and singles; compilations and
+/// appearances on other artists' releases are not included. A collaborator is
+/// any other artist credited on one of its tracks that also credits `artist_id`.
+/// Track lists are fetched with at most `MAX_CONCURRENT_REQUESTS` albums in
+/// flight, and fetching stops once the threshold is met.
+///
+/// # Errors
+///
+/// This function will return an error if any API request fails, if the artist is
+/// unknown, or if a response is not in the expected format.
+pub async fn can_claim_artist_collaboration_count(
+    authorization: String,
+    artist_id: String,
+    threshold: u8,
+) -> Result<String, Box<dyn Error>> {
+    let endpoint = format!(
+        "{}/v1/artists/{}/albums?include_groups=album,single&limit=50",
+        spotify_api_base(),
+        artist_id
+    );
+    let albums = fetch_all_pages::<ArtistAlbum>(endpoint, authorization.clone()).await?;
+
+    let requests: Vec<_> = albums
+        .into_iter()
+        .map(|album| {
+            let endpoint = format!(
+                "{}/v1/albums/{}/tracks?limit=50",
+                spotify_api_base(),
+                album.id
+            );
+            fetch_all_pages::<AlbumTrackCredits>(endpoint, authorization.clone())
+        })
+        .collect();
+    let mut lookups = stream::iter(requests).buffer_unordered(MAX_CONCURRENT_REQUESTS);
+
+    let mut collaborators: HashSet<String> = HashSet::new();
+    while let Some(tracks) = lookups.next().await {
+        for track in tracks? {
+            if !track.artists.iter().any(|artist| artist.id == artist_id) {
+                continue;
+            }
+            collaborators.extend(
+                track
+                    .artists
+                    .into_iter()
+                    .map(|artist| artist.id)
+                    .filter(|id| *id != artist_id),
+            );
+        }
+        if collaborators.len() >= usize::from(threshold) {
+            return Ok(String::from("1"));
+        }
+    }
+    if collaborators.len() >= usize::from(threshold) {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if the user has listened to at least one track of every studio album
+/// by an artist.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `artist_id` - The ID of the artist.
+///
+/// Listening is judged from the available recently played history and the top
+/// tracks of all three time ranges, so this is a lower bound. Studio albums are
+/// the `album` group of the artist's releases; their track lists are cached per
+/// artist for a day. An artist without studio albums answers "0".
+///
+/// # Errors
+///
+/// This function will return an error if any API request fails, if the artist is
+/// unknown, or if a response is not in the expected format.
+pub async fn can_claim_discography_completed(
+    authorization: String,
+    artist_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let albums = match STUDIO_ALBUMS.get(&artist_id) {
+        Some(albums) => albums,
+        None => {
+            let albums = studio_album_tracks(authorization.clone(), &artist_id).await?;
+            STUDIO_ALBUMS.insert(artist_id.clone(), albums.clone());
+            albums
+        }
+    };
+    if albums.is_empty() {
+        return Ok(String::from("0"));
+    }
+
+    let listened = listened_ids(authorization, false).await?;
+    let completed = albums
+        .iter()
+        .all(|tracks| tracks.iter().any(|track_id| listened.contains(track_id)));
+    if completed {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Fetches the track IDs of each of an artist's studio albums, with at most
+/// `MAX_CONCURRENT_REQUESTS` albums in flight.
+async fn studio_album_tracks(
+    authorization: String,
+    artist_id: &str,
+) -> Result<Vec<Vec<String>>, Box<dyn Error>> {
+    let endpoint = format!(
+        "{}/v1/artists/{}/albums?include_groups=album&limit=50",
+        spotify_api_base(),
+        artist_id
+    );
+    let albums = fetch_all_pages::<ArtistAlbum>(endpoint, authorization.clone()).await?;
+
+    let requests: Vec<_> = albums
+        .iter()
+        .map(|album| album_tracks_query_builder(authorization.clone(), &album.id))
+        .collect();
+    let mut lookups = stream::iter(requests).buffer_unordered(MAX_CONCURRENT_REQUESTS);
+
+    let mut tracks = Vec::new();
+    while let Some(album_tracks) = lookups.next().await {
+        tracks.push(album_tracks?.into_iter().map(|track| track.id).collect());
+    }
+    Ok(tracks)
+}
@@ -0,0 +1,69 @@
+use futures::future::{join_all, BoxFuture};
+use std::error::Error;
+
+use super::covers::track_isrc;
+use crate::providers::apple_music::AppleMusicProvider;
+use crate::providers::musicbrainz::MusicBrainzProvider;
+
+/// A catalog outside Spotify that can be searched for a recording.
+pub trait PlatformCheckProvider: Send + Sync {
+    /// Whether the catalog lists a recording with this ISRC.
+    fn has_isrc<'a>(&'a self, isrc: &'a str) -> BoxFuture<'a, Result<bool, String>>;
+}
+
+impl PlatformCheckProvider for AppleMusicProvider {
+    fn has_isrc<'a>(&'a self, isrc: &'a str) -> BoxFuture<'a, Result<bool, String>> {
+        Box::pin(async move {
+            self.songs_by_isrc(isrc)
+                .await
+                .map(|songs| !songs.is_empty())
+                .map_err(|e| e.to_string())
+        })
+    }
+}
+
+impl PlatformCheckProvider for MusicBrainzProvider {
+    fn has_isrc<'a>(&'a self, isrc: &'a str) -> BoxFuture<'a, Result<bool, String>> {
+        Box::pin(async move {
+            self.recordings_by_isrc(isrc)
+                .await
+                .map(|recordings| !recordings.is_empty())
+                .map_err(|e| e.to_string())
+        })
+    }
+}
+
+/// Checks if a track is found on no platform other than Spotify.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The Spotify ID of the track.
+///
+/// The track's ISRC is looked up on Apple Music and MusicBrainz at once; it is
+/// exclusive when neither lists it. This proves absence from those two catalogs
+/// only, which Spotify Originals and other exclusives satisfy, not from every
+/// other service.
+///
+/// # Errors
+///
+/// This function will return an error if Apple Music is not configured, if a
+/// request fails, if the track is unknown, or if it has no ISRC (an
+/// indeterminate answer rather than zero).
+pub async fn can_claim_track_exclusive_to_spotify(
+    authorization: String,
+    track_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let apple_music = AppleMusicProvider::from_env()?;
+    let isrc = track_isrc(authorization, &track_id).await?;
+
+    let musicbrainz = MusicBrainzProvider::from_env();
+    let providers: [&dyn PlatformCheckProvider; 2] = [&apple_music, &musicbrainz];
+    let listed = join_all(providers.iter().map(|provider| provider.has_isrc(&isrc))).await;
+    for found in listed {
+        if found? {
+            return Ok(String::from("0"));
+        }
+    }
+    Ok(String::from("1"))
+}
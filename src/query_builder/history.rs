@@ -0,0 +1,1099 @@
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use reqwest::StatusCode;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::sync::LazyLock;
+
+use super::{
+    album_tracks_query_builder, failed_status, spotify_api_base, spotify_api_request,
+    spotify_market, top_item_ids, track_query_builder,
+};
+use crate::cache::TtlCache;
+use crate::leniency::{self, Leniency};
+use crate::types::{ArtistTopTracks, PlayedTrack, PlaylistName, RecentlyPlayed, TimeRange};
+
+/// Maximum number of recently-played pages fetched for a single claim.
+const MAX_HISTORY_PAGES: usize = 10;
+
+/// Largest page of top items Spotify returns, used when collecting everything.
+const TOP_ITEMS_LIMIT: u8 = 50;
+
+/// Longest pause between two plays of one listening session.
+const SESSION_GAP_MINUTES: i64 = 30;
+
+/// The morning, as UTC hours from the first up to but excluding the second.
+const MORNING_HOURS: (u32, u32) = (6, 10);
+
+/// Words in a playlist name that mark it as a workout playlist.
+const WORKOUT_KEYWORDS: &[&str] = &["workout", "gym", "fitness"];
+
+/// How long an artist's top tracks are reused; they change over days, not minutes.
+const ARTIST_TOP_TRACKS_TTL: std::time::Duration = std::time::Duration::from_secs(6 * 60 * 60);
+
+/// Track IDs of an artist's top tracks, keyed by artist ID and market.
+static ARTIST_TOP_TRACKS: LazyLock<TtlCache<(String, String), Vec<String>>> =
+    LazyLock::new(|| TtlCache::new(ARTIST_TOP_TRACKS_TTL));
+
+/// Plays from the user's recently played history, newest first.
+pub struct PlayWindow {
+    /// Plays at or after the requested start of the window.
+    pub plays: Vec<PlayedTrack>,
+    /// Whether the fetched history reaches back to the start of the window.
+    pub covered: bool,
+    /// The oldest play Spotify returned, i.e. how far back the history goes.
+    pub coverage_boundary: Option<DateTime<Utc>>,
+}
+
+impl PlayWindow {
+    /// Fails with [`Indeterminate`] when the history does not reach back far
+    /// enough for a negative answer to be meaningful.
+    pub fn require_coverage(&self) -> Result<(), Box<dyn Error>> {
+        if self.covered {
+            return Ok(());
+        }
+        Err(Box::new(Indeterminate {
+            coverage_boundary: self.coverage_boundary,
+        }))
+    }
+}
+
+/// Returned when the available listening history is too short to answer a claim.
+#[derive(Debug)]
+pub struct Indeterminate {
+    pub coverage_boundary: Option<DateTime<Utc>>,
+}
+
+impl fmt::Display for Indeterminate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.coverage_boundary {
+            Some(boundary) => write!(
+                f,
+                "indeterminate: listening history only covers plays since {}",
+                boundary.to_rfc3339()
+            ),
+            None => write!(f, "indeterminate: listening history is empty"),
+        }
+    }
+}
+
+impl Error for Indeterminate {}
+
+/// Fetches the user's recently played tracks back to `after`.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `after` - Start of the window, as a Unix timestamp in milliseconds.
+///
+/// Pages are followed through `next` until a play older than `after` shows up,
+/// Spotify runs out of history or `MAX_HISTORY_PAGES` is reached.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn recently_played_since(
+    authorization: String,
+    after: i64,
+) -> Result<PlayWindow, Box<dyn Error>> {
+    let mut endpoint = Some(format!(
+        "{}/v1/me/player/recently-played?limit=50",
+        spotify_api_base()
+    ));
+    let mut window = PlayWindow {
+        plays: Vec::new(),
+        covered: false,
+        coverage_boundary: None,
+    };
+
+    for _ in 0..MAX_HISTORY_PAGES {
+        let Some(url) = endpoint.take() else {
+            break;
+        };
+        let page = spotify_api_request::<RecentlyPlayed>(url, authorization.clone()).await?;
+        let exhausted = page.next.is_none() && (page.items.len() as u32) < page.limit;
+
+        for played in page.items {
+            window.coverage_boundary = Some(played.played_at);
+            if played.played_at.timestamp_millis() < after {
+                window.covered = true;
+                return Ok(window);
+            }
+            window.plays.push(played);
+        }
+
+        if exhausted {
+            // Fewer plays than a full page and no cursor: this is all there is.
+            window.covered = true;
+            return Ok(window);
+        }
+        endpoint = page.next;
+    }
+    Ok(window)
+}
+
+/// Checks if the user played a track on its release date (UTC calendar day).
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to check.
+/// * `allow_journal` - Whether plays recorded in the listening journal may be used.
+///   No journal is configured yet, so only live history is consulted either way.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails, if the album's
+/// release date is only known to year or month precision, or an [`Indeterminate`]
+/// error if the listening history does not reach back to the release day.
+pub async fn can_claim_recent_play_on_release_day(
+    authorization: String,
+    track_id: String,
+    _allow_journal: bool,
+) -> Result<String, Box<dyn Error>> {
+    let track = track_query_builder(authorization.clone(), &track_id).await?;
+    if track.album.release_date_precision != "day" {
+        return Err(format!(
+            "Release date precision is '{}'; the release day cannot be proven",
+            track.album.release_date_precision
+        )
+        .into());
+    }
+
+    let release_day = NaiveDate::parse_from_str(&track.album.release_date, "%Y-%m-%d")?;
+    let day_start = Utc.from_utc_datetime(&release_day.and_hms_opt(0, 0, 0).unwrap_or_default());
+    let day_end = day_start + Duration::days(1);
+
+    let window = recently_played_since(authorization, day_start.timestamp_millis()).await?;
+    let played_on_release_day = window.plays.iter().any(|played| {
+        played.track.id == track_id && played.played_at >= day_start && played.played_at < day_end
+    });
+    if played_on_release_day {
+        return Ok(String::from("1"));
+    }
+
+    window.require_coverage()?;
+    Ok(String::from("0"))
+}
+
+/// Checks if the user played a track between `start` and `end`.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to check.
+/// * `start` - Start of the window, as a Unix timestamp in milliseconds.
+/// * `end` - End of the window (inclusive), as a Unix timestamp in milliseconds.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format, or an [`Indeterminate`] error if no play was
+/// found and the history does not reach back to `start`.
+pub async fn can_claim_played_within_window(
+    authorization: String,
+    track_id: String,
+    start: i64,
+    end: i64,
+) -> Result<String, Box<dyn Error>> {
+    let window = recently_played_since(authorization, start).await?;
+    let played_in_window = window
+        .plays
+        .iter()
+        .any(|played| played.track.id == track_id && played.played_at.timestamp_millis() <= end);
+    if played_in_window {
+        return Ok(String::from("1"));
+    }
+
+    window.require_coverage()?;
+    Ok(String::from("0"))
+}
+
+/// Checks if the user played any of an artist's current top tracks since `after`.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `artist_id` - The ID of the artist.
+/// * `after` - Start of the window, as a Unix timestamp in milliseconds.
+///
+/// The top tracks are looked up in the `SPOTIFY_MARKET` market, concurrently
+/// with the user's history. Tracks relinked for that market match plays of
+/// either the relinked or the original track.
+///
+/// # Errors
+///
+/// This function will return an error if the artist is unknown, if an API request
+/// fails or if a response is not in the expected format, or an [`Indeterminate`]
+/// error if no play was found and the history does not reach back to `after`.
+pub async fn can_claim_artist_top_track_in_user_history(
+    authorization: String,
+    artist_id: String,
+    after: i64,
+) -> Result<String, Box<dyn Error>> {
+    let (top_tracks, window) = tokio::join!(
+        artist_top_track_ids(authorization.clone(), &artist_id, spotify_market()),
+        async {
+            recently_played_since(authorization, after)
+                .await
+                .map_err(|e| e.to_string())
+        },
+    );
+    let top_tracks = top_tracks?;
+    let window = window?;
+
+    let played_top_track = window
+        .plays
+        .iter()
+        .any(|played| top_tracks.contains(&played.track.id));
+    if played_top_track {
+        return Ok(String::from("1"));
+    }
+
+    window.require_coverage()?;
+    Ok(String::from("0"))
+}
+
+/// Returns the IDs of an artist's top tracks in `market`, including the original
+/// IDs of relinked tracks.
+///
+/// Errors are flattened to strings so the future stays `Send` while joined.
+pub(super) async fn artist_top_track_ids(
+    authorization: String,
+    artist_id: &str,
+    market: String,
+) -> Result<Vec<String>, String> {
+    let cache_key = (artist_id.to_string(), market);
+    if let Some(track_ids) = ARTIST_TOP_TRACKS.get(&cache_key) {
+        return Ok(track_ids);
+    }
+
+    let endpoint = format!(
+        "{}/v1/artists/{}/top-tracks?market={}",
+        spotify_api_base(),
+        artist_id,
+        cache_key.1
+    );
+    let top_tracks = spotify_api_request::<ArtistTopTracks>(endpoint, authorization)
+        .await
+        .map_err(|e| match failed_status(e.as_ref()) {
+            Some(StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND) => {
+                format!("Unknown artist: {}", artist_id)
+            }
+            _ => e.to_string(),
+        })?;
+
+    let track_ids: Vec<String> = top_tracks
+        .tracks
+        .into_iter()
+        .flat_map(|track| {
+            std::iter::once(track.id).chain(track.linked_from.map(|linked| linked.id))
+        })
+        .collect();
+    ARTIST_TOP_TRACKS.insert(cache_key, track_ids.clone());
+    Ok(track_ids)
+}
+
+/// Checks if the user played an album front-to-back since `after`.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `album_id` - The ID of the album.
+/// * `after` - Start of the window, as a Unix timestamp in milliseconds.
+/// * `max_gap_minutes` - The longest allowed pause between two consecutive album tracks.
+///
+/// All of the album's tracks must show up in order, each within `max_gap_minutes`
+/// of the previous one. Other plays may be interleaved. A partial playthrough
+/// returns `"0"`.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format, or an [`Indeterminate`] error if no playthrough
+/// was found and the history does not reach back to `after`.
+pub async fn can_claim_multi_track_ordered_playthrough(
+    authorization: String,
+    album_id: String,
+    after: i64,
+    max_gap_minutes: u8,
+) -> Result<String, Box<dyn Error>> {
+    let album_tracks = album_tracks_query_builder(authorization.clone(), &album_id).await?;
+    if album_tracks.is_empty() {
+        return Err(format!("Album {} has no tracks", album_id).into());
+    }
+    let sequence: Vec<&str> = album_tracks.iter().map(|track| track.id.as_str()).collect();
+
+    let window = recently_played_since(authorization, after).await?;
+    let chronological: Vec<(&str, DateTime<Utc>)> = window
+        .plays
+        .iter()
+        .rev()
+        .map(|played| (played.track.id.as_str(), played.played_at))
+        .collect();
+
+    let gap = SequenceGap {
+        max_plays: usize::MAX,
+        max_pause: Some(Duration::minutes(max_gap_minutes as i64)),
+    };
+    if contains_ordered_subsequence(&chronological, &sequence, gap) {
+        return Ok(String::from("1"));
+    }
+
+    window.require_coverage()?;
+    Ok(String::from("0"))
+}
+
+/// Checks if the user played every track of a reference sequence, in order, since `after`.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `sequence` - The track IDs to look for, in the order they must be played.
+/// * `after` - Start of the window, as a Unix timestamp in milliseconds.
+/// * `max_gap_plays` - The most other plays allowed between two sequence tracks;
+///   `0` requires them back to back and `u8::MAX` allows any number.
+///
+/// Listening journals are not configured yet, so only live history is consulted.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format, or an [`Indeterminate`] error if the sequence
+/// was not found and the history does not reach back to `after`.
+pub async fn can_claim_history_contains_full_reference_sequence(
+    authorization: String,
+    sequence: Vec<String>,
+    after: i64,
+    max_gap_plays: u8,
+) -> Result<String, Box<dyn Error>> {
+    let sequence: Vec<&str> = sequence.iter().map(String::as_str).collect();
+
+    let window = recently_played_since(authorization, after).await?;
+    let chronological: Vec<(&str, DateTime<Utc>)> = window
+        .plays
+        .iter()
+        .rev()
+        .map(|played| (played.track.id.as_str(), played.played_at))
+        .collect();
+
+    let gap = SequenceGap {
+        max_plays: match max_gap_plays {
+            u8::MAX => usize::MAX,
+            plays => plays as usize,
+        },
+        max_pause: None,
+    };
+    if contains_ordered_subsequence(&chronological, &sequence, gap) {
+        return Ok(String::from("1"));
+    }
+
+    window.require_coverage()?;
+    Ok(String::from("0"))
+}
+
+/// Checks if the user played tracks from at least `threshold` distinct albums since `after`.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `after` - Start of the window, as a Unix timestamp in milliseconds.
+/// * `threshold` - The minimum number of distinct albums.
+///
+/// Several plays from the same album, including repeats of one track, count once.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format, or an [`Indeterminate`] error if fewer albums
+/// were found and the history does not reach back to `after`.
+pub async fn can_claim_distinct_albums_in_history_threshold(
+    authorization: String,
+    after: i64,
+    threshold: u8,
+) -> Result<String, Box<dyn Error>> {
+    let window = recently_played_since(authorization, after).await?;
+
+    let mut albums: HashSet<&str> = HashSet::new();
+    for played in &window.plays {
+        if albums.len() >= threshold as usize {
+            break;
+        }
+        albums.insert(played.track.album.id.as_str());
+    }
+    if albums.len() >= threshold as usize {
+        return Ok(String::from("1"));
+    }
+
+    window.require_coverage()?;
+    Ok(String::from("0"))
+}
+
+/// Checks if a track was played at least `threshold` times in the user's last 50 plays.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to count.
+/// * `threshold` - The minimum number of plays.
+///
+/// Only the most recent page of history is read, so the answer is about the
+/// last 50 plays rather than any time window.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_track_repeated_listening(
+    authorization: String,
+    track_id: String,
+    threshold: u8,
+) -> Result<String, Box<dyn Error>> {
+    let endpoint = format!(
+        "{}/v1/me/player/recently-played?limit=50",
+        spotify_api_base()
+    );
+    let page = spotify_api_request::<RecentlyPlayed>(endpoint, authorization).await?;
+    let count = page
+        .items
+        .iter()
+        .filter(|played| played.track.id == track_id)
+        .count();
+    if count >= usize::from(threshold) {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if the user played at least `threshold` tracks in one listening session
+/// since `after`.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `threshold` - The minimum number of plays in a session.
+/// * `after` - Start of the window, as a Unix timestamp in milliseconds.
+///
+/// Sessions are split with [`listening_sessions`]. Repeats of one track count once
+/// per play, and a session running across `after` only counts its later plays.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format, or an [`Indeterminate`] error if no long enough
+/// session was found and the history does not reach back to `after`.
+pub async fn can_claim_multi_track_session(
+    authorization: String,
+    threshold: u8,
+    after: i64,
+) -> Result<String, Box<dyn Error>> {
+    let window = recently_played_since(authorization, after).await?;
+
+    let longest = listening_sessions(&window.plays)
+        .iter()
+        .map(|session| session.len())
+        .max()
+        .unwrap_or_default();
+    if longest >= usize::from(threshold) {
+        return Ok(String::from("1"));
+    }
+
+    window.require_coverage()?;
+    Ok(String::from("0"))
+}
+
+/// Splits plays (newest first, as Spotify returns them) into listening sessions:
+/// runs of plays each starting less than `SESSION_GAP_MINUTES` after the previous one.
+pub fn listening_sessions(plays: &[PlayedTrack]) -> Vec<&[PlayedTrack]> {
+    let gap = Duration::minutes(SESSION_GAP_MINUTES);
+    plays
+        .chunk_by(|newer, older| newer.played_at - older.played_at < gap)
+        .collect()
+}
+
+/// Checks if a track is among the user's `list_range` most played tracks in the
+/// morning since `after`.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to check.
+/// * `after` - Start of the window, as a Unix timestamp in milliseconds.
+/// * `list_range` - How many of the most played morning tracks to consider.
+///
+/// Mornings are 06:00 to 10:00 UTC (see `MORNING_HOURS`). Tracks tie by play
+/// count, so the track is in the top `list_range` when fewer than `list_range`
+/// other tracks were played more often. A track without morning plays is never in it.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format, or an [`Indeterminate`] error if the history does
+/// not reach back to `after`, as the ranking could change either way.
+pub async fn can_claim_track_in_morning_top(
+    authorization: String,
+    track_id: String,
+    after: i64,
+    list_range: u8,
+) -> Result<String, Box<dyn Error>> {
+    let window = recently_played_since(authorization, after).await?;
+    window.require_coverage()?;
+
+    let (start, end) = MORNING_HOURS;
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for played in &window.plays {
+        if (start..end).contains(&played.played_at.hour()) {
+            *counts.entry(played.track.id.as_str()).or_default() += 1;
+        }
+    }
+
+    let Some(plays) = counts.get(track_id.as_str()) else {
+        return Ok(String::from("0"));
+    };
+    let played_more = counts.values().filter(|count| *count > plays).count();
+    if played_more < usize::from(list_range) {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if the user played a track from a workout playlist since `after`.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to check, or empty for any track.
+/// * `after` - Start of the window, as a Unix timestamp in milliseconds.
+///
+/// A workout playlist has one of `WORKOUT_KEYWORDS` as a whole word of its name,
+/// ignoring case, so "Gym Hits" matches and "Gymnopédies" does not. Playlists come
+/// from the context of each play; plays from albums, artists or no context never
+/// match. Each playlist's name is fetched once.
+///
+/// # Errors
+///
+/// This function will return an error if an API request fails or if a response
+/// is not in the expected format, or an [`Indeterminate`] error if no such play
+/// was found and the history does not reach back to `after`.
+pub async fn can_claim_track_in_workout_context(
+    authorization: String,
+    track_id: String,
+    after: i64,
+) -> Result<String, Box<dyn Error>> {
+    let window = recently_played_since(authorization.clone(), after).await?;
+
+    let mut checked: HashSet<&str> = HashSet::new();
+    for played in &window.plays {
+        if !track_id.is_empty() && played.track.id != track_id {
+            continue;
+        }
+        let Some(playlist_id) = played.context.as_ref().and_then(|c| c.playlist_id()) else {
+            continue;
+        };
+        if !checked.insert(playlist_id) {
+            continue;
+        }
+
+        let endpoint = format!(
+            "{}/v1/playlists/{}?fields=name",
+            spotify_api_base(),
+            playlist_id
+        );
+        let name = match spotify_api_request::<PlaylistName>(endpoint, authorization.clone()).await
+        {
+            Ok(playlist) => playlist.name,
+            Err(e) => match failed_status(e.as_ref()) {
+                Some(StatusCode::FORBIDDEN | StatusCode::NOT_FOUND) => {
+                    leniency::check(Leniency::UnreadableContextPlaylist)?;
+                    continue;
+                }
+                _ => return Err(e),
+            },
+        };
+        if is_workout_playlist(&name) {
+            return Ok(String::from("1"));
+        }
+    }
+
+    window.require_coverage()?;
+    Ok(String::from("0"))
+}
+
+/// Whether a playlist name contains one of `WORKOUT_KEYWORDS` as a whole word.
+fn is_workout_playlist(name: &str) -> bool {
+    name.split(|c: char| !c.is_alphanumeric()).any(|word| {
+        WORKOUT_KEYWORDS
+            .iter()
+            .any(|keyword| word.eq_ignore_ascii_case(keyword))
+    })
+}
+
+/// Checks if the user has listened to at least `threshold` distinct tracks.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `threshold` - The minimum number of distinct tracks.
+///
+/// Spotify does not expose how many tracks a user has listened to, so this counts
+/// the unique tracks across the whole available recently played history and the
+/// top tracks of all three time ranges. It is a lower bound on the real number.
+///
+/// # Errors
+///
+/// This function will return an error if an API request fails or if a response
+/// is not in the expected format.
+pub async fn can_claim_user_has_listened_to_n_tracks_total(
+    authorization: String,
+    threshold: u64,
+) -> Result<String, Box<dyn Error>> {
+    if listened_ids(authorization, false).await?.len() as u64 >= threshold {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if the user has listened to at least `threshold` distinct artists.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `threshold` - The minimum number of distinct artists.
+///
+/// Counts every artist credited on a track in the available recently played
+/// history, plus the top artists of all three time ranges. Like the track count,
+/// this is a lower bound.
+///
+/// # Errors
+///
+/// This function will return an error if an API request fails or if a response
+/// is not in the expected format.
+pub async fn can_claim_user_has_listened_to_n_artists_total(
+    authorization: String,
+    threshold: u64,
+) -> Result<String, Box<dyn Error>> {
+    if listened_ids(authorization, true).await?.len() as u64 >= threshold {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Collects the unique artist (`artists == true`) or track IDs in the user's
+/// recently played history and top items of all three time ranges.
+pub(super) async fn listened_ids(
+    authorization: String,
+    artists: bool,
+) -> Result<HashSet<String>, Box<dyn Error>> {
+    let (history, short_term, medium_term, long_term) = tokio::join!(
+        async {
+            recently_played_since(authorization.clone(), 0)
+                .await
+                .map_err(|e| e.to_string())
+        },
+        top_item_ids(
+            authorization.clone(),
+            artists,
+            TimeRange::ShortTerm,
+            TOP_ITEMS_LIMIT
+        ),
+        top_item_ids(
+            authorization.clone(),
+            artists,
+            TimeRange::MediumTerm,
+            TOP_ITEMS_LIMIT
+        ),
+        top_item_ids(
+            authorization.clone(),
+            artists,
+            TimeRange::LongTerm,
+            TOP_ITEMS_LIMIT
+        ),
+    );
+
+    let mut ids: HashSet<String> = HashSet::new();
+    for played in history?.plays {
+        if artists {
+            ids.extend(played.track.artists.into_iter().map(|artist| artist.id));
+        } else {
+            ids.insert(played.track.id);
+        }
+    }
+    for top_ids in [short_term?, medium_term?, long_term?] {
+        ids.extend(top_ids);
+    }
+    Ok(ids)
+}
+
+/// Checks if the user played at least `threshold` tracks in a row by one artist since `after`.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `artist_id` - The ID of the artist, matched against each track's primary (first) artist.
+/// * `after` - Start of the window, as a Unix timestamp in milliseconds.
+/// * `threshold` - The minimum number of consecutive plays.
+///
+/// A play by any other artist ends the run; repeats of one track count once per play.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format, or an [`Indeterminate`] error if no long enough
+/// run was found and the history does not reach back to `after`.
+pub async fn can_claim_consecutive_same_artist_plays(
+    authorization: String,
+    artist_id: String,
+    after: i64,
+    threshold: u8,
+) -> Result<String, Box<dyn Error>> {
+    let window = recently_played_since(authorization, after).await?;
+    // Spotify lists plays newest first; plays sharing a timestamp keep its order.
+    let primary_artists: Vec<&str> = window
+        .plays
+        .iter()
+        .rev()
+        .map(|played| {
+            played
+                .track
+                .artists
+                .first()
+                .map(|artist| artist.id.as_str())
+                .unwrap_or_else(|| {
+                    leniency::note(Leniency::MissingPrimaryArtist);
+                    ""
+                })
+        })
+        .collect();
+
+    if longest_artist_run(&primary_artists, &artist_id) >= threshold as usize {
+        return Ok(String::from("1"));
+    }
+
+    window.require_coverage()?;
+    Ok(String::from("0"))
+}
+
+/// Checks if the user played a matching track at least `played_times` times since `after`.
+///
+/// A negative answer needs the history to reach back to `after`; otherwise this
+/// fails with [`Indeterminate`].
+async fn check_recently_played(
+    authorization: String,
+    after: i64,
+    played_times: u8,
+    matches: impl Fn(&PlayedTrack) -> bool,
+) -> Result<bool, Box<dyn Error>> {
+    let window = recently_played_since(authorization, after).await?;
+    let plays = window.plays.iter().filter(|played| matches(played)).count();
+    if plays >= played_times as usize {
+        return Ok(true);
+    }
+
+    window.require_coverage()?;
+    Ok(false)
+}
+
+/// Checks if the user played a track fewer than `played_times` times since `after`;
+/// the negation of `can_claim_recently_played_track`, for an input of 1 meaning
+/// the track was not played at all.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track.
+/// * `after` - Start of the window, as a Unix timestamp in milliseconds.
+/// * `played_times` - The number of plays the user must stay below.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format, or an [`Indeterminate`] error if the history
+/// does not reach back to `after`.
+pub async fn can_claim_track_not_in_recently_played(
+    authorization: String,
+    track_id: String,
+    after: i64,
+    played_times: u8,
+) -> Result<String, Box<dyn Error>> {
+    let played = check_recently_played(authorization, after, played_times, |played| {
+        played.track.id == track_id
+    })
+    .await?;
+    if played {
+        return Ok(String::from("0"));
+    }
+    Ok(String::from("1"))
+}
+
+/// Checks if the user played tracks by an artist fewer than `played_times` times
+/// since `after`; any credited artist counts, not only the primary one.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `artist_id` - The ID of the artist.
+/// * `after` - Start of the window, as a Unix timestamp in milliseconds.
+/// * `played_times` - The number of plays the user must stay below.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format, or an [`Indeterminate`] error if the history
+/// does not reach back to `after`.
+pub async fn can_claim_artist_not_recently_played(
+    authorization: String,
+    artist_id: String,
+    after: i64,
+    played_times: u8,
+) -> Result<String, Box<dyn Error>> {
+    let played = check_recently_played(authorization, after, played_times, |played| {
+        played
+            .track
+            .artists
+            .iter()
+            .any(|artist| artist.id == artist_id)
+    })
+    .await?;
+    if played {
+        return Ok(String::from("0"));
+    }
+    Ok(String::from("1"))
+}
+
+/// Returns the length of the longest run of consecutive entries equal to `artist_id`
+/// in `primary_artists`, the primary artist of each play in chronological order.
+fn longest_artist_run(primary_artists: &[&str], artist_id: &str) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    for primary_artist in primary_artists {
+        if *primary_artist == artist_id {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    longest
+}
+
+/// How far apart two consecutive matches of an ordered sequence may be.
+#[derive(Clone, Copy, Debug)]
+struct SequenceGap {
+    /// Most other plays allowed between two matched plays; 0 requires consecutive plays.
+    max_plays: usize,
+    /// Longest allowed pause between two matched plays, if limited.
+    max_pause: Option<Duration>,
+}
+
+/// Looks for `sequence` as an in-order subsequence of `plays` (oldest first),
+/// where each matched play follows the previous match within `gap`.
+///
+/// Every play of `sequence[k]` is considered as the k-th match, not only the
+/// first one found, so repeats and interleaved plays cannot hide a valid match
+/// (`a,b,a,c` contains `a,b,c`; `a,a,a,b` contains `a,a,b` consecutively). Since
+/// plays are in time order, the latest earlier match is always the best
+/// predecessor, which keeps this at one pass over `plays` per sequence entry.
+fn contains_ordered_subsequence(
+    plays: &[(&str, DateTime<Utc>)],
+    sequence: &[&str],
+    gap: SequenceGap,
+) -> bool {
+    let Some((first, rest)) = sequence.split_first() else {
+        return true;
+    };
+
+    // Indices of the plays that can end a match of the sequence so far.
+    let mut ends: Vec<usize> = plays
+        .iter()
+        .enumerate()
+        .filter(|(_, (track_id, _))| track_id == first)
+        .map(|(i, _)| i)
+        .collect();
+
+    for track_id in rest {
+        let mut next = Vec::new();
+        let mut previous = ends.iter().peekable();
+        let mut latest: Option<usize> = None;
+        for (i, (candidate, played_at)) in plays.iter().enumerate() {
+            while let Some(&&end) = previous.peek() {
+                if end >= i {
+                    break;
+                }
+                latest = Some(end);
+                previous.next();
+            }
+            if candidate != track_id {
+                continue;
+            }
+            let Some(end) = latest else {
+                continue;
+            };
+            let within_plays = i - end - 1 <= gap.max_plays;
+            let within_pause = gap
+                .max_pause
+                .is_none_or(|max_pause| *played_at - plays[end].1 <= max_pause);
+            if within_plays && within_pause {
+                next.push(i);
+            }
+        }
+        if next.is_empty() {
+            return false;
+        }
+        ends = next;
+    }
+    !ends.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn album_playthrough_matcher_handles_shuffled_interleaved_and_repeated_plays() {
+        let at = |minute: u32| {
+            chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2024, 5, 1, 10, minute, 0).unwrap()
+        };
+        let album = ["t1", "t2", "t3"];
+        // The album playthrough allows any number of other plays, but at most ten
+        // minutes between album tracks.
+        let gap = SequenceGap {
+            max_plays: usize::MAX,
+            max_pause: Some(chrono::Duration::minutes(10)),
+        };
+        let plays_through = |plays: &[(&str, u32)]| {
+            let plays: Vec<(&str, chrono::DateTime<chrono::Utc>)> = plays
+                .iter()
+                .map(|(track, minute)| (*track, at(*minute)))
+                .collect();
+            contains_ordered_subsequence(&plays, &album, gap)
+        };
+
+        // Shuffled: every track was played, but not in album order.
+        assert!(!plays_through(&[("t2", 0), ("t1", 3), ("t3", 6)]));
+        assert!(!plays_through(&[("t1", 0), ("t3", 3), ("t2", 6)]));
+        // Interleaved with other tracks.
+        assert!(plays_through(&[
+            ("t1", 0),
+            ("x", 2),
+            ("t2", 4),
+            ("y", 6),
+            ("z", 8),
+            ("t3", 10),
+        ]));
+        // Repeated: a replay of an earlier track does not break the playthrough.
+        assert!(plays_through(&[("t1", 0), ("t2", 3), ("t1", 5), ("t3", 8)]));
+        assert!(plays_through(&[
+            ("t1", 0),
+            ("t1", 2),
+            ("t2", 4),
+            ("t2", 6),
+            ("t3", 8)
+        ]));
+        // Repeated after a long pause: the fresh start counts.
+        assert!(plays_through(&[
+            ("t1", 0),
+            ("t1", 40),
+            ("t2", 45),
+            ("t3", 50)
+        ]));
+        // A partial playthrough is not enough.
+        assert!(!plays_through(&[("t1", 0), ("t2", 3)]));
+        // The pause before t3 is too long.
+        assert!(!plays_through(&[("t1", 0), ("t2", 3), ("t3", 30)]));
+    }
+
+    #[test]
+    fn longest_artist_run_resets_on_interleaved_artists() {
+        assert_eq!(longest_artist_run(&[], "x"), 0);
+        assert_eq!(longest_artist_run(&["y", "y"], "x"), 0);
+        assert_eq!(longest_artist_run(&["x", "y", "x", "x", "y", "x"], "x"), 2);
+        assert_eq!(longest_artist_run(&["x", "x", "x", "y", "x", "x"], "x"), 3);
+        // Repeats of the same track (and plays sharing a timestamp) count individually.
+        assert_eq!(longest_artist_run(&["y", "x", "x", "x", "x"], "x"), 4);
+        // Plays without a primary artist break a run too.
+        assert_eq!(longest_artist_run(&["x", "", "x"], "x"), 1);
+    }
+
+    #[test]
+    fn ordered_subsequence_respects_the_gap_limits() {
+        let at = |minute: u32| {
+            chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2024, 5, 1, 10, minute, 0).unwrap()
+        };
+        let plays = [
+            ("a", at(0)),
+            ("x", at(1)),
+            ("b", at(2)),
+            ("x", at(3)),
+            ("x", at(4)),
+            ("c", at(30)),
+        ];
+        let gap = |max_plays, max_pause: Option<i64>| SequenceGap {
+            max_plays,
+            max_pause: max_pause.map(chrono::Duration::minutes),
+        };
+
+        assert!(contains_ordered_subsequence(
+            &plays,
+            &["a", "b"],
+            gap(1, None)
+        ));
+        // 0 requires the tracks back to back.
+        assert!(!contains_ordered_subsequence(
+            &plays,
+            &["a", "b"],
+            gap(0, None)
+        ));
+        assert!(contains_ordered_subsequence(
+            &plays,
+            &["x", "b", "x"],
+            gap(0, None)
+        ));
+        assert!(!contains_ordered_subsequence(
+            &plays,
+            &["a", "b", "c"],
+            gap(1, None)
+        ));
+        assert!(contains_ordered_subsequence(
+            &plays,
+            &["a", "b", "c"],
+            gap(2, None)
+        ));
+        // The pause before "c" is too long.
+        assert!(!contains_ordered_subsequence(
+            &plays,
+            &["a", "b", "c"],
+            gap(usize::MAX, Some(10))
+        ));
+        assert!(!contains_ordered_subsequence(
+            &plays,
+            &["b", "a"],
+            gap(usize::MAX, None)
+        ));
+
+        // A later play of the first track must not discard an earlier partial match.
+        let restarted = [("a", at(0)), ("b", at(1)), ("a", at(2)), ("c", at(3))];
+        assert!(contains_ordered_subsequence(
+            &restarted,
+            &["a", "b", "c"],
+            gap(usize::MAX, None)
+        ));
+        assert!(contains_ordered_subsequence(
+            &restarted,
+            &["a", "b", "c"],
+            gap(1, None)
+        ));
+        // Consecutive matching has to allow the match to start at a later repeat.
+        let repeated = [("a", at(0)), ("a", at(1)), ("a", at(2)), ("b", at(3))];
+        assert!(contains_ordered_subsequence(
+            &repeated,
+            &["a", "a", "b"],
+            gap(0, None)
+        ));
+        assert!(!contains_ordered_subsequence(
+            &repeated,
+            &["a", "b", "b"],
+            gap(0, None)
+        ));
+    }
+}
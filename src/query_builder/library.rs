@@ -0,0 +1,807 @@
+use futures::stream::{self, StreamExt};
+use reqwest::StatusCode;
+use std::collections::HashSet;
+use std::error::Error;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use super::{
+    current_user_query_builder, failed_status, fetch_all_pages, playlist_items_query_builder,
+    spotify_api_base, spotify_api_request, user_playlists_query_builder, MAX_CONCURRENT_REQUESTS,
+    MAX_PAGES,
+};
+use crate::cache::TtlCache;
+use crate::leniency::{self, Leniency};
+use crate::types::{
+    AddedAtPage, FollowedArtists, Paging, PagingTotal, PlaylistCollaborative, PlaylistFollowers,
+    PlaylistItemAddedBy, PlaylistItemExplicit, PlaylistSnapshot, PlaylistSnapshotId,
+};
+
+/// How long library totals are reused, so one proof making several threshold
+/// claims against the same library does not repeat the same requests.
+const LIBRARY_TOTALS_TTL: Duration = Duration::from_secs(60);
+
+/// How long playlist follower counts are reused; they move, so keep this short.
+const PLAYLIST_FOLLOWERS_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// A playlist's follower count and owner ID.
+type PlaylistReach = (Option<u64>, String);
+
+/// Follower count and owner ID per session and playlist. Keyed by token too, so
+/// a private playlist one user can see is never answered for another.
+static PLAYLIST_FOLLOWERS: LazyLock<TtlCache<(String, String), PlaylistReach>> =
+    LazyLock::new(|| TtlCache::new(PLAYLIST_FOLLOWERS_TTL));
+
+static LIBRARY_TOTALS: LazyLock<TtlCache<(String, LibraryTotal), u32>> =
+    LazyLock::new(|| TtlCache::new(LIBRARY_TOTALS_TTL));
+
+/// Maximum number of IDs accepted by `/v1/me/tracks/contains` per call.
+const CONTAINS_BATCH_SIZE: usize = 50;
+
+/// Checks if at least `threshold` tracks of a reference list are in the user's saved tracks.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `reference_ids` - The track IDs to look up in the user's library.
+/// * `threshold` - The minimum number of reference tracks that must be saved.
+///
+/// Repeated IDs in the reference list count once. The list is checked in
+/// batches of 50 IDs, with at most
+/// `MAX_CONCURRENT_REQUESTS` batches in flight. Remaining batches are dropped as
+/// soon as `threshold` hits have been found.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_library_overlap_with_reference_threshold(
+    authorization: String,
+    reference_ids: Vec<String>,
+    threshold: u8,
+) -> Result<String, Box<dyn Error>> {
+    if threshold == 0 {
+        return Ok(String::from("1"));
+    }
+
+    // Deduplicated in order, so one saved track listed K times is one hit.
+    let mut seen = HashSet::new();
+    let reference_ids: Vec<String> = reference_ids
+        .into_iter()
+        .filter(|id| seen.insert(id.clone()))
+        .collect();
+
+    // The request futures are built eagerly so the stream owns them outright;
+    // they do nothing until `buffer_unordered` polls them.
+    let requests: Vec<_> = reference_ids
+        .chunks(CONTAINS_BATCH_SIZE)
+        .map(|batch| {
+            let endpoint = format!(
+                "{}/v1/me/tracks/contains?ids={}",
+                spotify_api_base(),
+                batch.join(",")
+            );
+            spotify_api_request::<Vec<bool>>(endpoint, authorization.clone())
+        })
+        .collect();
+    let mut batches = stream::iter(requests).buffer_unordered(MAX_CONCURRENT_REQUESTS);
+
+    let mut hits: usize = 0;
+    while let Some(saved) = batches.next().await {
+        hits += saved?.into_iter().filter(|is_saved| *is_saved).count();
+
+        if hits >= threshold as usize {
+            return Ok(String::from("1"));
+        }
+    }
+    Ok(String::from("0"))
+}
+
+/// Counts the user's playlists containing a track and checks the count against `threshold`.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to look for.
+/// * `threshold` - The minimum number of playlists.
+///
+/// Returns two values: `"1"`/`"0"` for the comparison, then the number of
+/// playlists (capped at 255, so it fits a `u8`). Every playlist is scanned, with
+/// at most `MAX_CONCURRENT_REQUESTS` playlists fetched at once.
+///
+/// # Errors
+///
+/// This function will return an error if an API request fails or if a response
+/// is not in the expected format.
+pub async fn can_claim_track_on_n_user_playlists(
+    authorization: String,
+    track_id: String,
+    threshold: u8,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let playlists = user_playlists_query_builder(authorization.clone()).await?;
+
+    let requests: Vec<_> = playlists
+        .iter()
+        .map(|playlist| playlist_items_query_builder(authorization.clone(), &playlist.id))
+        .collect();
+    let mut scans = stream::iter(requests).buffer_unordered(MAX_CONCURRENT_REQUESTS);
+
+    let mut count: usize = 0;
+    while let Some(items) = scans.next().await {
+        let contains_track = items?.iter().any(|item| {
+            let id = item.track.as_ref().and_then(|track| track.id.as_deref());
+            if id.is_none() {
+                leniency::note(Leniency::UnavailablePlaylistItems);
+            }
+            id == Some(track_id.as_str())
+        });
+        if contains_track {
+            count += 1;
+        }
+    }
+
+    let claimed = if count >= threshold as usize {
+        "1"
+    } else {
+        "0"
+    };
+    Ok(vec![
+        claimed.to_string(),
+        count.min(u8::MAX as usize).to_string(),
+    ])
+}
+
+/// The parts of a user's library whose size Spotify reports as a paging `total`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum LibraryTotal {
+    SavedTracks,
+    SavedAlbums,
+    Playlists,
+    SavedShows,
+    SavedEpisodes,
+}
+
+impl LibraryTotal {
+    fn endpoint(&self) -> String {
+        let path = match self {
+            LibraryTotal::SavedTracks => "/v1/me/tracks?limit=1",
+            LibraryTotal::SavedAlbums => "/v1/me/albums?limit=1",
+            LibraryTotal::Playlists => "/v1/me/playlists?limit=1",
+            LibraryTotal::SavedShows => "/v1/me/shows?limit=1",
+            LibraryTotal::SavedEpisodes => "/v1/me/episodes?limit=1",
+        };
+        format!("{}{}", spotify_api_base(), path)
+    }
+
+    /// The OAuth scope Spotify requires to read this total.
+    fn scope(&self) -> &'static str {
+        match self {
+            LibraryTotal::Playlists => "playlist-read-private",
+            LibraryTotal::SavedEpisodes => "user-read-playback-position",
+            _ => "user-library-read",
+        }
+    }
+}
+
+/// Returns the size of part of the user's library, reusing totals fetched
+/// within the last `LIBRARY_TOTALS_TTL` for the same session.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `kind` - Which library total to fetch.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format. Authorization failures name the scope required.
+pub async fn library_total(
+    authorization: String,
+    kind: LibraryTotal,
+) -> Result<u32, Box<dyn Error>> {
+    let cache_key = (authorization, kind);
+    if let Some(total) = LIBRARY_TOTALS.get(&cache_key) {
+        return Ok(total);
+    }
+
+    let total = fetch_library_total(cache_key.0.clone(), kind).await?;
+    LIBRARY_TOTALS.insert(cache_key, total);
+    Ok(total)
+}
+
+/// Like [`library_total`], but always asks Spotify.
+async fn fetch_library_total(
+    authorization: String,
+    kind: LibraryTotal,
+) -> Result<u32, Box<dyn Error>> {
+    let page = spotify_api_request::<PagingTotal>(kind.endpoint(), authorization)
+        .await
+        .map_err(|e| match failed_status(e.as_ref()) {
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                format!("{}; the {} scope is required", e, kind.scope()).into()
+            }
+            _ => e,
+        })?;
+    Ok(page.total)
+}
+
+/// Checks if a library total is at least `threshold`.
+async fn library_total_at_least(
+    authorization: String,
+    kind: LibraryTotal,
+    threshold: u64,
+) -> Result<String, Box<dyn Error>> {
+    if library_total(authorization, kind).await? as u64 >= threshold {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if the user follows at least `threshold` podcasts (saved shows).
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `threshold` - The minimum number of saved shows.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_saved_show_count_threshold(
+    authorization: String,
+    threshold: u64,
+) -> Result<String, Box<dyn Error>> {
+    library_total_at_least(authorization, LibraryTotal::SavedShows, threshold).await
+}
+
+/// Checks if the user follows at least `threshold` artists.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `threshold` - The minimum number of followed artists.
+///
+/// Followed artists are counted by walking the cursor-paged list, stopping as
+/// soon as `threshold` is reached.
+///
+/// # Errors
+///
+/// This function will return an error if an API request fails, if a response is
+/// not in the expected format, or if the count is still short of `threshold`
+/// after `MAX_PAGES` pages.
+pub async fn can_claim_user_follows_n_artists(
+    authorization: String,
+    threshold: u64,
+) -> Result<String, Box<dyn Error>> {
+    let mut next = Some(format!(
+        "{}/v1/me/following?type=artist&limit=50",
+        spotify_api_base()
+    ));
+    let mut followed: u64 = 0;
+
+    for _ in 0..MAX_PAGES {
+        if followed >= threshold {
+            return Ok(String::from("1"));
+        }
+        let Some(url) = next.take() else {
+            return Ok(String::from("0"));
+        };
+        let page = spotify_api_request::<FollowedArtists>(url, authorization.clone())
+            .await
+            .map_err(|e| match failed_status(e.as_ref()) {
+                Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                    format!("{}; the user-follow-read scope is required", e).into()
+                }
+                _ => e,
+            })?;
+        followed += page.artists.items.len() as u64;
+        next = page.artists.next;
+    }
+
+    if followed >= threshold {
+        return Ok(String::from("1"));
+    }
+    if next.is_none() {
+        return Ok(String::from("0"));
+    }
+    Err(format!("Followed artists span more than {} pages", MAX_PAGES).into())
+}
+
+/// Checks if a playlist has at least `threshold` followers.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `playlist_id` - The ID of the playlist.
+/// * `threshold` - The minimum number of followers.
+/// * `require_owner` - Whether the playlist must be owned by the user.
+///
+/// Follower counts are reused for `PLAYLIST_FOLLOWERS_TTL` within the same session.
+///
+/// # Errors
+///
+/// This function will return an error if an API request fails or the playlist is
+/// not visible to the user, if `require_owner` is set and the user does not own
+/// the playlist, or if Spotify reports no follower count for it (an
+/// indeterminate answer rather than zero).
+pub async fn can_claim_playlist_follower_threshold(
+    authorization: String,
+    playlist_id: String,
+    threshold: u64,
+    require_owner: bool,
+) -> Result<String, Box<dyn Error>> {
+    let cache_key = (authorization.clone(), playlist_id.clone());
+    let (followers, owner_id) = match PLAYLIST_FOLLOWERS.get(&cache_key) {
+        Some(cached) => cached,
+        None => {
+            let endpoint = format!(
+                "{}/v1/playlists/{}?fields=followers.total,owner.id",
+                spotify_api_base(),
+                playlist_id
+            );
+            let playlist =
+                spotify_api_request::<PlaylistFollowers>(endpoint, authorization.clone())
+                    .await
+                    .map_err(|e| -> Box<dyn Error> {
+                        match failed_status(e.as_ref()) {
+                            Some(StatusCode::UNAUTHORIZED) => {
+                                format!("{}; the playlist-read-private scope is required", e).into()
+                            }
+                            Some(StatusCode::FORBIDDEN | StatusCode::NOT_FOUND) => {
+                                format!("Playlist {} is not visible to this user", playlist_id)
+                                    .into()
+                            }
+                            _ => e,
+                        }
+                    })?;
+            let entry = (playlist.followers.total, playlist.owner.id);
+            PLAYLIST_FOLLOWERS.insert(cache_key, entry.clone());
+            entry
+        }
+    };
+
+    if require_owner && current_user_query_builder(authorization).await?.id != owner_id {
+        return Err(format!("Playlist {} is not owned by this user", playlist_id).into());
+    }
+
+    let Some(followers) = followers else {
+        return Err(format!(
+            "indeterminate: Spotify reports no follower count for playlist {}",
+            playlist_id
+        )
+        .into());
+    };
+    if followers >= threshold {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Fetches the current `snapshot_id` of a playlist.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or the playlist is
+/// not visible to the user.
+pub async fn playlist_snapshot_id(
+    authorization: String,
+    playlist_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let endpoint = format!(
+        "{}/v1/playlists/{}?fields=snapshot_id",
+        spotify_api_base(),
+        playlist_id
+    );
+    let playlist = spotify_api_request::<PlaylistSnapshotId>(endpoint, authorization)
+        .await
+        .map_err(|e| not_visible(e, &playlist_id))?;
+    Ok(playlist.snapshot_id)
+}
+
+/// Names the playlist in errors Spotify gives for playlists hidden from the user.
+fn not_visible(e: Box<dyn Error>, playlist_id: &str) -> Box<dyn Error> {
+    match failed_status(e.as_ref()) {
+        Some(StatusCode::FORBIDDEN | StatusCode::NOT_FOUND) => {
+            format!("Playlist {} is not visible to this user", playlist_id).into()
+        }
+        _ => e,
+    }
+}
+
+/// Checks if a playlist was created before `before`, judged by when its tracks
+/// were added.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `playlist_id` - The ID of the playlist to check.
+/// * `before` - The latest creation time to prove, as a Unix timestamp in milliseconds.
+///
+/// Spotify exposes no creation date, but a playlist exists by the time a track
+/// is added to it. Only the first and last items are fetched: items are usually
+/// appended, so one of them is the earliest addition.
+///
+/// # Errors
+///
+/// This function will return an error if an API request fails or the playlist is
+/// not visible to the user, or if no track was added before `before` (an
+/// indeterminate answer, as the playlist may have been filled later).
+pub async fn can_claim_playlist_created_before(
+    authorization: String,
+    playlist_id: String,
+    before: i64,
+) -> Result<String, Box<dyn Error>> {
+    let endpoint = |offset: u32| {
+        format!(
+            "{}/v1/playlists/{}/tracks?fields=total,items(added_at)&limit=1&offset={}",
+            spotify_api_base(),
+            playlist_id,
+            offset
+        )
+    };
+    let first = spotify_api_request::<AddedAtPage>(endpoint(0), authorization.clone())
+        .await
+        .map_err(|e| not_visible(e, &playlist_id))?;
+    let mut added = first.items;
+    if first.total > 1 {
+        let last = spotify_api_request::<AddedAtPage>(endpoint(first.total - 1), authorization)
+            .await
+            .map_err(|e| not_visible(e, &playlist_id))?;
+        added.extend(last.items);
+    }
+
+    let earliest = added
+        .iter()
+        .filter_map(|item| item.added_at)
+        .map(|added_at| added_at.timestamp_millis())
+        .min();
+    match earliest {
+        Some(earliest) if earliest < before => Ok(String::from("1")),
+        Some(_) => Err(format!(
+            "indeterminate: no track of playlist {} was added before the given time",
+            playlist_id
+        )
+        .into()),
+        None => Err(format!(
+            "indeterminate: playlist {} has no tracks with a known added date",
+            playlist_id
+        )
+        .into()),
+    }
+}
+
+/// Records that a playlist had `snapshot_id` at `now`, given what was observed
+/// of it before.
+pub fn observe_playlist_snapshot(
+    previous: Option<PlaylistSnapshot>,
+    snapshot_id: String,
+    now: i64,
+) -> PlaylistSnapshot {
+    match previous {
+        Some(previous) if previous.snapshot_id == snapshot_id => PlaylistSnapshot {
+            last_seen: now,
+            ..previous
+        },
+        previous => PlaylistSnapshot {
+            snapshot_id,
+            first_seen: now,
+            last_seen: now,
+            changed_after: previous.map(|previous| previous.last_seen),
+        },
+    }
+}
+
+/// Checks if a playlist changed after `cutoff`, a Unix timestamp in seconds.
+///
+/// Spotify exposes no modification time, so the oracle only knows a playlist
+/// changed somewhere between two observations of different `snapshot_id`s;
+/// `snapshot` must include an observation made just now.
+///
+/// # Errors
+///
+/// This function will return an error if the observations cannot tell whether
+/// the playlist changed after `cutoff` (an indeterminate answer), as on the first
+/// observation of a playlist within the window.
+pub fn can_claim_playlist_recently_updated(
+    snapshot: &PlaylistSnapshot,
+    playlist_id: &str,
+    cutoff: i64,
+) -> Result<String, Box<dyn Error>> {
+    match snapshot.changed_after {
+        Some(changed_after) if changed_after >= cutoff => Ok(String::from("1")),
+        _ if snapshot.first_seen <= cutoff => Ok(String::from("0")),
+        Some(_) => Err(format!(
+            "indeterminate: playlist {} changed between observations around the start of the window",
+            playlist_id
+        )
+        .into()),
+        None => Err(format!(
+            "indeterminate: playlist {} was not observed before the start of the window",
+            playlist_id
+        )
+        .into()),
+    }
+}
+
+/// Checks if the oldest track still in the user's library was saved before `before`.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `before` - The time to compare against, as a Unix timestamp in milliseconds.
+///
+/// Saved tracks are listed newest first, so only the last one is fetched. Its
+/// offset comes from a fresh total, not a cached one, which would point past
+/// the end or at a newer track once the library changed.
+///
+/// # Errors
+///
+/// This function will return an error if an API request fails, if a response
+/// is not in the expected format, or if the library changed between the two
+/// requests (an indeterminate answer).
+pub async fn can_claim_first_saved_track_before(
+    authorization: String,
+    before: i64,
+) -> Result<String, Box<dyn Error>> {
+    let total = fetch_library_total(authorization.clone(), LibraryTotal::SavedTracks).await?;
+    if total == 0 {
+        return Ok(String::from("0"));
+    }
+
+    let endpoint = format!(
+        "{}/v1/me/tracks?limit=1&offset={}",
+        spotify_api_base(),
+        total - 1
+    );
+    let page = spotify_api_request::<AddedAtPage>(endpoint, authorization).await?;
+    let Some(oldest) = page.items.first().filter(|_| page.total == total) else {
+        return Err(
+            "indeterminate: saved tracks changed while the oldest one was being read".into(),
+        );
+    };
+    match oldest.added_at {
+        Some(added_at) if added_at.timestamp_millis() < before => Ok(String::from("1")),
+        _ => Ok(String::from("0")),
+    }
+}
+
+/// Checks if a playlist is collaborative and the user added at least one of its items.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `playlist_id` - The ID of the playlist to check.
+///
+/// # Errors
+///
+/// This function will return an error if an API request fails, if the playlist is
+/// not visible to the user, or if it has more than `MAX_PAGES` pages of items.
+pub async fn can_claim_playlist_collaborative_with_user(
+    authorization: String,
+    playlist_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let endpoint = format!(
+        "{}/v1/playlists/{}?fields=collaborative",
+        spotify_api_base(),
+        playlist_id
+    );
+    let playlist = spotify_api_request::<PlaylistCollaborative>(endpoint, authorization.clone())
+        .await
+        .map_err(|e| not_visible(e, &playlist_id))?;
+    if !playlist.collaborative {
+        return Ok(String::from("0"));
+    }
+
+    let user_id = current_user_query_builder(authorization.clone()).await?.id;
+    let endpoint = format!(
+        "{}/v1/playlists/{}/tracks?limit=100&fields=limit,offset,total,next,items(added_by.id)",
+        spotify_api_base(),
+        playlist_id
+    );
+    let items = fetch_all_pages::<PlaylistItemAddedBy>(endpoint, authorization)
+        .await
+        .map_err(|e| not_visible(e, &playlist_id))?;
+    let added = items
+        .iter()
+        .filter_map(|item| item.added_by.as_ref())
+        .any(|adder| adder.id == user_id);
+    if added {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if a given user added a track to a playlist, crediting its curation.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `playlist_id` - The ID of the playlist, typically a collaborative one.
+/// * `track_id` - The ID of the track.
+/// * `curator_id` - The Spotify user ID of the curator.
+///
+/// Any occurrence of the track added by the curator counts, including when the
+/// same track was also added by someone else.
+///
+/// # Errors
+///
+/// This function will return an error if an API request fails, if the playlist is
+/// not visible to the user, or if it has more than `MAX_PAGES` pages of items.
+pub async fn can_claim_track_added_by_curator(
+    authorization: String,
+    playlist_id: String,
+    track_id: String,
+    curator_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let endpoint = format!(
+        "{}/v1/playlists/{}/tracks?limit=100&fields=limit,offset,total,next,items(added_by.id,track(id))",
+        spotify_api_base(),
+        playlist_id
+    );
+    let items = fetch_all_pages::<PlaylistItemAddedBy>(endpoint, authorization)
+        .await
+        .map_err(|e| not_visible(e, &playlist_id))?;
+    let added = items.iter().any(|item| {
+        let added_by = item.added_by.as_ref().map(|adder| adder.id.as_str());
+        let track = item.track.as_ref().and_then(|track| track.id.as_deref());
+        added_by == Some(curator_id.as_str()) && track == Some(track_id.as_str())
+    });
+    if added {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Returns how many items of a playlist the user added, as a u8 output value.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `playlist_id` - The ID of the playlist, typically a collaborative one.
+///
+/// The count saturates at 255, the largest value the output holds.
+///
+/// # Errors
+///
+/// This function will return an error if an API request fails, if the playlist is
+/// not visible to the user, or if it has more than `MAX_PAGES` pages of items.
+pub async fn can_claim_curator_track_count(
+    authorization: String,
+    playlist_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let user_id = current_user_query_builder(authorization.clone()).await?.id;
+    let endpoint = format!(
+        "{}/v1/playlists/{}/tracks?limit=100&fields=limit,offset,total,next,items(added_by.id)",
+        spotify_api_base(),
+        playlist_id
+    );
+    let items = fetch_all_pages::<PlaylistItemAddedBy>(endpoint, authorization)
+        .await
+        .map_err(|e| not_visible(e, &playlist_id))?;
+    let added = items
+        .iter()
+        .filter_map(|item| item.added_by.as_ref())
+        .filter(|adder| adder.id == user_id)
+        .count();
+    Ok(u8::try_from(added).unwrap_or(u8::MAX).to_string())
+}
+
+/// Checks if no item of a playlist is marked explicit.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `playlist_id` - The ID of the playlist to check.
+///
+/// Pages are fetched one at a time, stopping at the first explicit item.
+/// Unavailable items cannot be checked and are skipped.
+///
+/// # Errors
+///
+/// This function will return an error if an API request fails, if the playlist is
+/// not visible to the user, or if it has more than `MAX_PAGES` pages of items.
+pub async fn can_claim_playlist_has_no_explicit_tracks(
+    authorization: String,
+    playlist_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let mut next = Some(format!(
+        "{}/v1/playlists/{}/tracks?limit=100&fields=limit,offset,total,next,items(track(explicit))",
+        spotify_api_base(),
+        playlist_id
+    ));
+
+    for _ in 0..MAX_PAGES {
+        let Some(url) = next.take() else {
+            return Ok(String::from("1"));
+        };
+        let page = spotify_api_request::<Paging<PlaylistItemExplicit>>(url, authorization.clone())
+            .await
+            .map_err(|e| not_visible(e, &playlist_id))?;
+        for item in page.items {
+            match item.track {
+                Some(track) if track.explicit => return Ok(String::from("0")),
+                Some(_) => {}
+                None => leniency::check(Leniency::UnavailablePlaylistItems)?,
+            }
+        }
+        next = page.next;
+    }
+
+    if next.is_some() {
+        return Err(format!("Result spans more than {} pages", MAX_PAGES).into());
+    }
+    Ok(String::from("1"))
+}
+
+/// Checks if the user has at least `threshold` saved tracks.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `threshold` - The minimum number of saved tracks.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_user_has_n_saved_tracks(
+    authorization: String,
+    threshold: u32,
+) -> Result<String, Box<dyn Error>> {
+    library_total_at_least(authorization, LibraryTotal::SavedTracks, threshold as u64).await
+}
+
+/// Checks if the user has at least `threshold` saved albums.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `threshold` - The minimum number of saved albums.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_user_has_n_saved_albums(
+    authorization: String,
+    threshold: u32,
+) -> Result<String, Box<dyn Error>> {
+    library_total_at_least(authorization, LibraryTotal::SavedAlbums, threshold as u64).await
+}
+
+/// Checks if the user has at least `threshold` playlists.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `threshold` - The minimum number of playlists.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_user_has_n_playlists(
+    authorization: String,
+    threshold: u32,
+) -> Result<String, Box<dyn Error>> {
+    library_total_at_least(authorization, LibraryTotal::Playlists, threshold as u64).await
+}
+
+/// Checks if the user has at least `threshold` followed podcasts (saved shows).
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `threshold` - The minimum number of followed podcasts.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_user_has_n_followed_podcasts(
+    authorization: String,
+    threshold: u32,
+) -> Result<String, Box<dyn Error>> {
+    library_total_at_least(authorization, LibraryTotal::SavedShows, threshold as u64).await
+}
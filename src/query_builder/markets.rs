@@ -0,0 +1,202 @@
+use futures::stream::{self, StreamExt};
+use reqwest::{Client, StatusCode};
+use std::error::Error;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use super::history::artist_top_track_ids;
+use super::{
+    failed_status, spotify_api_base, spotify_api_request, track_query_builder,
+    MAX_CONCURRENT_REQUESTS,
+};
+use crate::cache::TtlCache;
+use crate::types::{AlbumMarkets, Markets};
+use crate::usage::{record, Outcome};
+
+/// Spotify markets at the time of writing; an item available in all of them is
+/// a global release.
+pub const SPOTIFY_MARKET_COUNT: u8 = 184;
+
+/// How long an artist's market count is reused; counting takes one request per market.
+const ARTIST_MARKET_COUNT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Number of markets an artist has top tracks in, keyed by artist ID.
+static ARTIST_MARKET_COUNTS: LazyLock<TtlCache<String, usize>> =
+    LazyLock::new(|| TtlCache::new(ARTIST_MARKET_COUNT_TTL));
+
+/// Shared so preview checks reuse connections. Previews are served from
+/// Spotify's CDN, which takes no authorization.
+static PREVIEW_CLIENT: LazyLock<Client> = LazyLock::new(Client::new);
+
+/// Answers whether `markets` lists at least `threshold` markets.
+fn available_in_n_markets(markets: &[String], threshold: u8) -> String {
+    if markets.len() >= usize::from(threshold) {
+        return String::from("1");
+    }
+    String::from("0")
+}
+
+/// Turns a 400 or 404 from a catalog lookup into an "Unknown <kind>" error.
+pub(super) fn unknown_item(
+    kind: &'static str,
+    id: &str,
+) -> impl FnOnce(Box<dyn Error>) -> Box<dyn Error> {
+    let id = id.to_string();
+    move |e| match failed_status(e.as_ref()) {
+        Some(StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND) => {
+            format!("Unknown {}: {}", kind, id).into()
+        }
+        _ => e,
+    }
+}
+
+/// Checks if a track is available in at least `threshold` markets.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track.
+/// * `threshold` - The minimum number of markets; see [`SPOTIFY_MARKET_COUNT`].
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails, if the track is
+/// unknown, or if the response is not in the expected format.
+pub async fn can_claim_track_available_in_n_markets(
+    authorization: String,
+    track_id: String,
+    threshold: u8,
+) -> Result<String, Box<dyn Error>> {
+    let track = track_query_builder(authorization, &track_id)
+        .await
+        .map_err(unknown_item("track", &track_id))?;
+    Ok(available_in_n_markets(&track.available_markets, threshold))
+}
+
+/// Checks if a track's 30-second preview can actually be fetched.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track.
+///
+/// A track without a `preview_url` answers "0". Otherwise the URL is sent a
+/// `HEAD` request and only a 200 counts: some previews are listed but refused
+/// with a 403 in certain regions, so a non-null URL alone proves nothing. Other
+/// 4xx responses also answer "0".
+///
+/// # Errors
+///
+/// This function will return an error if the track request fails, if the track
+/// is unknown, or if the preview server cannot be reached or fails with a 5xx.
+pub async fn can_claim_track_preview_url_accessible(
+    authorization: String,
+    track_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let track = track_query_builder(authorization, &track_id)
+        .await
+        .map_err(unknown_item("track", &track_id))?;
+    let Some(preview_url) = track.preview_url else {
+        return Ok(String::from("0"));
+    };
+
+    let template = "/mp3-preview/{id}";
+    let status = match PREVIEW_CLIENT.head(&preview_url).send().await {
+        Ok(response) => response.status(),
+        Err(e) => {
+            record("spotify_preview", template, Outcome::Transport, None);
+            return Err(format!("Preview request failed: {}", e).into());
+        }
+    };
+    record(
+        "spotify_preview",
+        template,
+        Outcome::from_status(status),
+        Some(status.as_u16()),
+    );
+
+    if status.is_server_error() {
+        return Err(format!("Preview request failed with status: {}", status).into());
+    }
+    if status == StatusCode::OK {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if an album is available in at least `threshold` markets.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `album_id` - The ID of the album.
+/// * `threshold` - The minimum number of markets; see [`SPOTIFY_MARKET_COUNT`].
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails, if the album is
+/// unknown, or if the response is not in the expected format.
+pub async fn can_claim_album_available_in_n_markets(
+    authorization: String,
+    album_id: String,
+    threshold: u8,
+) -> Result<String, Box<dyn Error>> {
+    let endpoint = format!("{}/v1/albums/{}", spotify_api_base(), album_id);
+    let album = spotify_api_request::<AlbumMarkets>(endpoint, authorization)
+        .await
+        .map_err(unknown_item("album", &album_id))?;
+    Ok(available_in_n_markets(&album.available_markets, threshold))
+}
+
+/// Checks if an artist has top tracks in at least `threshold` markets.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `artist_id` - The ID of the artist.
+/// * `threshold` - The minimum number of markets; see [`SPOTIFY_MARKET_COUNT`].
+///
+/// Artists have no `available_markets`, so presence is proxied by asking for the
+/// artist's top tracks in every market from `GET /v1/markets`, with at most
+/// `MAX_CONCURRENT_REQUESTS` requests in flight. The count is cached per artist
+/// for an hour.
+///
+/// # Errors
+///
+/// This function will return an error if any API request fails, if the artist is
+/// unknown, or if a response is not in the expected format.
+pub async fn can_claim_artist_markets_count(
+    authorization: String,
+    artist_id: String,
+    threshold: u8,
+) -> Result<String, Box<dyn Error>> {
+    let count = match ARTIST_MARKET_COUNTS.get(&artist_id) {
+        Some(count) => count,
+        None => {
+            let endpoint = format!("{}/v1/markets", spotify_api_base());
+            let markets = spotify_api_request::<Markets>(endpoint, authorization.clone())
+                .await?
+                .markets;
+
+            let requests: Vec<_> = markets
+                .into_iter()
+                .map(|market| artist_top_track_ids(authorization.clone(), &artist_id, market))
+                .collect();
+            let mut lookups = stream::iter(requests).buffer_unordered(MAX_CONCURRENT_REQUESTS);
+
+            let mut count: usize = 0;
+            while let Some(track_ids) = lookups.next().await {
+                if !track_ids?.is_empty() {
+                    count += 1;
+                }
+            }
+            ARTIST_MARKET_COUNTS.insert(artist_id.clone(), count);
+            count
+        }
+    };
+
+    if count >= usize::from(threshold) {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
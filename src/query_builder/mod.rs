@@ -1,9 +1,109 @@
-use reqwest::header::{HeaderMap, HeaderValue};
-use reqwest::Client;
+use futures::stream::{self, StreamExt};
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use reqwest::redirect::Policy;
+use reqwest::{Client, StatusCode};
 use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet};
+use std::env;
 use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
 
-use crate::types::{AristsStatsResponse, RecentlyPlayed, TimeRange, TracksStatsResponse};
+use crate::cache::PendingRequestCache;
+use crate::leniency::{self, Leniency};
+use crate::telemetry::{current_traceparent, in_span, SpanKind};
+use crate::types::{
+    AlbumTrack, AristsStatsResponse, ArtistFollowers, CurrentUser, ListeningEvolution, Paging,
+    PlaylistItem, RecentlyPlayed, SeveralArtists, SimplifiedPlaylist, TimeRange, Track,
+    TracksStatsResponse,
+};
+use crate::usage::{endpoint_template, record, Outcome};
+
+pub mod audio_analysis;
+pub mod audiobooks;
+pub mod biography;
+pub mod concerts;
+pub mod covers;
+pub mod devices;
+pub mod discography;
+pub mod exclusivity;
+pub mod history;
+pub mod library;
+pub mod markets;
+pub mod podcasts;
+pub mod royalty_free;
+pub mod sampling;
+pub mod sync_licensing;
+pub mod videos;
+
+/// Upper bound on concurrent Spotify requests issued on behalf of a single claim.
+pub const MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// Maximum number of pages `fetch_all_pages` follows before giving up.
+pub const MAX_PAGES: usize = 20;
+
+/// Attempts made for a Spotify call answered with an interstitial (an HTML page
+/// or a redirect), which maintenance windows and challenges usually clear soon.
+const UNAVAILABLE_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry of an interstitial; doubled for each further one.
+const UNAVAILABLE_BACKOFF: Duration = Duration::from_millis(100);
+
+/// How many top tracks of each user the shared top track claim considers.
+const SHARED_TOP_TRACKS_LIMIT: u8 = 50;
+
+/// Most artist IDs `GET /v1/artists` accepts in one request.
+const ARTISTS_PER_REQUEST: usize = 50;
+
+/// Returns the base URL of the Spotify Web API.
+///
+/// Defaults to `https://api.spotify.com`; `SPOTIFY_API_URL` overrides it, e.g. to
+/// point the oracle at a mock server.
+pub fn spotify_api_base() -> String {
+    env::var("SPOTIFY_API_URL")
+        .map(|url| url.trim_end_matches('/').to_string())
+        .unwrap_or_else(|_| String::from("https://api.spotify.com"))
+}
+
+/// Returns the market (ISO 3166-1 alpha-2 country code) used for catalog requests
+/// that depend on it, from `SPOTIFY_MARKET`; defaults to `US`.
+pub fn spotify_market() -> String {
+    env::var("SPOTIFY_MARKET").unwrap_or_else(|_| String::from("US"))
+}
+
+/// Outcome of one GET request to the Spotify API, shared by every caller that
+/// asked for the same URL with the same token while it was in flight.
+#[derive(Clone)]
+enum Fetched {
+    Json(Arc<String>),
+    Failed(StatusCode),
+    /// A redirect, or a success status with a non-API body.
+    Unavailable {
+        status: StatusCode,
+    },
+    /// No response at all.
+    Transport(Arc<String>),
+}
+
+impl fmt::Display for Fetched {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fetched::Json(_) => write!(f, "ok"),
+            Fetched::Failed(status) => write!(f, "Request failed with status: {}", status),
+            Fetched::Unavailable { status } => write!(
+                f,
+                "provider returned non-API response, status {}",
+                status.as_u16()
+            ),
+            Fetched::Transport(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Spotify requests in flight, keyed by endpoint and authorization.
+static PENDING_REQUESTS: LazyLock<PendingRequestCache<(String, String), Fetched>> =
+    LazyLock::new(PendingRequestCache::default);
 
 /// Performs a GET request to the Spotify API.
 ///
@@ -13,11 +113,14 @@ use crate::types::{AristsStatsResponse, RecentlyPlayed, TimeRange, TracksStatsRe
 /// * `authorization` - The Bearer token for the user's Spotify API session.
 /// * `response_type` - The type of response to expect from the API.
 ///
+/// Concurrent requests for the same endpoint with the same token, e.g. from
+/// proofs of the same input generated in parallel, share a single Spotify call.
+///
 /// # Errors
 ///
 /// This function will return an error if the API request fails or if the response
-/// is not in the expected format.
-
+/// is not in the expected format. Redirects and HTML/non-JSON bodies are retried
+/// a few times, then reported as [`ProviderUnavailable`].
 pub async fn spotify_api_request<T>(
     endpoint: String,
     authorization: String,
@@ -25,26 +128,202 @@ pub async fn spotify_api_request<T>(
 where
     T: DeserializeOwned,
 {
-    let client = Client::new();
+    let key = (endpoint.clone(), authorization.clone());
+    match PENDING_REQUESTS
+        .get_or_run(key, || fetch(endpoint, authorization))
+        .await
+    {
+        // Deserialize the JSON response
+        Fetched::Json(body) => Ok(serde_json::from_str::<T>(&body)?),
+        // Handle HTTP errors gracefully
+        Fetched::Failed(status) => Err(Box::new(RequestFailed { status })),
+        Fetched::Unavailable { status } => {
+            // Checked per caller, since each runs under its own strict mode policy.
+            if !status.is_redirection() {
+                leniency::check(Leniency::ResponseFormatHeuristic)?;
+            }
+            Err(Box::new(ProviderUnavailable { status }))
+        }
+        Fetched::Transport(message) => Err(message.to_string().into()),
+    }
+}
+
+/// Performs one Spotify call, traced as a client span.
+///
+/// Interstitial answers are retried up to `UNAVAILABLE_ATTEMPTS` times in all,
+/// backing off exponentially from `UNAVAILABLE_BACKOFF`; every attempt is
+/// counted in the usage metrics as `unavailable`, so challenge rates show.
+/// Other failures are not retried.
+async fn fetch(endpoint: String, authorization: String) -> Fetched {
+    let name = format!("GET {}", endpoint_template(&endpoint));
+    let call = async {
+        let mut backoff = UNAVAILABLE_BACKOFF;
+        let mut attempt = 1;
+        loop {
+            match fetch_once(endpoint.clone(), authorization.clone()).await {
+                fetched @ Fetched::Json(_) => return Ok(fetched),
+                Fetched::Unavailable { .. } if attempt < UNAVAILABLE_ATTEMPTS => {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                fetched => return Err(fetched),
+            }
+        }
+    };
+    in_span(&name, SpanKind::Client, call)
+        .await
+        .unwrap_or_else(|fetched| fetched)
+}
+
+async fn fetch_once(endpoint: String, authorization: String) -> Fetched {
+    // Redirects are never followed: Spotify only redirects API calls to
+    // interstitial pages (maintenance, bot challenges), never to JSON.
+    let client = match Client::builder().redirect(Policy::none()).build() {
+        Ok(client) => client,
+        Err(e) => return Fetched::Transport(Arc::new(e.to_string())),
+    };
 
     // Build headers
     let mut headers = HeaderMap::new();
-    headers.insert("Authorization", HeaderValue::from_str(&authorization)?);
+    match HeaderValue::from_str(&authorization) {
+        Ok(value) => headers.insert("Authorization", value),
+        Err(e) => return Fetched::Transport(Arc::new(e.to_string())),
+    };
+    if let Some(value) = current_traceparent().and_then(|tp| HeaderValue::from_str(&tp).ok()) {
+        headers.insert("traceparent", value);
+    }
 
-    println!("Endpoint: {}, headers: {:#?}", endpoint, headers);
     // Make the GET request
-    let response = client.get(endpoint).headers(headers).send().await?;
+    let response = match client.get(&endpoint).headers(headers).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            record("spotify", &endpoint, Outcome::Transport, None);
+            return Fetched::Transport(Arc::new(e.to_string()));
+        }
+    };
 
-    // Check for HTTP success
-    if response.status().is_success() {
-        // Deserialize the JSON response
-        Ok(response.json::<T>().await?)
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.starts_with("application/json"))
+        .unwrap_or(false);
+    let body = match response.text().await {
+        Ok(body) => body,
+        Err(e) => return Fetched::Transport(Arc::new(e.to_string())),
+    };
+
+    // Classify interstitials before attempting to deserialize them, so the
+    // caller never sees a serde error (or the page itself).
+    if status.is_redirection() || (status.is_success() && !is_json) || looks_like_html(&body) {
+        record(
+            "spotify",
+            &endpoint,
+            Outcome::Unavailable,
+            Some(status.as_u16()),
+        );
+        return Fetched::Unavailable { status };
+    }
+    record(
+        "spotify",
+        &endpoint,
+        Outcome::from_status(status),
+        Some(status.as_u16()),
+    );
+
+    if status.is_success() {
+        Fetched::Json(Arc::new(body))
     } else {
-        // Handle HTTP errors gracefully
-        Err(format!("Request failed with status: {}", response.status()).into())
+        Fetched::Failed(status)
     }
 }
 
+/// Returned when Spotify answers an API request with an unsuccessful status.
+#[derive(Debug)]
+pub struct RequestFailed {
+    pub status: StatusCode,
+}
+
+impl fmt::Display for RequestFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Request failed with status: {}", self.status)
+    }
+}
+
+impl Error for RequestFailed {}
+
+/// Returns the HTTP status of a failed Spotify request, if that is what `error` is.
+pub fn failed_status(error: &(dyn Error + 'static)) -> Option<StatusCode> {
+    error
+        .downcast_ref::<RequestFailed>()
+        .map(|failed| failed.status)
+}
+
+/// Returned when Spotify answers with something other than its JSON API,
+/// such as an HTML maintenance page or a redirect to a challenge.
+///
+/// The message deliberately carries only the status code; the body is never
+/// included so provider markup cannot leak into client-facing errors.
+#[derive(Debug)]
+pub struct ProviderUnavailable {
+    pub status: StatusCode,
+}
+
+impl fmt::Display for ProviderUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "provider returned non-API response, status {}",
+            self.status.as_u16()
+        )
+    }
+}
+
+impl Error for ProviderUnavailable {}
+
+fn looks_like_html(body: &str) -> bool {
+    let start = body.trim_start();
+    start.starts_with('<')
+}
+
+/// Collects the items of an offset-paged Spotify endpoint by following `next`.
+///
+/// # Arguments
+///
+/// * `endpoint` - The URL of the first page.
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+///
+/// # Errors
+///
+/// This function will return an error if any page request fails, if a response
+/// is not in the expected format, or if there are more than `MAX_PAGES` pages.
+pub async fn fetch_all_pages<T>(
+    endpoint: String,
+    authorization: String,
+) -> Result<Vec<T>, Box<dyn Error>>
+where
+    T: DeserializeOwned,
+{
+    let mut items = Vec::new();
+    let mut next = Some(endpoint);
+
+    for _ in 0..MAX_PAGES {
+        let Some(url) = next.take() else {
+            return Ok(items);
+        };
+        let page = spotify_api_request::<Paging<T>>(url, authorization.clone()).await?;
+        items.extend(page.items);
+        next = page.next;
+    }
+
+    if next.is_some() {
+        return Err(format!("Result spans more than {} pages", MAX_PAGES).into());
+    }
+    Ok(items)
+}
+
 /// Builds a query to the Spotify API to fetch a user's top artists or tracks.
 ///
 /// # Arguments
@@ -69,24 +348,20 @@ pub async fn stats_query_builder<T>(
 where
     T: DeserializeOwned,
 {
-    // Convert `TimeRange` enum to string
-    let time_range = match range {
-        TimeRange::ShortTerm => "short_term",
-        TimeRange::MediumTerm => "medium_term",
-        TimeRange::LongTerm => "long_term",
-    };
-
     // Convert `query_type` boolean to corresponding Spotify API type
     let query_type_string = if query_type { "artists" } else { "tracks" };
 
     // Construct the API endpoint URL
     let endpoint = format!(
-        "https://api.spotify.com/v1/me/top/{query_type_string}?time_range={}&limit={}&offset={}",
-        time_range, limit, offset
+        "{}/v1/me/top/{query_type_string}?time_range={}&limit={}&offset={}",
+        spotify_api_base(),
+        range,
+        limit,
+        offset
     );
 
     // Add Bearer token to the authorization header
-    let auth_header = format!("{}", authorization);
+    let auth_header = authorization.to_string();
 
     // Perform the API request
     let response = spotify_api_request::<T>(endpoint, auth_header).await?;
@@ -112,14 +387,114 @@ pub async fn recently_played_query_builder(
     after: u64,
 ) -> Result<RecentlyPlayed, Box<dyn Error>> {
     let endpoint = format!(
-        "https://api.spotify.com/v1/me/player/recently-played?after={}",
+        "{}/v1/me/player/recently-played?after={}",
+        spotify_api_base(),
         after
     );
-    let auth_header = format!("{}", authorization);
+    let auth_header = authorization.to_string();
     let response = spotify_api_request::<RecentlyPlayed>(endpoint, auth_header).await?;
     Ok(response)
 }
 
+/// Fetches a single track from the Spotify catalog.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to fetch.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn track_query_builder(
+    authorization: String,
+    track_id: &str,
+) -> Result<Track, Box<dyn Error>> {
+    let endpoint = format!("{}/v1/tracks/{}", spotify_api_base(), track_id);
+    spotify_api_request::<Track>(endpoint, authorization).await
+}
+
+/// Fetches the tracks of an album in disc and track order.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `album_id` - The ID of the album.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn album_tracks_query_builder(
+    authorization: String,
+    album_id: &str,
+) -> Result<Vec<AlbumTrack>, Box<dyn Error>> {
+    let endpoint = format!(
+        "{}/v1/albums/{}/tracks?limit=50",
+        spotify_api_base(),
+        album_id
+    );
+    fetch_all_pages::<AlbumTrack>(endpoint, authorization).await
+}
+
+/// Fetches the profile of the user the session belongs to.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn current_user_query_builder(
+    authorization: String,
+) -> Result<CurrentUser, Box<dyn Error>> {
+    let endpoint = format!("{}/v1/me", spotify_api_base());
+    spotify_api_request::<CurrentUser>(endpoint, authorization).await
+}
+
+/// Fetches every playlist the user owns or follows.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn user_playlists_query_builder(
+    authorization: String,
+) -> Result<Vec<SimplifiedPlaylist>, Box<dyn Error>> {
+    let endpoint = format!("{}/v1/me/playlists?limit=50", spotify_api_base());
+    fetch_all_pages::<SimplifiedPlaylist>(endpoint, authorization).await
+}
+
+/// Fetches the items of a playlist, requesting only the track IDs.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `playlist_id` - The ID of the playlist.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn playlist_items_query_builder(
+    authorization: String,
+    playlist_id: &str,
+) -> Result<Vec<PlaylistItem>, Box<dyn Error>> {
+    let endpoint = format!(
+        "{}/v1/playlists/{}/tracks?limit=100&fields=limit,offset,total,next,items(track(id))",
+        spotify_api_base(),
+        playlist_id
+    );
+    fetch_all_pages::<PlaylistItem>(endpoint, authorization).await
+}
+
 /// Checks if the user can claim a given track in the top tracks of a given list range.
 ///
 /// # Arguments
@@ -147,7 +522,295 @@ pub async fn can_claim_top_tracks(
             return Ok(String::from("1"));
         }
     }
-    return Ok(String::from("0"));
+    Ok(String::from("0"))
+}
+
+/// Checks if the user's top tracks were released in at least `threshold` distinct decades.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `threshold` - The minimum number of distinct decades.
+/// * `time_range` - The time range of the top tracks.
+/// * `list_range` - How many top tracks to consider.
+///
+/// Release years come from the album embedded in each top track, so no further
+/// requests are made. Albums with an unparsable release date are skipped.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_top_tracks_span_multiple_decades(
+    authorization: String,
+    threshold: u8,
+    time_range: TimeRange,
+    list_range: u8,
+) -> Result<String, Box<dyn Error>> {
+    let query =
+        stats_query_builder::<TracksStatsResponse>(authorization, false, time_range, list_range, 0)
+            .await?;
+    let decades: HashSet<u32> = query
+        .items
+        .iter()
+        .filter_map(release_year)
+        .map(|year| year / 10)
+        .collect();
+    if decades.len() >= usize::from(threshold) {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Counts how many of the user's top tracks were released in `decade`.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `time_range` - The time range of the top tracks.
+/// * `decade` - The first year of the decade, e.g. 1990 for 1990-1999.
+/// * `list_range` - How many top tracks to consider.
+///
+/// The count saturates at 255. Albums with an unparsable release date are not
+/// counted.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_top_tracks_decade_count(
+    authorization: String,
+    time_range: TimeRange,
+    decade: u16,
+    list_range: u8,
+) -> Result<String, Box<dyn Error>> {
+    let query =
+        stats_query_builder::<TracksStatsResponse>(authorization, false, time_range, list_range, 0)
+            .await?;
+    let count = query
+        .items
+        .iter()
+        .filter_map(release_year)
+        .filter(|year| year / 10 == u32::from(decade) / 10)
+        .count();
+    Ok(u8::try_from(count).unwrap_or(u8::MAX).to_string())
+}
+
+/// The year of a track's album release date, whatever its precision.
+fn release_year(track: &Track) -> Option<u32> {
+    let year = track.album.release_date.get(..4)?.parse::<u32>().ok()?;
+    (year > 0).then_some(year)
+}
+
+/// Checks if every track in the user's top tracks is marked explicit.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `time_range` - The time range of the top tracks.
+/// * `list_range` - How many top tracks must all be explicit.
+///
+/// An empty top tracks list answers "0", as for
+/// [`can_claim_top_tracks_all_by_same_artist`].
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_top_tracks_all_explicit(
+    authorization: String,
+    time_range: TimeRange,
+    list_range: u8,
+) -> Result<String, Box<dyn Error>> {
+    let query =
+        stats_query_builder::<TracksStatsResponse>(authorization, false, time_range, list_range, 0)
+            .await?;
+    if !query.items.is_empty() && query.items.iter().all(|track| track.explicit) {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if every track in the user's top tracks has `artist_id` as its primary artist.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `artist_id` - The ID of the artist.
+/// * `time_range` - The time range of the top tracks.
+/// * `list_range` - How many top tracks must all be by the artist.
+///
+/// An empty top tracks list answers "0": it proves no affinity at all.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_top_tracks_all_by_same_artist(
+    authorization: String,
+    artist_id: String,
+    time_range: TimeRange,
+    list_range: u8,
+) -> Result<String, Box<dyn Error>> {
+    let query =
+        stats_query_builder::<TracksStatsResponse>(authorization, false, time_range, list_range, 0)
+            .await?;
+    if query.items.is_empty() {
+        return Ok(String::from("0"));
+    }
+    for track in query.items {
+        let primary = match track.artists.first() {
+            Some(artist) => artist.id.as_str(),
+            None => {
+                leniency::check(Leniency::MissingPrimaryArtist)?;
+                ""
+            }
+        };
+        if primary != artist_id {
+            return Ok(String::from("0"));
+        }
+    }
+    Ok(String::from("1"))
+}
+
+/// Checks if a track is in the user's top tracks for all three time ranges at once.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to check.
+/// * `list_range` - How many top tracks to consider in each time range.
+///
+/// The three ranges are fetched in parallel.
+///
+/// # Errors
+///
+/// This function will return an error if any of the API requests fails or if a
+/// response is not in the expected format.
+pub async fn can_claim_track_in_all_top_ranges(
+    authorization: String,
+    track_id: String,
+    list_range: u8,
+) -> Result<String, Box<dyn Error>> {
+    let (short_term, medium_term, long_term) = tokio::join!(
+        is_top_item(
+            authorization.clone(),
+            &track_id,
+            false,
+            TimeRange::ShortTerm,
+            list_range
+        ),
+        is_top_item(
+            authorization.clone(),
+            &track_id,
+            false,
+            TimeRange::MediumTerm,
+            list_range
+        ),
+        is_top_item(
+            authorization,
+            &track_id,
+            false,
+            TimeRange::LongTerm,
+            list_range
+        ),
+    );
+
+    if short_term? && medium_term? && long_term? {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if a track is in the user's top tracks for two time ranges.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to check.
+/// * `time_ranges` - The two time ranges, e.g. short and long term.
+/// * `list_range` - How many top tracks to consider in each time range.
+///
+/// Both ranges are fetched in parallel and combined with [`all_windows_hold`].
+///
+/// # Errors
+///
+/// This function will return an error if either API request fails or if a
+/// response is not in the expected format, even when the other range alone
+/// already rules the track out.
+pub async fn can_claim_top_tracks_stability(
+    authorization: String,
+    track_id: String,
+    time_ranges: (TimeRange, TimeRange),
+    list_range: u8,
+) -> Result<String, Box<dyn Error>> {
+    let (first, second) = tokio::join!(
+        is_top_item(
+            authorization.clone(),
+            &track_id,
+            false,
+            time_ranges.0,
+            list_range
+        ),
+        is_top_item(authorization, &track_id, false, time_ranges.1, list_range),
+    );
+
+    if all_windows_hold([first, second])? {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Combines the answers of a claim that must hold in every window.
+///
+/// Any error wins over the other answers, including `false` ones, so a failed
+/// lookup is never reported as a negative (soundness over availability).
+pub fn all_windows_hold(
+    results: impl IntoIterator<Item = Result<bool, String>>,
+) -> Result<bool, String> {
+    let mut holds = true;
+    for result in results {
+        holds &= result?;
+    }
+    Ok(holds)
+}
+
+/// Whether `id` is among the user's top `list_range` artists (`query_type == true`)
+/// or tracks for `time_range`.
+///
+/// Errors are flattened to strings so the futures stay `Send` while joined.
+async fn is_top_item(
+    authorization: String,
+    id: &str,
+    query_type: bool,
+    time_range: TimeRange,
+    list_range: u8,
+) -> Result<bool, String> {
+    top_item_ids(authorization, query_type, time_range, list_range)
+        .await
+        .map(|ids| ids.iter().any(|top_id| top_id == id))
+}
+
+/// Returns the IDs of the user's top `list_range` artists (`query_type == true`)
+/// or tracks for `time_range`, in rank order.
+///
+/// Errors are flattened to strings so callers can join several of these futures.
+pub async fn top_item_ids(
+    authorization: String,
+    query_type: bool,
+    time_range: TimeRange,
+    list_range: u8,
+) -> Result<Vec<String>, String> {
+    let ids = if query_type {
+        stats_query_builder::<AristsStatsResponse>(authorization, true, time_range, list_range, 0)
+            .await
+            .map(|query| query.items.into_iter().map(|artist| artist.id).collect())
+    } else {
+        stats_query_builder::<TracksStatsResponse>(authorization, false, time_range, list_range, 0)
+            .await
+            .map(|query| query.items.into_iter().map(|track| track.id).collect())
+    };
+    ids.map_err(|e| e.to_string())
 }
 
 pub async fn can_claim_top_artist(
@@ -167,6 +830,336 @@ pub async fn can_claim_top_artist(
     Ok(String::from("0"))
 }
 
+/// Checks that no artist among the user's top `list_range` artists for `time_range`
+/// is tagged with `genre`.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `genre` - The genre to look for, compared case-insensitively.
+/// * `time_range` - The time range of the top artists.
+/// * `list_range` - How many top artists to consider.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails or if the response
+/// is not in the expected format.
+pub async fn can_claim_genre_not_in_top_artists(
+    authorization: String,
+    genre: String,
+    time_range: TimeRange,
+    list_range: u8,
+) -> Result<String, Box<dyn Error>> {
+    let query =
+        stats_query_builder::<AristsStatsResponse>(authorization, true, time_range, list_range, 0)
+            .await?;
+    let genre = genre.to_lowercase();
+    let found = query
+        .items
+        .iter()
+        .flat_map(|artist| &artist.genres)
+        .any(|artist_genre| artist_genre.to_lowercase() == genre);
+    if found {
+        return Ok(String::from("0"));
+    }
+    Ok(String::from("1"))
+}
+
+/// Checks if the genres of the user's top tracks have a Shannon entropy of at least
+/// `threshold`.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `time_range` - The time range of the top tracks.
+/// * `threshold` - The minimum entropy in sixteenths of a bit, e.g. 24 for 1.5 bits.
+/// * `list_range` - How many top tracks to consider.
+///
+/// Tracks have no genres of their own, so each track counts once for every genre
+/// of any of its artists, fetched with `GET /v1/artists`. Tracks whose artists
+/// have no genres are left out; a list without genres has an entropy of 0.
+///
+/// # Errors
+///
+/// This function will return an error if any API request fails or if a response
+/// is not in the expected format.
+pub async fn can_claim_genre_entropy(
+    authorization: String,
+    time_range: TimeRange,
+    threshold: u8,
+    list_range: u8,
+) -> Result<String, Box<dyn Error>> {
+    let query = stats_query_builder::<TracksStatsResponse>(
+        authorization.clone(),
+        false,
+        time_range,
+        list_range,
+        0,
+    )
+    .await?;
+
+    let mut artist_ids: Vec<&str> = query
+        .items
+        .iter()
+        .flat_map(|track| &track.artists)
+        .map(|artist| artist.id.as_str())
+        .collect();
+    artist_ids.sort_unstable();
+    artist_ids.dedup();
+
+    let mut artist_genres: HashMap<String, Vec<String>> = HashMap::new();
+    for ids in artist_ids.chunks(ARTISTS_PER_REQUEST) {
+        let endpoint = format!("{}/v1/artists?ids={}", spotify_api_base(), ids.join(","));
+        let response =
+            spotify_api_request::<SeveralArtists>(endpoint, authorization.clone()).await?;
+        for artist in response.artists.into_iter().flatten() {
+            artist_genres.insert(artist.id, artist.genres);
+        }
+    }
+
+    let mut counts: HashMap<&str, u32> = HashMap::new();
+    for track in &query.items {
+        let genres: HashSet<&str> = track
+            .artists
+            .iter()
+            .filter_map(|artist| artist_genres.get(&artist.id))
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        for genre in genres {
+            *counts.entry(genre).or_default() += 1;
+        }
+    }
+
+    let total = f64::from(counts.values().sum::<u32>());
+    let entropy: f64 = counts
+        .values()
+        .map(|count| {
+            let p = f64::from(*count) / total;
+            -p * p.log2()
+        })
+        .sum();
+    if entropy * 16.0 >= f64::from(threshold) {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if an artist is in the user's top artists for all three time ranges at once.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `artist_id` - The ID of the artist to check.
+/// * `list_range` - How many top artists to consider in each time range.
+///
+/// The three ranges are fetched in parallel.
+///
+/// # Errors
+///
+/// This function will return an error if any of the API requests fails or if a
+/// response is not in the expected format.
+pub async fn can_claim_artist_in_all_top_ranges(
+    authorization: String,
+    artist_id: String,
+    list_range: u8,
+) -> Result<String, Box<dyn Error>> {
+    let (short_term, medium_term, long_term) = tokio::join!(
+        is_top_item(
+            authorization.clone(),
+            &artist_id,
+            true,
+            TimeRange::ShortTerm,
+            list_range
+        ),
+        is_top_item(
+            authorization.clone(),
+            &artist_id,
+            true,
+            TimeRange::MediumTerm,
+            list_range
+        ),
+        is_top_item(
+            authorization,
+            &artist_id,
+            true,
+            TimeRange::LongTerm,
+            list_range
+        ),
+    );
+
+    if short_term? && medium_term? && long_term? {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if a track is among the short-term top 50 tracks of every one of several users.
+///
+/// # Arguments
+///
+/// * `authorizations` - The Bearer tokens of the users, the claimant first; the
+///   RPC handler rejects repeated keys and tokens.
+/// * `track_id` - The ID of the track to check.
+///
+/// The users' top tracks are fetched concurrently within the calling task, with
+/// at most `MAX_CONCURRENT_REQUESTS` requests in flight; the first failure ends
+/// the claim.
+///
+/// # Errors
+///
+/// This function will return an error if any of the API requests fails or if a
+/// response is not in the expected format.
+pub async fn can_claim_shared_top_track(
+    authorizations: Vec<String>,
+    track_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let lookups = authorizations.into_iter().map(|authorization| {
+        is_top_item(
+            authorization,
+            &track_id,
+            false,
+            TimeRange::ShortTerm,
+            SHARED_TOP_TRACKS_LIMIT,
+        )
+    });
+    let mut lookups = stream::iter(lookups).buffer_unordered(MAX_CONCURRENT_REQUESTS);
+
+    let mut shared = true;
+    while let Some(is_top) = lookups.next().await {
+        shared &= is_top?;
+    }
+    if shared {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks that two users have no artist in common among their top artists.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token of the claimant.
+/// * `other_authorization` - The Bearer token of the other user.
+/// * `time_range` - The time range of the top artists.
+/// * `list_range` - How many top artists of each user to consider.
+///
+/// The two lists are fetched in parallel. A user without top artists shares none,
+/// so an empty list answers "1".
+///
+/// # Errors
+///
+/// This function will return an error if either API request fails or if a
+/// response is not in the expected format.
+pub async fn can_claim_disjoint_top_artists(
+    authorization: String,
+    other_authorization: String,
+    time_range: TimeRange,
+    list_range: u8,
+) -> Result<String, Box<dyn Error>> {
+    let (own, other) = tokio::join!(
+        top_item_ids(authorization, true, time_range, list_range),
+        top_item_ids(other_authorization, true, time_range, list_range),
+    );
+    let own: HashSet<String> = own?.into_iter().collect();
+    if other?.iter().any(|artist_id| own.contains(artist_id)) {
+        return Ok(String::from("0"));
+    }
+    Ok(String::from("1"))
+}
+
+/// Checks if the user's interest in an artist changed between the long and short term.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `artist_id` - The ID of the artist to check.
+/// * `list_range` - How many top artists to consider in each time range.
+/// * `mode` - [`ListeningEvolution::NewlyDiscovered`] to prove the artist is in the
+///   short-term top artists but not the long-term ones, [`ListeningEvolution::Faded`]
+///   for the reverse.
+///
+/// The two ranges are fetched in parallel.
+///
+/// # Errors
+///
+/// This function will return an error if any of the API requests fails or if a
+/// response is not in the expected format.
+pub async fn can_claim_listening_evolved(
+    authorization: String,
+    artist_id: String,
+    list_range: u8,
+    mode: ListeningEvolution,
+) -> Result<String, Box<dyn Error>> {
+    let (short_term, long_term) = tokio::join!(
+        is_top_item(
+            authorization.clone(),
+            &artist_id,
+            true,
+            TimeRange::ShortTerm,
+            list_range
+        ),
+        is_top_item(
+            authorization,
+            &artist_id,
+            true,
+            TimeRange::LongTerm,
+            list_range
+        ),
+    );
+
+    let evolved = match mode {
+        ListeningEvolution::NewlyDiscovered => short_term? && !long_term?,
+        ListeningEvolution::Faded => long_term? && !short_term?,
+    };
+    if evolved {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if an artist has at least `threshold` followers.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `artist_id` - The ID of the artist.
+/// * `threshold` - The minimum number of followers.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails, if the artist is
+/// unknown, or if Spotify reports no follower count for it.
+pub async fn can_claim_artist_has_n_followers(
+    authorization: String,
+    artist_id: String,
+    threshold: u64,
+) -> Result<String, Box<dyn Error>> {
+    let endpoint = format!("{}/v1/artists/{}", spotify_api_base(), artist_id);
+    let artist = spotify_api_request::<ArtistFollowers>(endpoint, authorization)
+        .await
+        .map_err(|e| -> Box<dyn Error> {
+            match failed_status(e.as_ref()) {
+                Some(StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND) => {
+                    format!("Unknown artist: {}", artist_id).into()
+                }
+                _ => e,
+            }
+        })?;
+
+    let Some(followers) = artist.followers.total else {
+        return Err(format!(
+            "indeterminate: Spotify reports no follower count for artist {}",
+            artist_id
+        )
+        .into());
+    };
+    if followers >= threshold {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
 pub async fn can_claim_recently_played_track(
     authorization: String,
     track_id: String,
@@ -0,0 +1,137 @@
+use reqwest::StatusCode;
+use std::error::Error;
+
+use super::{failed_status, fetch_all_pages, spotify_api_base, spotify_api_request};
+use crate::types::{Episode, SavedShow};
+
+/// Fetches a podcast episode, or `None` if the ID does not name one.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `episode_id` - The ID of the episode.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails for any other
+/// reason than an unknown ID, or if the response is not in the expected format.
+pub async fn episode_query_builder(
+    authorization: String,
+    episode_id: &str,
+) -> Result<Option<Episode>, Box<dyn Error>> {
+    let endpoint = format!("{}/v1/episodes/{}", spotify_api_base(), episode_id);
+    match spotify_api_request::<Episode>(endpoint, authorization).await {
+        Ok(episode) => Ok(Some(episode)),
+        Err(e) => match failed_status(e.as_ref()) {
+            Some(StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND) => Ok(None),
+            _ => Err(e),
+        },
+    }
+}
+
+/// Checks if an ID names a podcast episode rather than, e.g., a music track.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `episode_id` - The ID to check.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails for any other
+/// reason than an unknown ID, or if the response is not in the expected format.
+pub async fn can_claim_track_is_podcast_episode(
+    authorization: String,
+    episode_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let episode = episode_query_builder(authorization, &episode_id).await?;
+    if episode.is_some_and(|episode| episode.r#type == "episode") {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if a podcast episode belongs to a show.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `episode_id` - The ID of the episode.
+/// * `show_id` - The ID of the show.
+///
+/// An ID that does not name an episode belongs to no show.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails for any other
+/// reason than an unknown ID, or if the response is not in the expected format.
+pub async fn can_claim_episode_in_show(
+    authorization: String,
+    episode_id: String,
+    show_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let episode = episode_query_builder(authorization, &episode_id).await?;
+    if episode.is_some_and(|episode| episode.show.id == show_id) {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if a show is among the user's saved shows.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `show_id` - The ID of the show.
+///
+/// # Errors
+///
+/// This function will return an error if an API request fails, if a response
+/// is not in the expected format, or if the saved shows span more than
+/// `MAX_PAGES` pages.
+pub async fn can_claim_show_in_saved_shows(
+    authorization: String,
+    show_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let endpoint = format!("{}/v1/me/shows?limit=50", spotify_api_base());
+    let saved = fetch_all_pages::<SavedShow>(endpoint, authorization).await?;
+    if saved.iter().any(|saved| saved.show.id == show_id) {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
+
+/// Checks if the user has listened to a podcast episode to the end.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `episode_id` - The ID of the episode.
+///
+/// Uses the episode's resume point, which Spotify marks `fully_played` once
+/// the user has finished it.
+///
+/// # Errors
+///
+/// This function will return an error if the API request fails, if the ID does
+/// not name an episode, or if Spotify returns no resume point because the
+/// session lacks the `user-read-playback-position` scope.
+pub async fn can_claim_episode_listened_to_completion(
+    authorization: String,
+    episode_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let Some(episode) = episode_query_builder(authorization, &episode_id).await? else {
+        return Err(format!("Unknown episode: {}", episode_id).into());
+    };
+    let Some(resume_point) = episode.resume_point else {
+        return Err(format!(
+            "No resume point for episode {}; the user-read-playback-position scope is required",
+            episode_id
+        )
+        .into());
+    };
+    if resume_point.fully_played {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
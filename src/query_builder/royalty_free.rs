@@ -0,0 +1,91 @@
+use futures::future::{self, BoxFuture};
+use reqwest::StatusCode;
+use std::collections::HashSet;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::sync::LazyLock;
+
+use super::{failed_status, track_query_builder};
+
+/// A source of tracks known to be free to reuse without licensing, such as
+/// Creative Commons releases.
+pub trait RoyaltyFreeProvider: Send + Sync {
+    /// Whether the track with this Spotify ID is known to be royalty free.
+    fn is_royalty_free<'a>(&'a self, track_id: &'a str) -> BoxFuture<'a, Result<bool, String>>;
+}
+
+/// Spotify track IDs listed in a local file, one per line; blank lines and
+/// lines starting with `#` are skipped.
+pub struct TrackListProvider {
+    track_ids: HashSet<String>,
+}
+
+impl TrackListProvider {
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read royalty-free track list {}: {}", path, e))?;
+        Ok(TrackListProvider::from_lines(&contents))
+    }
+
+    pub fn from_lines(contents: &str) -> Self {
+        let track_ids = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        TrackListProvider { track_ids }
+    }
+}
+
+impl RoyaltyFreeProvider for TrackListProvider {
+    fn is_royalty_free<'a>(&'a self, track_id: &'a str) -> BoxFuture<'a, Result<bool, String>> {
+        Box::pin(future::ready(Ok(self.track_ids.contains(track_id))))
+    }
+}
+
+/// The provider configured through `ROYALTY_FREE_TRACKS_FILE`, loaded on first use.
+static PROVIDER: LazyLock<Result<Box<dyn RoyaltyFreeProvider>, String>> = LazyLock::new(|| {
+    let path = env::var("ROYALTY_FREE_TRACKS_FILE")
+        .map_err(|_| String::from("No royalty-free track list is configured"))?;
+    let provider = TrackListProvider::from_file(&path)?;
+    Ok(Box::new(provider))
+});
+
+/// Checks if a track is known to be free to reuse without licensing.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The ID of the track to check.
+///
+/// The track is looked up on Spotify first, so a mistyped ID is an error
+/// rather than a "0". A track the provider does not list answers "0": it may
+/// still be free to reuse, but that is not proven.
+///
+/// # Errors
+///
+/// This function will return an error if no provider is configured, if the
+/// provider fails, if the API request fails or if the track is unknown.
+pub async fn can_claim_track_royalty_free(
+    authorization: String,
+    track_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let provider = PROVIDER.as_ref().map_err(|e| e.clone())?;
+    track_query_builder(authorization, &track_id)
+        .await
+        .map_err(|e| -> Box<dyn Error> {
+            match failed_status(e.as_ref()) {
+                Some(StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND) => {
+                    format!("Unknown track: {}", track_id).into()
+                }
+                _ => e,
+            }
+        })?;
+
+    if provider.is_royalty_free(&track_id).await? {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
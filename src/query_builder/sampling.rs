@@ -0,0 +1,116 @@
+use futures::future::{self, BoxFuture};
+use reqwest::StatusCode;
+use std::collections::HashSet;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::sync::LazyLock;
+
+use super::{failed_status, track_query_builder};
+
+/// A source of sample lineage between tracks.
+pub trait SamplingProvider: Send + Sync {
+    /// Whether the track `sampling_id` is known to sample the track `sampled_id`,
+    /// both Spotify IDs.
+    fn samples<'a>(
+        &'a self,
+        sampling_id: &'a str,
+        sampled_id: &'a str,
+    ) -> BoxFuture<'a, Result<bool, String>>;
+}
+
+/// Sample pairs listed in a local file, one `sampling_id,sampled_id` pair of
+/// Spotify track IDs per line; blank lines and lines starting with `#` are skipped.
+pub struct SamplingDatabase {
+    pairs: HashSet<(String, String)>,
+}
+
+impl SamplingDatabase {
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read sampling database {}: {}", path, e))?;
+        SamplingDatabase::from_lines(&contents)
+    }
+
+    pub fn from_lines(contents: &str) -> Result<Self, String> {
+        let mut pairs = HashSet::new();
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (sampling, sampled) = line
+                .split_once(',')
+                .map(|(sampling, sampled)| (sampling.trim(), sampled.trim()))
+                .filter(|(sampling, sampled)| !sampling.is_empty() && !sampled.is_empty())
+                .ok_or_else(|| {
+                    format!(
+                        "Sampling database line {} is not a `sampling_id,sampled_id` pair",
+                        number + 1
+                    )
+                })?;
+            pairs.insert((sampling.to_string(), sampled.to_string()));
+        }
+        Ok(SamplingDatabase { pairs })
+    }
+}
+
+impl SamplingProvider for SamplingDatabase {
+    fn samples<'a>(
+        &'a self,
+        sampling_id: &'a str,
+        sampled_id: &'a str,
+    ) -> BoxFuture<'a, Result<bool, String>> {
+        let pair = (sampling_id.to_string(), sampled_id.to_string());
+        Box::pin(future::ready(Ok(self.pairs.contains(&pair))))
+    }
+}
+
+/// The provider configured through `SAMPLING_DATABASE_FILE`, loaded on first use.
+static PROVIDER: LazyLock<Result<Box<dyn SamplingProvider>, String>> = LazyLock::new(|| {
+    let path = env::var("SAMPLING_DATABASE_FILE")
+        .map_err(|_| String::from("No sampling database is configured"))?;
+    let provider = SamplingDatabase::from_file(&path)?;
+    Ok(Box::new(provider))
+});
+
+/// Checks if a track samples another track.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `sampling_id` - The ID of the track that would contain the sample.
+/// * `sampled_id` - The ID of the track that would be sampled.
+///
+/// Both tracks are looked up on Spotify first, so a mistyped ID is an error
+/// rather than a "0". A pair the provider does not list answers "0": the
+/// sample may exist, but it is not proven.
+///
+/// # Errors
+///
+/// This function will return an error if no provider is configured, if the
+/// provider fails, if an API request fails or if either track is unknown.
+pub async fn can_claim_track_sampling(
+    authorization: String,
+    sampling_id: String,
+    sampled_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let provider = PROVIDER.as_ref().map_err(|e| e.clone())?;
+    for track_id in [&sampling_id, &sampled_id] {
+        track_query_builder(authorization.clone(), track_id)
+            .await
+            .map_err(|e| -> Box<dyn Error> {
+                match failed_status(e.as_ref()) {
+                    Some(StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND) => {
+                        format!("Unknown track: {}", track_id).into()
+                    }
+                    _ => e,
+                }
+            })?;
+    }
+
+    if provider.samples(&sampling_id, &sampled_id).await? {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
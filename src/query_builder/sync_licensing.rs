@@ -0,0 +1,48 @@
+use std::error::Error;
+
+use super::markets::unknown_item;
+use super::track_query_builder;
+use crate::providers::tunefind::TuneFindProvider;
+
+/// Checks if a track was synced to a TV show or film.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The Spotify ID of the track.
+/// * `show` - The name of the show or film, matched case-insensitively.
+///
+/// TuneFind has no Spotify IDs, so the song is found by its title and first
+/// credited artist. A song TuneFind does not list answers "0": it may still
+/// have been used, but that is not proven.
+///
+/// # Errors
+///
+/// This function will return an error if TuneFind is not configured, if a
+/// request fails, if TuneFind's response is not in the expected format, or if
+/// the track is unknown.
+pub async fn can_claim_track_in_tv_show(
+    authorization: String,
+    track_id: String,
+    show: String,
+) -> Result<String, Box<dyn Error>> {
+    let provider = TuneFindProvider::from_env()?;
+    let track = track_query_builder(authorization, &track_id)
+        .await
+        .map_err(unknown_item("track", &track_id))?;
+    let Some(artist) = track.artists.first() else {
+        return Err(format!("indeterminate: track {} credits no artist", track_id).into());
+    };
+
+    let productions = provider
+        .productions_featuring(&artist.name, &track.name)
+        .await?;
+    let show = show.trim();
+    if productions
+        .iter()
+        .any(|production| production.trim().eq_ignore_ascii_case(show))
+    {
+        return Ok(String::from("1"));
+    }
+    Ok(String::from("0"))
+}
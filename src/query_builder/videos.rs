@@ -0,0 +1,77 @@
+use std::error::Error;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use super::markets::unknown_item;
+use super::track_query_builder;
+use crate::cache::TtlCache;
+use crate::providers::youtube::YouTubeProvider;
+
+/// How long a track's music video answer is reused, 24 hours; every YouTube
+/// search costs quota, and official videos rarely appear or vanish within a day.
+const MUSIC_VIDEO_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Whether a track has an official music video, keyed by track ID.
+static MUSIC_VIDEOS: LazyLock<TtlCache<String, bool>> =
+    LazyLock::new(|| TtlCache::new(MUSIC_VIDEO_TTL));
+
+/// Whether a YouTube channel title is the artist's own channel: the artist's
+/// name, or its Vevo channel ("Artist NameVEVO"), ignoring case and spaces.
+/// Auto-generated "Artist - Topic" channels only carry audio and do not count.
+fn is_artist_channel(channel_title: &str, artist: &str) -> bool {
+    let normalize = |name: &str| -> String {
+        name.chars()
+            .filter(|c| !c.is_whitespace())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let channel = normalize(channel_title);
+    let artist = normalize(artist);
+    !artist.is_empty() && (channel == artist || channel == format!("{}vevo", artist))
+}
+
+/// Checks if a track has an official music video on YouTube.
+///
+/// # Arguments
+///
+/// * `authorization` - The Bearer token for the user's Spotify API session.
+/// * `track_id` - The Spotify ID of the track.
+///
+/// YouTube is searched for the first credited artist, the track name and
+/// "official video"; the track has a video when one of the top results was
+/// uploaded by the artist's own channel. Videos the search ranks lower are
+/// missed, so "0" is not proof that none exists. Answers are reused per track
+/// for `MUSIC_VIDEO_TTL`, whichever session asks; only the YouTube search is
+/// skipped, the track is still looked up on Spotify with the session's token.
+///
+/// # Errors
+///
+/// This function will return an error if YouTube is not configured, if a
+/// request fails, or if the track is unknown.
+pub async fn can_claim_track_has_music_video(
+    authorization: String,
+    track_id: String,
+) -> Result<String, Box<dyn Error>> {
+    let provider = YouTubeProvider::from_env()?;
+    let track = track_query_builder(authorization, &track_id)
+        .await
+        .map_err(unknown_item("track", &track_id))?;
+    if let Some(has_video) = MUSIC_VIDEOS.get(&track_id) {
+        return Ok(answer(has_video));
+    }
+    let Some(artist) = track.artists.first() else {
+        return Err(format!("indeterminate: track {} credits no artist", track_id).into());
+    };
+
+    let query = format!("{} {} official video", artist.name, track.name);
+    let channels = provider.video_channels(&query).await?;
+    let has_video = channels
+        .iter()
+        .any(|channel| is_artist_channel(channel, &artist.name));
+    MUSIC_VIDEOS.insert(track_id, has_video);
+    Ok(answer(has_video))
+}
+
+fn answer(has_video: bool) -> String {
+    String::from(if has_video { "1" } else { "0" })
+}
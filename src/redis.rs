@@ -1,10 +1,41 @@
-use redis::RedisResult;
+use aes_gcm::aead::{Aead, Generate, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use chrono::NaiveDate;
+use redis::{ErrorKind, IntoConnectionInfo, RedisError, RedisResult};
+use std::collections::HashMap;
 use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
 
-fn connect() -> redis::Connection {
+use crate::types::{
+    KeyUsage, ACCOUNTS_KEY_PREFIX, EVENT_KEY_PREFIX, PLAYLIST_SNAPSHOT_KEY_PREFIX,
+    PROVIDER_USAGE_KEY_PREFIX, PROVIDER_USAGE_RETENTION_SECS, REFERENCE_LIST_KEY_PREFIX,
+};
+
+/// `REDIS_HOSTNAME` scheme selecting Sentinel-based master discovery, with the
+/// sentinels in `REDIS_SENTINEL_URLS` and the master in `REDIS_MASTER_NAME`.
+const SENTINEL_SCHEME: &str = "redis+sentinel://";
+
+/// `REDIS_HOSTNAME` scheme of a Redis Cluster, which is not supported yet.
+///
+/// Supporting it needs the `redis` crate's `cluster` feature and slot-aware
+/// routing of the multi-key commands (`DEL` of a key and its accounts hash,
+/// the storage scan); until then [`check_redis_hostname`] refuses the scheme.
+const CLUSTER_SCHEME: &str = "redis+cluster://";
+
+/// How long to wait for a sentinel before asking the next one.
+const SENTINEL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Opens a connection to the configured Redis server.
+///
+/// Configuration and connection problems are returned rather than raised, so a
+/// Redis outage fails the requests that need it without taking down the
+/// threads that make them.
+fn connect() -> RedisResult<redis::Connection> {
     //format - host:port
-    let redis_host_name =
-        env::var("REDIS_HOSTNAME").expect("missing environment variable REDIS_HOSTNAME");
+    let redis_host_name = env::var("REDIS_HOSTNAME")
+        .map_err(|_| client_error("missing environment variable REDIS_HOSTNAME"))?;
     let redis_password = env::var("REDIS_PASSWORD").unwrap_or_default();
 
     //if Redis server needs secure connection
@@ -13,36 +44,607 @@ fn connect() -> redis::Connection {
         Err(_) => "redis",
     };
 
+    let redis_host_name = if redis_host_name.starts_with(SENTINEL_SCHEME) {
+        let sentinel = SentinelConfig::from_env()?;
+        let urls: Vec<&str> = sentinel.urls.iter().map(String::as_str).collect();
+        discover_master(&urls, &sentinel.master_name)?
+    } else {
+        redis_host_name
+    };
+
     let redis_conn_url = format!("{}://:{}@{}", uri_scheme, redis_password, redis_host_name);
 
-    redis::Client::open("redis://127.0.0.1:6379")
-        .expect("Invalid connection URL")
-        .get_connection()
-        .expect("failed to connect to Redis")
+    redis::Client::open(redis_conn_url)?.get_connection()
+}
+
+/// Checks `REDIS_HOSTNAME` once at startup, before any connection is made.
+///
+/// Commands are sent without slot routing, so a cluster would answer them with
+/// MOVED errors; a `redis+cluster://` address is rejected up front instead. A
+/// `redis+sentinel://` address needs a valid [`SentinelConfig`] in the
+/// environment.
+pub fn check_redis_hostname(redis_host_name: &str) -> RedisResult<()> {
+    if redis_host_name.starts_with(CLUSTER_SCHEME) {
+        return Err(client_error(
+            "Redis Cluster (redis+cluster://) is not supported; use a single node or Sentinel",
+        ));
+    }
+    if redis_host_name.starts_with(SENTINEL_SCHEME) {
+        SentinelConfig::from_env()?;
+    }
+    Ok(())
+}
+
+/// Where to discover the Redis master when `REDIS_HOSTNAME` selects Sentinel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SentinelConfig {
+    pub urls: Vec<String>,
+    pub master_name: String,
+}
+
+impl SentinelConfig {
+    /// Reads `REDIS_SENTINEL_URLS` and `REDIS_MASTER_NAME`.
+    pub fn from_env() -> RedisResult<Self> {
+        Self::parse(
+            env::var("REDIS_SENTINEL_URLS").ok().as_deref(),
+            env::var("REDIS_MASTER_NAME").ok().as_deref(),
+        )
+    }
+
+    /// Validates a comma-separated list of sentinel URLs and a master name.
+    pub fn parse(urls: Option<&str>, master_name: Option<&str>) -> RedisResult<Self> {
+        let urls: Vec<String> = urls
+            .ok_or_else(|| client_error("missing environment variable REDIS_SENTINEL_URLS"))?
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(str::to_string)
+            .collect();
+        if urls.is_empty() {
+            return Err(client_error("REDIS_SENTINEL_URLS lists no sentinels"));
+        }
+        for url in &urls {
+            url.as_str().into_connection_info()?;
+        }
+        let master_name = master_name
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| client_error("missing environment variable REDIS_MASTER_NAME"))?;
+        Ok(SentinelConfig {
+            urls,
+            master_name: master_name.to_string(),
+        })
+    }
+}
+
+/// Asks the sentinels at `sentinel_urls`, in order, for the address of
+/// `master_name`, returned as `host:port`.
+///
+/// Unreachable sentinels and sentinels that do not monitor the master are skipped.
+pub fn discover_master(sentinel_urls: &[&str], master_name: &str) -> RedisResult<String> {
+    let mut last_error = client_error("REDIS_SENTINEL_URLS lists no sentinels");
+    for url in sentinel_urls {
+        let address: RedisResult<Option<(String, u16)>> = redis::Client::open(*url)
+            .and_then(|client| client.get_connection_with_timeout(SENTINEL_TIMEOUT))
+            .and_then(|mut conn| {
+                redis::cmd("SENTINEL")
+                    .arg("get-master-addr-by-name")
+                    .arg(master_name)
+                    .query(&mut conn)
+            });
+        match address {
+            Ok(Some((host, port))) => return Ok(format!("{}:{}", host, port)),
+            Ok(None) => last_error = client_error("No sentinel monitors REDIS_MASTER_NAME"),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+/// Marks a stored token as encrypted; the rest is the hex of nonce and ciphertext.
+const ENCRYPTED_TOKEN_PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+
+/// Parses an `ORACLE_ENCRYPTION_KEY` value: 32 bytes as 64 hex digits.
+pub fn parse_encryption_key(hex: &str) -> Option<[u8; 32]> {
+    let bytes = decode_hex(hex.trim())?;
+    bytes.try_into().ok()
+}
+
+/// Checks the token encryption settings once at startup.
+///
+/// Tokens are bearer credentials, so storing them in the clear has to be asked
+/// for: without `ORACLE_ENCRYPTION_KEY` the oracle only starts when
+/// `ORACLE_ALLOW_PLAINTEXT` is `true` or `1`.
+pub fn check_encryption_config(
+    encryption_key: Option<&str>,
+    allow_plaintext: Option<&str>,
+) -> RedisResult<()> {
+    match encryption_key {
+        Some(hex) => parse_encryption_key(hex)
+            .map(|_| ())
+            .ok_or_else(|| client_error("ORACLE_ENCRYPTION_KEY must be 32 bytes of hex")),
+        None if allow_plaintext.is_some_and(|value| {
+            value.trim() == "1" || value.trim().eq_ignore_ascii_case("true")
+        }) =>
+        {
+            Ok(())
+        }
+        None => Err(client_error(
+            "ORACLE_ENCRYPTION_KEY is not set; set ORACLE_ALLOW_PLAINTEXT=true to store tokens unencrypted",
+        )),
+    }
+}
+
+/// The key tokens are encrypted with, if `ORACLE_ENCRYPTION_KEY` is set.
+fn encryption_key() -> RedisResult<Option<[u8; 32]>> {
+    match env::var("ORACLE_ENCRYPTION_KEY") {
+        Ok(hex) => parse_encryption_key(&hex)
+            .map(Some)
+            .ok_or_else(|| client_error("ORACLE_ENCRYPTION_KEY must be 32 bytes of hex")),
+        Err(_) => Ok(None),
+    }
+}
+
+fn client_error(description: &'static str) -> RedisError {
+    (ErrorKind::ClientError, description).into()
+}
+
+pub(crate) fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Encrypts `token` with AES-256-GCM under a fresh random nonce.
+pub fn encrypt_token(key: &[u8; 32], token: &str) -> String {
+    let cipher = Aes256Gcm::new(&Key::<Aes256Gcm>::from(*key));
+    let nonce = Nonce::generate();
+    let ciphertext = cipher
+        .encrypt(&nonce, token.as_bytes())
+        .expect("AES-GCM encryption of a short token cannot fail");
+
+    let mut stored = String::from(ENCRYPTED_TOKEN_PREFIX);
+    for byte in nonce.iter().chain(&ciphertext) {
+        stored.push_str(&format!("{:02x}", byte));
+    }
+    stored
+}
+
+/// Decrypts a token produced by [`encrypt_token`].
+///
+/// A wrong key, or a value that was tampered with, fails authentication and is
+/// an error; it never decrypts to a different token.
+pub fn decrypt_token(key: &[u8; 32], stored: &str) -> RedisResult<String> {
+    let malformed = || client_error("Stored token is not a valid encrypted token");
+    let bytes = stored
+        .strip_prefix(ENCRYPTED_TOKEN_PREFIX)
+        .and_then(decode_hex)
+        .filter(|bytes| bytes.len() > NONCE_LEN)
+        .ok_or_else(malformed)?;
+    let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+    let nonce = Nonce::try_from(nonce).map_err(|_| malformed())?;
+
+    let cipher = Aes256Gcm::new(&Key::<Aes256Gcm>::from(*key));
+    let token = cipher
+        .decrypt(&nonce, ciphertext)
+        .map_err(|_| client_error("Failed to decrypt token; is ORACLE_ENCRYPTION_KEY correct?"))?;
+    String::from_utf8(token).map_err(|_| malformed())
+}
+
+/// Prepares `token` for storage: encrypted when a key is configured.
+fn seal(token: &str) -> RedisResult<String> {
+    Ok(match encryption_key()? {
+        Some(key) => encrypt_token(&key, token),
+        None => token.to_string(),
+    })
+}
+
+/// Stored tokens read in the clear since startup.
+static PLAINTEXT_TOKEN_READS: AtomicU64 = AtomicU64::new(0);
+
+/// How many stored tokens were read in the clear since startup. With a key
+/// configured each of them was re-encrypted, so this stops growing once every
+/// legacy token was used once.
+pub fn plaintext_token_reads() -> u64 {
+    PLAINTEXT_TOKEN_READS.load(Ordering::Relaxed)
+}
+
+/// Recovers a token from its stored value.
+///
+/// Values stored before encryption was enabled are read as they are, so
+/// existing keys keep working; encrypted values need the key they were
+/// encrypted with.
+fn open(stored: String) -> RedisResult<String> {
+    if !stored.starts_with(ENCRYPTED_TOKEN_PREFIX) {
+        return Ok(stored);
+    }
+    match encryption_key()? {
+        Some(key) => decrypt_token(&key, &stored),
+        None => Err(client_error(
+            "Token is encrypted but ORACLE_ENCRYPTION_KEY is not set",
+        )),
+    }
+}
+
+/// Replaces a hash field still holding `ARGV[1]` with `ARGV[2]`; the field
+/// counterpart of [`ROTATE_TOKEN_SCRIPT`].
+const SWAP_FIELD_SCRIPT: &str = r#"
+local current = redis.call('HGET', KEYS[1], ARGV[3])
+if not current then
+    return -2
+end
+if current ~= ARGV[1] then
+    return -3
+end
+redis.call('HSET', KEYS[1], ARGV[3], ARGV[2])
+return -1
+"#;
+
+/// Like [`open`], for a token just read from `key` (or from its `field`, for a
+/// hash): a token stored in the clear is counted and, when a key is configured,
+/// written back encrypted unless it changed in the meantime.
+///
+/// A failed write-back is logged and leaves the token as it was; it is retried
+/// on the next read.
+fn open_stored(
+    conn: &mut redis::Connection,
+    key: &str,
+    field: Option<&str>,
+    stored: String,
+) -> RedisResult<String> {
+    if stored.starts_with(ENCRYPTED_TOKEN_PREFIX) {
+        return open(stored);
+    }
+    PLAINTEXT_TOKEN_READS.fetch_add(1, Ordering::Relaxed);
+    if let Some(encryption_key) = encryption_key()? {
+        let sealed = encrypt_token(&encryption_key, &stored);
+        let swapped: RedisResult<i64> = match field {
+            Some(field) => redis::Script::new(SWAP_FIELD_SCRIPT)
+                .key(key)
+                .arg(&stored)
+                .arg(sealed)
+                .arg(field)
+                .invoke(conn),
+            None => redis::Script::new(ROTATE_TOKEN_SCRIPT)
+                .key(key)
+                .arg(&stored)
+                .arg(sealed)
+                .invoke(conn),
+        };
+        match swapped {
+            Ok(_) => eprintln!("Re-encrypted a token stored before encryption was enabled"),
+            Err(e) => eprintln!("Failed to re-encrypt a plaintext token: {}", e),
+        }
+    }
+    Ok(stored)
 }
 
 pub fn store_key_and_token(key: String, token: String) -> RedisResult<bool> {
-    let mut conn = connect();
+    let mut conn = connect()?;
 
-    let _: () = redis::cmd("SET").arg(&key).arg(&token).query(&mut conn)?;
+    let _: () = redis::cmd("SET")
+        .arg(&key)
+        .arg(seal(&token)?)
+        .query(&mut conn)?;
 
     let found_token: String = redis::cmd("GET").arg(key).query(&mut conn)?;
-    if found_token != token {
+    if open(found_token)? != token {
         return Ok(false);
     }
     Ok(true)
 }
 
+/// Swaps the stored value of a key if it is still the one the caller read, and
+/// reports the swapped value's remaining TTL: -1 when it had no expiry, -2 when
+/// the key holds no token and -3 when the value changed in the meantime.
+///
+/// Stored tokens may be encrypted, so the prefix check happens before the script
+/// and the script only compares the whole stored value.
+const ROTATE_TOKEN_SCRIPT: &str = r#"
+local current = redis.call('GET', KEYS[1])
+if not current then
+    return -2
+end
+if current ~= ARGV[1] then
+    return -3
+end
+local ttl = redis.call('TTL', KEYS[1])
+redis.call('SET', KEYS[1], ARGV[2])
+return ttl
+"#;
+
+/// Result of [`rotate_token`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum TokenRotation {
+    /// The token was replaced; `expires_in` is the old token's remaining lifetime
+    /// in seconds, `None` when it did not expire.
+    Rotated {
+        expires_in: Option<u64>,
+    },
+    /// The stored token does not start with the given prefix and was kept.
+    PrefixMismatch,
+    UnknownKey,
+}
+
+/// Atomically replaces the token stored under `key` with `new_token`, provided
+/// the stored token starts with `old_token_prefix` once its `Bearer ` scheme is
+/// removed.
+pub fn rotate_token(
+    key: String,
+    old_token_prefix: String,
+    new_token: String,
+) -> RedisResult<TokenRotation> {
+    let mut conn = connect()?;
+
+    let stored: Option<String> = redis::cmd("GET").arg(&key).query(&mut conn)?;
+    let Some(stored) = stored else {
+        return Ok(TokenRotation::UnknownKey);
+    };
+    let current = open(stored.clone())?;
+    let raw_token = current.strip_prefix("Bearer ").unwrap_or(&current);
+    if !raw_token.starts_with(&old_token_prefix) {
+        return Ok(TokenRotation::PrefixMismatch);
+    }
+
+    let ttl: i64 = redis::Script::new(ROTATE_TOKEN_SCRIPT)
+        .key(key)
+        .arg(stored)
+        .arg(seal(&new_token)?)
+        .invoke(&mut conn)?;
+    Ok(match ttl {
+        // Rotated by someone else since it was read.
+        -3 => TokenRotation::PrefixMismatch,
+        -2 => TokenRotation::UnknownKey,
+        ttl => TokenRotation::Rotated {
+            expires_in: u64::try_from(ttl).ok(),
+        },
+    })
+}
+
+/// Binds the token of Spotify account `account` to `key`, next to any other accounts.
+pub fn store_key_account(key: String, account: String, token: String) -> RedisResult<bool> {
+    let mut conn = connect()?;
+    let hash = format!("{}{}", ACCOUNTS_KEY_PREFIX, key);
+
+    let _: () = redis::cmd("HSET")
+        .arg(&hash)
+        .arg(&account)
+        .arg(seal(&token)?)
+        .query(&mut conn)?;
+
+    let found_token: Option<String> = redis::cmd("HGET").arg(hash).arg(account).query(&mut conn)?;
+    match found_token {
+        Some(found_token) => Ok(open(found_token)? == token),
+        None => Ok(false),
+    }
+}
+
+/// Fetches the token stored under `key`.
 pub fn get_token(key: String) -> RedisResult<String> {
-    let mut conn = connect();
+    let mut conn = connect()?;
 
-    let found_token: String = redis::cmd("GET").arg(key).query(&mut conn)?;
-    Ok(found_token)
+    let found_token: Option<String> = redis::cmd("GET").arg(&key).query(&mut conn)?;
+    match found_token {
+        Some(token) => open_stored(&mut conn, &key, None, token),
+        None => Err(client_error("No token is stored for this key")),
+    }
+}
+
+/// Fetches the token of Spotify account `account` bound to `key` through
+/// `store_key_account`.
+pub fn get_account_token(key: String, account: String) -> RedisResult<String> {
+    let mut conn = connect()?;
+    let hash = format!("{}{}", ACCOUNTS_KEY_PREFIX, key);
+
+    let found_token: Option<String> = redis::cmd("HGET")
+        .arg(&hash)
+        .arg(&account)
+        .query(&mut conn)?;
+    match found_token {
+        Some(token) => open_stored(&mut conn, &hash, Some(&account), token),
+        None => Err(client_error("No token is stored for this key and account")),
+    }
+}
+
+/// Deletes the token stored under `key`, along with any accounts bound to it.
+///
+/// Deletion is idempotent: removing a key that does not exist is not an error.
+/// Returns whether a token was actually removed.
+pub fn delete_token(key: String) -> RedisResult<bool> {
+    let mut conn = connect()?;
+
+    let removed: u32 = redis::cmd("DEL")
+        .arg(&key)
+        .arg(format!("{}{}", ACCOUNTS_KEY_PREFIX, key))
+        .query(&mut conn)?;
+    Ok(removed > 0)
+}
+
+/// Replaces the reference list stored under `id` with `track_ids`, keeping their order.
+pub fn store_reference_list(id: String, track_ids: Vec<String>) -> RedisResult<usize> {
+    let mut conn = connect()?;
+    let key = format!("{}{}", REFERENCE_LIST_KEY_PREFIX, id);
+
+    let _: () = redis::cmd("DEL").arg(&key).query(&mut conn)?;
+    let stored: usize = redis::cmd("RPUSH")
+        .arg(&key)
+        .arg(track_ids)
+        .query(&mut conn)?;
+    Ok(stored)
+}
+
+/// Fetches the reference list stored under `id`; an unknown id yields an empty list.
+pub fn get_reference_list(id: String) -> RedisResult<Vec<String>> {
+    let mut conn = connect()?;
+    let key = format!("{}{}", REFERENCE_LIST_KEY_PREFIX, id);
+
+    let track_ids: Vec<String> = redis::cmd("LRANGE")
+        .arg(key)
+        .arg(0)
+        .arg(-1)
+        .query(&mut conn)?;
+    Ok(track_ids)
+}
+
+/// Stores the serialized event under `id`, replacing any previous version; it
+/// expires after `ttl_secs` seconds.
+pub fn store_event(id: String, event: String, ttl_secs: u64) -> RedisResult<()> {
+    let mut conn = connect()?;
+    let key = format!("{}{}", EVENT_KEY_PREFIX, id);
+
+    redis::cmd("SET")
+        .arg(key)
+        .arg(event)
+        .arg("EX")
+        .arg(ttl_secs)
+        .query(&mut conn)
+}
+
+/// Fetches the serialized event stored under `id`; unknown or expired ids yield `None`.
+pub fn get_event(id: String) -> RedisResult<Option<String>> {
+    let mut conn = connect()?;
+    let key = format!("{}{}", EVENT_KEY_PREFIX, id);
+
+    redis::cmd("GET").arg(key).query(&mut conn)
+}
+
+/// Stores the serialized snapshot of playlist `id`; it expires after `ttl_secs`
+/// seconds unless stored again.
+pub fn store_playlist_snapshot(id: String, snapshot: String, ttl_secs: u64) -> RedisResult<()> {
+    let mut conn = connect()?;
+    let key = format!("{}{}", PLAYLIST_SNAPSHOT_KEY_PREFIX, id);
+
+    redis::cmd("SET")
+        .arg(key)
+        .arg(snapshot)
+        .arg("EX")
+        .arg(ttl_secs)
+        .query(&mut conn)
+}
+
+/// Fetches the serialized snapshot of playlist `id`; a playlist not seen before
+/// yields `None`.
+pub fn get_playlist_snapshot(id: String) -> RedisResult<Option<String>> {
+    let mut conn = connect()?;
+    let key = format!("{}{}", PLAYLIST_SNAPSHOT_KEY_PREFIX, id);
+
+    redis::cmd("GET").arg(key).query(&mut conn)
+}
+
+/// Most keys of each feature `storage_usage` sizes with `MEMORY USAGE`.
+const STORAGE_USAGE_SAMPLE_KEYS: u64 = 100;
+
+/// Nested values `MEMORY USAGE` samples to size a list or hash.
+const MEMORY_USAGE_SAMPLES: u32 = 5;
+
+/// The last report of [`storage_usage`], exported as gauges.
+static LAST_STORAGE_USAGE: Mutex<Vec<(&'static str, KeyUsage)>> = Mutex::new(Vec::new());
+
+/// Reports how many keys each feature stores and roughly how many bytes they take.
+///
+/// Walks the keyspace with `SCAN` to count keys, but sizes only the first
+/// `STORAGE_USAGE_SAMPLE_KEYS` keys of each feature with `MEMORY USAGE ...
+/// SAMPLES`, so the report costs one cheap command per key and a bounded number
+/// of sizings; `bytes` extrapolates the sampled average to every key. Keys
+/// without a feature prefix are API tokens stored through `store_key`.
+pub fn storage_usage() -> RedisResult<Vec<(&'static str, KeyUsage)>> {
+    let mut conn = connect()?;
+    let mut usage = vec![
+        ("tokens", KeyUsage::default()),
+        ("reference_lists", KeyUsage::default()),
+        ("events", KeyUsage::default()),
+        ("provider_usage", KeyUsage::default()),
+        ("accounts", KeyUsage::default()),
+        ("playlist_snapshots", KeyUsage::default()),
+    ];
+    let mut sampled_bytes = vec![0u64; usage.len()];
+
+    let mut cursor: u64 = 0;
+    loop {
+        let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("COUNT")
+            .arg(500)
+            .query(&mut conn)?;
+        for key in keys {
+            let feature = if key.starts_with(PROVIDER_USAGE_KEY_PREFIX) {
+                3
+            } else if key.starts_with(REFERENCE_LIST_KEY_PREFIX) {
+                1
+            } else if key.starts_with(EVENT_KEY_PREFIX) {
+                2
+            } else if key.starts_with(ACCOUNTS_KEY_PREFIX) {
+                4
+            } else if key.starts_with(PLAYLIST_SNAPSHOT_KEY_PREFIX) {
+                5
+            } else {
+                0
+            };
+            let feature_usage = &mut usage[feature].1;
+            feature_usage.keys += 1;
+            if feature_usage.sampled < STORAGE_USAGE_SAMPLE_KEYS {
+                let bytes: Option<u64> = redis::cmd("MEMORY")
+                    .arg("USAGE")
+                    .arg(&key)
+                    .arg("SAMPLES")
+                    .arg(MEMORY_USAGE_SAMPLES)
+                    .query(&mut conn)?;
+                // A key that expired since the scan saw it is not a sample.
+                if let Some(bytes) = bytes {
+                    feature_usage.sampled += 1;
+                    sampled_bytes[feature] += bytes;
+                }
+            }
+        }
+
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+
+    for ((_, feature_usage), sampled_bytes) in usage.iter_mut().zip(sampled_bytes) {
+        feature_usage.bytes = (sampled_bytes * feature_usage.keys)
+            .checked_div(feature_usage.sampled)
+            .unwrap_or(0);
+    }
+    *LAST_STORAGE_USAGE.lock().unwrap_or_else(|e| e.into_inner()) = usage.clone();
+    Ok(usage)
+}
+
+/// The report of the last [`storage_usage`] call; empty before the first.
+pub fn last_storage_usage() -> Vec<(&'static str, KeyUsage)> {
+    LAST_STORAGE_USAGE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Adds provider call counts to the bucket of `day`.
+pub fn incr_provider_usage(day: NaiveDate, counts: Vec<(String, u64)>) -> RedisResult<()> {
+    let mut conn = connect()?;
+    let key = format!("{}{}", PROVIDER_USAGE_KEY_PREFIX, day);
+
+    let mut pipe = redis::pipe();
+    for (field, count) in counts {
+        pipe.cmd("HINCRBY").arg(&key).arg(field).arg(count).ignore();
+    }
+    pipe.cmd("EXPIRE")
+        .arg(&key)
+        .arg(PROVIDER_USAGE_RETENTION_SECS)
+        .ignore();
+    pipe.query(&mut conn)
 }
 
-pub fn delete_token(key: String) -> RedisResult<String> {
-    let mut conn = connect();
+/// Fetches the provider call counts recorded on `day`.
+pub fn get_provider_usage(day: NaiveDate) -> RedisResult<HashMap<String, u64>> {
+    let mut conn = connect()?;
+    let key = format!("{}{}", PROVIDER_USAGE_KEY_PREFIX, day);
 
-    let found_key: String = redis::cmd("DEL").arg(key).query(&mut conn)?;
-    Ok(found_key)
+    redis::cmd("HGETALL").arg(key).query(&mut conn)
 }
@@ -1,17 +1,129 @@
 use crate::{
-    redis::{delete_token, get_token, store_key_and_token},
+    redis::{
+        delete_token, get_account_token, get_event, get_playlist_snapshot, get_reference_list,
+        get_token, rotate_token, storage_usage, store_event, store_key_account,
+        store_key_and_token, store_playlist_snapshot, store_reference_list, TokenRotation,
+    },
     types::{
-        TimeRange, CAN_CLAIM_RECENTLY_PLAYED_TRACK, CAN_CLAIM_TOP_ARTISTS, CAN_CLAIM_TOP_TRACKS,
+        claim_by_id, claim_by_name, Claim, ListeningEvent, ListeningEvolution, PlaylistSnapshot,
+        TimeRange, CAN_CLAIM_ALBUM_N_MARKETS, CAN_CLAIM_ALBUM_PLAYTHROUGH,
+        CAN_CLAIM_ARTIST_ALL_RANGES, CAN_CLAIM_ARTIST_BIO_LENGTH, CAN_CLAIM_ARTIST_COLLAB_COUNT,
+        CAN_CLAIM_ARTIST_MARKET_COUNT, CAN_CLAIM_ARTIST_NOT_RECENTLY_PLAYED,
+        CAN_CLAIM_ARTIST_N_FOLLOWERS, CAN_CLAIM_ARTIST_ON_TOUR,
+        CAN_CLAIM_ARTIST_TOP_TRACK_IN_HISTORY, CAN_CLAIM_AUDIOBOOK_CHAPTER,
+        CAN_CLAIM_AUDIOBOOK_SAVED, CAN_CLAIM_CONSECUTIVE_ARTIST_PLAYS,
+        CAN_CLAIM_CURATOR_CONTRIBUTION_COUNT, CAN_CLAIM_DEVICE_LOCATION_HINT,
+        CAN_CLAIM_DISCOGRAPHY_COMPLETED, CAN_CLAIM_DISJOINT_ARTISTS,
+        CAN_CLAIM_DISTINCT_ALBUMS_IN_HISTORY, CAN_CLAIM_EPISODE_COMPLETED,
+        CAN_CLAIM_EPISODE_IN_SHOW, CAN_CLAIM_EVENT_LOCAL, CAN_CLAIM_FOLLOWS_N_ARTISTS,
+        CAN_CLAIM_GENRE_ENTROPY, CAN_CLAIM_GENRE_NOT_IN_TOP, CAN_CLAIM_IS_PODCAST_EPISODE,
+        CAN_CLAIM_LIBRARY_AGE, CAN_CLAIM_LIBRARY_OVERLAP, CAN_CLAIM_LISTENING_EVOLVED,
+        CAN_CLAIM_MORNING_TOP_TRACK, CAN_CLAIM_N_ARTISTS_TOTAL, CAN_CLAIM_N_FOLLOWED_PODCASTS,
+        CAN_CLAIM_N_PLAYLISTS, CAN_CLAIM_N_SAVED_ALBUMS, CAN_CLAIM_N_SAVED_TRACKS,
+        CAN_CLAIM_N_TRACKS_TOTAL, CAN_CLAIM_PLAYED_DURING_EVENT, CAN_CLAIM_PLAYLIST_CLEAN,
+        CAN_CLAIM_PLAYLIST_CO_CURATED, CAN_CLAIM_PLAYLIST_CREATED_BEFORE,
+        CAN_CLAIM_PLAYLIST_FOLLOWER_THRESHOLD, CAN_CLAIM_PLAYLIST_RECENTLY_UPDATED,
+        CAN_CLAIM_PREVIEW_ACCESSIBLE, CAN_CLAIM_RECENTLY_PLAYED_TRACK,
+        CAN_CLAIM_RECENT_PLAY_ON_RELEASE_DAY, CAN_CLAIM_REFERENCE_SEQUENCE, CAN_CLAIM_ROYALTY_FREE,
+        CAN_CLAIM_SAME_RECORDING, CAN_CLAIM_SAVED_SHOW_COUNT, CAN_CLAIM_SESSION_TRACK_COUNT,
+        CAN_CLAIM_SHARED_TOP_TRACK, CAN_CLAIM_SHOW_SAVED, CAN_CLAIM_TOP_ARTISTS,
+        CAN_CLAIM_TOP_TRACKS, CAN_CLAIM_TOP_TRACKS_ALL_EXPLICIT, CAN_CLAIM_TOP_TRACKS_DECADE,
+        CAN_CLAIM_TOP_TRACKS_MONOARTIST, CAN_CLAIM_TOP_TRACKS_TEMPORAL_DIVERSITY,
+        CAN_CLAIM_TOP_TRACK_STABILITY, CAN_CLAIM_TRACK_ADDED_BY, CAN_CLAIM_TRACK_ALL_RANGES,
+        CAN_CLAIM_TRACK_ANALYSIS_SUMMARY, CAN_CLAIM_TRACK_BARS_COUNT, CAN_CLAIM_TRACK_BEATS_COUNT,
+        CAN_CLAIM_TRACK_FADE_IN, CAN_CLAIM_TRACK_FADE_OUT, CAN_CLAIM_TRACK_HAS_MUSIC_VIDEO,
+        CAN_CLAIM_TRACK_IN_TV_SHOW, CAN_CLAIM_TRACK_IS_COVER, CAN_CLAIM_TRACK_NOT_RECENTLY_PLAYED,
+        CAN_CLAIM_TRACK_N_MARKETS, CAN_CLAIM_TRACK_PLAYLIST_COUNT, CAN_CLAIM_TRACK_REPEAT_COUNT,
+        CAN_CLAIM_TRACK_SAMPLING, CAN_CLAIM_TRACK_SEGMENTS_COUNT,
+        CAN_CLAIM_TRACK_SPOTIFY_EXCLUSIVE, CAN_CLAIM_TRACK_TATUMS_COUNT,
+        CAN_CLAIM_WORKOUT_LISTENER, CLAIMS, DEFAULT_MAX_SHARED_PROOF_USERS,
+        DEFAULT_PLAYTHROUGH_MAX_GAP_MINUTES, DEFAULT_STABILITY_LIST_RANGE, KEY_PREFIX_SEPARATOR,
+        MAX_REFERENCE_LIST_LEN, MAX_REFERENCE_SEQUENCE_LEN, PLAYLIST_SNAPSHOT_RETENTION_SECS,
+        ROTATE_TOKEN_PREFIX_LEN, STORED_REFERENCE_LIST_PREFIX,
     },
 };
+use ::redis::RedisResult;
+use chrono::{NaiveDate, Utc};
+use futures::future::try_join_all;
+use futures::stream::{self, StreamExt, TryStreamExt};
 use jsonrpc_core::types::Value;
 use jsonrpc_core::{Error, IoHandler, Params};
 use serde_json::json;
+use std::collections::HashSet;
+use std::env;
+use std::sync::Arc;
+
+use crate::admin::require_admin;
+use crate::clock::{Clock, SystemClock};
+use crate::encoding::{hex_to_char, hex_to_u16, hex_to_u32, hex_to_u64, hex_to_u8};
+use crate::geo::{blocked_markets, check_market};
+use crate::leniency::{self, Leniency, Policy, StrictMode, POLICY};
+use crate::providers::musicbrainz::is_mbid;
+use crate::signing::{sign, signing_key};
+use crate::telemetry::{in_span, SpanKind};
+use crate::usage::{health, recent_failures, usage_between, CLAIM_FUNCTION};
 
 use crate::query_builder::{
-    can_claim_recently_played_track, can_claim_top_artist, can_claim_top_tracks,
+    audio_analysis::{
+        can_claim_track_analysis_bars_count, can_claim_track_analysis_beats_count,
+        can_claim_track_analysis_end_of_fade_in, can_claim_track_analysis_segments_count,
+        can_claim_track_analysis_start_of_fade_out, can_claim_track_analysis_summary,
+        can_claim_track_analysis_tatums_count,
+    },
+    audiobooks::{can_claim_audiobook_chapter_listened, can_claim_audiobook_saved},
+    biography::can_claim_artist_biography_length,
+    can_claim_artist_has_n_followers, can_claim_artist_in_all_top_ranges,
+    can_claim_disjoint_top_artists, can_claim_genre_entropy, can_claim_genre_not_in_top_artists,
+    can_claim_listening_evolved, can_claim_recently_played_track, can_claim_shared_top_track,
+    can_claim_top_artist, can_claim_top_tracks, can_claim_top_tracks_all_by_same_artist,
+    can_claim_top_tracks_all_explicit, can_claim_top_tracks_decade_count,
+    can_claim_top_tracks_span_multiple_decades, can_claim_top_tracks_stability,
+    can_claim_track_in_all_top_ranges,
+    concerts::{can_claim_artist_tour_announced, can_claim_event_in_user_city},
+    covers::{can_claim_track_covers_original, can_claim_track_same_recording},
+    devices::can_claim_device_location_hint,
+    discography::{can_claim_artist_collaboration_count, can_claim_discography_completed},
+    exclusivity::can_claim_track_exclusive_to_spotify,
+    history::{
+        can_claim_artist_not_recently_played, can_claim_artist_top_track_in_user_history,
+        can_claim_consecutive_same_artist_plays, can_claim_distinct_albums_in_history_threshold,
+        can_claim_history_contains_full_reference_sequence,
+        can_claim_multi_track_ordered_playthrough, can_claim_multi_track_session,
+        can_claim_played_within_window, can_claim_recent_play_on_release_day,
+        can_claim_track_in_morning_top, can_claim_track_in_workout_context,
+        can_claim_track_not_in_recently_played, can_claim_track_repeated_listening,
+        can_claim_user_has_listened_to_n_artists_total,
+        can_claim_user_has_listened_to_n_tracks_total,
+    },
+    library::{
+        can_claim_curator_track_count, can_claim_first_saved_track_before,
+        can_claim_library_overlap_with_reference_threshold,
+        can_claim_playlist_collaborative_with_user, can_claim_playlist_created_before,
+        can_claim_playlist_follower_threshold, can_claim_playlist_has_no_explicit_tracks,
+        can_claim_playlist_recently_updated, can_claim_saved_show_count_threshold,
+        can_claim_track_added_by_curator, can_claim_track_on_n_user_playlists,
+        can_claim_user_follows_n_artists, can_claim_user_has_n_followed_podcasts,
+        can_claim_user_has_n_playlists, can_claim_user_has_n_saved_albums,
+        can_claim_user_has_n_saved_tracks, observe_playlist_snapshot, playlist_snapshot_id,
+    },
+    markets::{
+        can_claim_album_available_in_n_markets, can_claim_artist_markets_count,
+        can_claim_track_available_in_n_markets, can_claim_track_preview_url_accessible,
+    },
+    podcasts::{
+        can_claim_episode_in_show, can_claim_episode_listened_to_completion,
+        can_claim_show_in_saved_shows, can_claim_track_is_podcast_episode,
+    },
+    royalty_free::can_claim_track_royalty_free,
+    sampling::can_claim_track_sampling,
+    sync_licensing::can_claim_track_in_tv_show,
+    videos::can_claim_track_has_music_video,
+    MAX_CONCURRENT_REQUESTS,
 };
 
+/// Longest date range `provider_usage` reports on, in days.
+const MAX_USAGE_RANGE_DAYS: i64 = 366;
+
 async fn validate_and_extract_inputs(
     params: &serde_json::Value,
 ) -> Result<(&Vec<Value>, &Vec<Value>, &Vec<Value>, &Vec<Value>), Error> {
@@ -42,9 +154,36 @@ async fn validate_and_extract_inputs(
     Ok((key, track, time_range, list_range))
 }
 
+/// Like [`validate_and_extract_inputs`], for claims taking three inputs.
+async fn validate_and_extract_three_inputs(
+    params: &serde_json::Value,
+) -> Result<(&Vec<Value>, &Vec<Value>, &Vec<Value>), Error> {
+    let inputs = params
+        .get("inputs")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| Error::invalid_params("Missing or invalid 'inputs'"))?;
+
+    if inputs.len() != 3 {
+        return Err(Error::invalid_params(
+            "Invalid input; requires 3 distinct inputs",
+        ));
+    }
+
+    let key = inputs[0]
+        .as_array()
+        .ok_or_else(|| Error::invalid_params("First input must be an array"))?;
+    let subject = inputs[1]
+        .as_array()
+        .ok_or_else(|| Error::invalid_params("Second input must be an array"))?;
+    let threshold = inputs[2]
+        .as_array()
+        .ok_or_else(|| Error::invalid_params("Third input must be an array"))?;
+
+    Ok((key, subject, threshold))
+}
+
 async fn handle_can_claim_top_tracks(params: &serde_json::Value) -> Result<Value, Error> {
     let (key, track, time_range, list_range) = validate_and_extract_inputs(params).await?;
-    let key_data: String = key.iter().map(hex_to_char).collect();
     let track_data: String = track.iter().map(hex_to_char).collect();
     let time_range_data: Vec<u8> = time_range.iter().map(hex_to_u8).collect();
     let list_range_data: Vec<u8> = list_range.iter().map(hex_to_u8).collect();
@@ -56,8 +195,8 @@ async fn handle_can_claim_top_tracks(params: &serde_json::Value) -> Result<Value
     let time_range_type = TimeRange::from_number(time_range_data[0])
         .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
 
-    let auth_data = get_token(key_data.clone())
-        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
     //println!("auth_data:{}", auth_data);
     can_claim_top_tracks(auth_data, track_data, time_range_type, list_range_data[0])
         .await
@@ -65,9 +204,25 @@ async fn handle_can_claim_top_tracks(params: &serde_json::Value) -> Result<Value
         .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
 }
 
+async fn handle_can_claim_track_all_ranges(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, track, _, list_range) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let list_range_data: Vec<u8> = list_range.iter().map(hex_to_u8).collect();
+
+    if list_range_data.is_empty() {
+        return Err(Error::invalid_params("List range is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_in_all_top_ranges(auth_data, track_data, list_range_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
 async fn handle_can_claim_top_artist(params: &serde_json::Value) -> Result<Value, Error> {
     let (key, track, time_range, list_range) = validate_and_extract_inputs(params).await?;
-    let key_data: String = key.iter().map(hex_to_char).collect();
     let track_data: String = track.iter().map(hex_to_char).collect();
     let time_range_data: Vec<u8> = time_range.iter().map(hex_to_u8).collect();
     let list_range_data: Vec<u8> = list_range.iter().map(hex_to_u8).collect();
@@ -79,8 +234,8 @@ async fn handle_can_claim_top_artist(params: &serde_json::Value) -> Result<Value
     let time_range_type = TimeRange::from_number(time_range_data[0])
         .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
 
-    let auth_data = get_token(key_data.clone())
-        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
     //println!("auth_data:{}", auth_data);
     can_claim_top_artist(auth_data, track_data, time_range_type, list_range_data[0])
         .await
@@ -88,11 +243,27 @@ async fn handle_can_claim_top_artist(params: &serde_json::Value) -> Result<Value
         .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
 }
 
+async fn handle_can_claim_artist_all_ranges(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, artist, _, list_range) = validate_and_extract_inputs(params).await?;
+    let artist_data: String = artist.iter().map(hex_to_char).collect();
+    let list_range_data: Vec<u8> = list_range.iter().map(hex_to_u8).collect();
+
+    if list_range_data.is_empty() {
+        return Err(Error::invalid_params("List range is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_artist_in_all_top_ranges(auth_data, artist_data, list_range_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
 async fn handle_can_claim_recently_played_track(
     params: &serde_json::Value,
 ) -> Result<Value, Error> {
     let (key, track, after_range, play_time_range) = validate_and_extract_inputs(params).await?;
-    let key_data: String = key.iter().map(hex_to_char).collect();
     let track_data: String = track.iter().map(hex_to_char).collect();
     let after_data: Vec<u64> = after_range.iter().map(hex_to_u64).collect();
     let played_time_data: Vec<u8> = play_time_range.iter().map(hex_to_u8).collect();
@@ -101,8 +272,8 @@ async fn handle_can_claim_recently_played_track(
         return Err(Error::invalid_params("Time range or list range is empty"));
     }
 
-    let auth_data = get_token(key_data.clone())
-        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
     //println!("auth_data: {}", auth_data);
     can_claim_recently_played_track(auth_data, track_data, after_data[0], played_time_data[0])
         .await
@@ -110,93 +281,2106 @@ async fn handle_can_claim_recently_played_track(
         .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
 }
 
-fn hex_to_u8(hex_string: &Value) -> u8 {
-    let hex_str = hex_string.as_str().unwrap_or("\0");
-    u8::from_str_radix(&hex_str[2..], 16).unwrap_or(0)
+async fn handle_can_claim_library_overlap(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, reference, _, threshold) = validate_and_extract_inputs(params).await?;
+    let reference_data: String = reference.iter().map(hex_to_char).collect();
+    let threshold_data: Vec<u8> = threshold.iter().map(hex_to_u8).collect();
+
+    if threshold_data.is_empty() {
+        return Err(Error::invalid_params("Threshold is empty"));
+    }
+
+    let reference_ids = resolve_reference_list(&reference_data)?;
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_library_overlap_with_reference_threshold(auth_data, reference_ids, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_segments_count(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, track, threshold, _) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let threshold_data: Vec<u64> = threshold.iter().map(hex_to_u64).collect();
+
+    if threshold_data.is_empty() {
+        return Err(Error::invalid_params("Threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_analysis_segments_count(auth_data, track_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_beats_count(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, track, threshold, _) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let threshold_data: Vec<u64> = threshold.iter().map(hex_to_u64).collect();
+
+    if threshold_data.is_empty() {
+        return Err(Error::invalid_params("Threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_analysis_beats_count(auth_data, track_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_bars_count(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, track, threshold, _) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let threshold_data: Vec<u64> = threshold.iter().map(hex_to_u64).collect();
+
+    if threshold_data.is_empty() {
+        return Err(Error::invalid_params("Threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_analysis_bars_count(auth_data, track_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
 }
 
-fn hex_to_u64(hex_string: &Value) -> u64 {
-    let hex_str = hex_string.as_str().unwrap_or("\0");
-    u64::from_str_radix(&hex_str[2..], 16).unwrap_or(0)
+async fn handle_can_claim_track_tatums_count(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, track, threshold, _) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let threshold_data: Vec<u64> = threshold.iter().map(hex_to_u64).collect();
+
+    if threshold_data.is_empty() {
+        return Err(Error::invalid_params("Threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_analysis_tatums_count(auth_data, track_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
 }
 
-fn hex_to_char(hex_string: &Value) -> char {
-    let hex_str = match hex_string.as_str() {
-        Some(s) => s,
-        None => return '\0',
-    };
+async fn handle_can_claim_track_fade_out(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, track, min_seconds, _) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let min_seconds_data: Vec<u16> = min_seconds.iter().map(hex_to_u16).collect();
+
+    if min_seconds_data.is_empty() {
+        return Err(Error::invalid_params("Minimum seconds is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_analysis_start_of_fade_out(auth_data, track_data, min_seconds_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
 
-    if !hex_str.starts_with("0x") {
-        return '\0';
+async fn handle_can_claim_track_fade_in(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, track, max_seconds, _) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let max_seconds_data: Vec<u16> = max_seconds.iter().map(hex_to_u16).collect();
+
+    if max_seconds_data.is_empty() {
+        return Err(Error::invalid_params("Maximum seconds is empty"));
     }
 
-    let trimmed = &hex_str[2..];
-    let number = match u32::from_str_radix(trimmed, 16) {
-        Ok(n) => n,
-        Err(_) => return '\0',
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_analysis_end_of_fade_in(auth_data, track_data, max_seconds_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_analysis_summary(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, track, _, _) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_analysis_summary(auth_data, track_data)
+        .await
+        .map(|counts| json!({ "values": counts }))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_recent_play_on_release_day(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, track, _, mode) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let mode_data: Vec<u8> = mode.iter().map(hex_to_u8).collect();
+
+    if mode_data.is_empty() {
+        return Err(Error::invalid_params("Mode is empty"));
+    }
+    // 0 = live history only, 1 = journal allowed
+    let allow_journal = match mode_data[0] {
+        0 => false,
+        1 => true,
+        _ => return Err(Error::invalid_params("Invalid mode; expected 0 or 1")),
     };
 
-    match char::from_u32(number) {
-        Some(c) => c,
-        None => '\0',
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_recent_play_on_release_day(auth_data, track_data, allow_journal)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_artist_top_track_in_history(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, artist, after_range, _) = validate_and_extract_inputs(params).await?;
+    let artist_data: String = artist.iter().map(hex_to_char).collect();
+    let after_data: Vec<u64> = after_range.iter().map(hex_to_u64).collect();
+
+    if after_data.is_empty() {
+        return Err(Error::invalid_params("After range is empty"));
     }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_artist_top_track_in_user_history(auth_data, artist_data, after_data[0] as i64)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
 }
 
-pub fn create_io() -> IoHandler {
-    let mut io = IoHandler::default();
-    io.add_method("resolve_foreign_call", |params: Params| async {
-        match params {
-            Params::Array(items) if items.len() == 1 => {
-                let params = &items[0];
+async fn handle_can_claim_album_playthrough(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, album, after_range, gap_range) = validate_and_extract_inputs(params).await?;
+    let album_data: String = album.iter().map(hex_to_char).collect();
+    let after_data: Vec<u64> = after_range.iter().map(hex_to_u64).collect();
+    let gap_data: Vec<u8> = gap_range.iter().map(hex_to_u8).collect();
 
-                if !params.is_object() {
-                    return Err(Error::invalid_params("Invalid params; expected an object"));
-                }
+    if after_data.is_empty() || gap_data.is_empty() {
+        return Err(Error::invalid_params("After range or gap range is empty"));
+    }
 
-                let function = params.get("function");
-                if let Some(function) = function {
-                    if function == CAN_CLAIM_TOP_TRACKS {
-                        return handle_can_claim_top_tracks(params).await;
-                    } else if function == CAN_CLAIM_TOP_ARTISTS {
-                        return handle_can_claim_top_artist(params).await;
-                    } else if function == CAN_CLAIM_RECENTLY_PLAYED_TRACK {
-                        return handle_can_claim_recently_played_track(params).await;
-                    } else {
-                        return Err(Error::invalid_params("Invalid method"));
-                    }
-                } else {
-                    return Err(Error::invalid_params("Missing 'function' field"));
-                }
-            }
-            _ => Err(Error::invalid_params(
-                "Invalid params; expected a single-item array",
-            )),
+    // A zero gap byte defers to the configured default.
+    let max_gap_minutes = match gap_data[0] {
+        0 => {
+            leniency::check(Leniency::DefaultPlaythroughGap)
+                .map_err(|e| Error::invalid_params(e.to_string()))?;
+            env::var("PLAYTHROUGH_MAX_GAP_MINUTES")
+                .ok()
+                .and_then(|minutes| minutes.parse::<u8>().ok())
+                .unwrap_or(DEFAULT_PLAYTHROUGH_MAX_GAP_MINUTES)
         }
-    });
+        minutes => minutes,
+    };
 
-    io.add_method("store_key", |params: Params| async move {
-        // Parse the parameters into a tuple of two strings
-        let (id, token): (String, String) = params
-            .parse::<(String, String)>()
-            .map_err(|e| Error::invalid_params(e.message))?;
-        if id.is_empty() || token.is_empty() {
-            return Err(Error::invalid_params("ID or token cannot be empty"));
-        }
-        store_key_and_token(id.clone(), token.clone())
-            .map_err(|e| Error::invalid_params(e.to_string()))?;
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_multi_track_ordered_playthrough(
+        auth_data,
+        album_data,
+        after_data[0] as i64,
+        max_gap_minutes,
+    )
+    .await
+    .map(|result| json!({"values": [result]}))
+    .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
 
-        Ok(Value::String(id))
-    });
+async fn handle_can_claim_saved_show_count(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, _, threshold, _) = validate_and_extract_inputs(params).await?;
+    let threshold_data: Vec<u64> = threshold.iter().map(hex_to_u64).collect();
 
-    io.add_method("delete_key", |params: Params| async move {
-        // Parse the parameters into a tuple of two strings
-        let id: String = params
-            .parse::<String>()
-            .map_err(|e| Error::invalid_params(e.message))?;
-        if id.is_empty() {
-            return Err(Error::invalid_params("ID or token cannot be empty"));
-        }
-        delete_token(id.clone()).map_err(|e| Error::invalid_params(e.to_string()))?;
+    if threshold_data.is_empty() {
+        return Err(Error::invalid_params("Threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_saved_show_count_threshold(auth_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_distinct_albums_in_history(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, _, after_range, threshold) = validate_and_extract_inputs(params).await?;
+    let after_data: Vec<u64> = after_range.iter().map(hex_to_u64).collect();
+    let threshold_data: Vec<u8> = threshold.iter().map(hex_to_u8).collect();
+
+    if after_data.is_empty() || threshold_data.is_empty() {
+        return Err(Error::invalid_params("After range or threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_distinct_albums_in_history_threshold(
+        auth_data,
+        after_data[0] as i64,
+        threshold_data[0],
+    )
+    .await
+    .map(|result| json!({"values": [result]}))
+    .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_played_during_event(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, event, _, _) = validate_and_extract_inputs(params).await?;
+    let event_data: String = event.iter().map(hex_to_char).collect();
+
+    let stored = get_event(event_data.clone())
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?
+        .ok_or_else(|| {
+            Error::invalid_params_with_details("Unknown or expired event", &event_data)
+        })?;
+    let event: ListeningEvent = serde_json::from_str(&stored)
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    let result =
+        can_claim_played_within_window(auth_data, event.track_id.clone(), event.start, event.end)
+            .await
+            .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+
+    // Echo the resolved window so the answer describes what was actually checked.
+    Ok(json!({
+        "values": [result],
+        "event": {
+            "id": event_data,
+            "track_id": event.track_id,
+            "start": event.start,
+            "end": event.end,
+        },
+    }))
+}
+
+async fn handle_can_claim_n_tracks_total(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, _, threshold, _) = validate_and_extract_inputs(params).await?;
+    let threshold_data: Vec<u64> = threshold.iter().map(hex_to_u64).collect();
+
+    if threshold_data.is_empty() {
+        return Err(Error::invalid_params("Threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_user_has_listened_to_n_tracks_total(auth_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_n_artists_total(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, _, threshold, _) = validate_and_extract_inputs(params).await?;
+    let threshold_data: Vec<u64> = threshold.iter().map(hex_to_u64).collect();
+
+    if threshold_data.is_empty() {
+        return Err(Error::invalid_params("Threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_user_has_listened_to_n_artists_total(auth_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_consecutive_artist_plays(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, artist, after_range, threshold) = validate_and_extract_inputs(params).await?;
+    let artist_data: String = artist.iter().map(hex_to_char).collect();
+    let after_data: Vec<u64> = after_range.iter().map(hex_to_u64).collect();
+    let threshold_data: Vec<u8> = threshold.iter().map(hex_to_u8).collect();
+
+    if after_data.is_empty() || threshold_data.is_empty() {
+        return Err(Error::invalid_params("After range or threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_consecutive_same_artist_plays(
+        auth_data,
+        artist_data,
+        after_data[0] as i64,
+        threshold_data[0],
+    )
+    .await
+    .map(|result| json!({"values": [result]}))
+    .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_playlist_count(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, track, threshold, _) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let threshold_data: Vec<u8> = threshold.iter().map(hex_to_u8).collect();
+
+    if threshold_data.is_empty() {
+        return Err(Error::invalid_params("Threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_on_n_user_playlists(auth_data, track_data, threshold_data[0])
+        .await
+        .map(|values| json!({ "values": values }))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_follows_n_artists(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, _, threshold, _) = validate_and_extract_inputs(params).await?;
+    let threshold_data: Vec<u64> = threshold.iter().map(hex_to_u64).collect();
+
+    if threshold_data.is_empty() {
+        return Err(Error::invalid_params("Threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_user_follows_n_artists(auth_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_playlist_follower_threshold(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, playlist, threshold, mode) = validate_and_extract_inputs(params).await?;
+    let playlist_data: String = playlist.iter().map(hex_to_char).collect();
+    let threshold_data: Vec<u64> = threshold.iter().map(hex_to_u64).collect();
+    let mode_data: Vec<u8> = mode.iter().map(hex_to_u8).collect();
+
+    if threshold_data.is_empty() || mode_data.is_empty() {
+        return Err(Error::invalid_params("Threshold or mode is empty"));
+    }
+    // 0 = any accessible playlist, 1 = owned by the user
+    let require_owner = match mode_data[0] {
+        0 => false,
+        1 => true,
+        _ => return Err(Error::invalid_params("Invalid mode; expected 0 or 1")),
+    };
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_playlist_follower_threshold(
+        auth_data,
+        playlist_data,
+        threshold_data[0],
+        require_owner,
+    )
+    .await
+    .map(|result| json!({"values": [result]}))
+    .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_user_has_n_saved_tracks(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, _, threshold, _) = validate_and_extract_inputs(params).await?;
+    let threshold_data: Vec<u32> = threshold.iter().map(hex_to_u32).collect();
+
+    if threshold_data.is_empty() {
+        return Err(Error::invalid_params("Threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_user_has_n_saved_tracks(auth_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_user_has_n_saved_albums(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, _, threshold, _) = validate_and_extract_inputs(params).await?;
+    let threshold_data: Vec<u32> = threshold.iter().map(hex_to_u32).collect();
+
+    if threshold_data.is_empty() {
+        return Err(Error::invalid_params("Threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_user_has_n_saved_albums(auth_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_reference_sequence(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, reference, after_range, gap_range) = validate_and_extract_inputs(params).await?;
+    let reference_data: String = reference.iter().map(hex_to_char).collect();
+    let after_data: Vec<u64> = after_range.iter().map(hex_to_u64).collect();
+    let gap_data: Vec<u8> = gap_range.iter().map(hex_to_u8).collect();
+
+    if after_data.is_empty() || gap_data.is_empty() {
+        return Err(Error::invalid_params("After range or gap range is empty"));
+    }
+
+    let sequence = resolve_reference_list(&reference_data)?;
+    if sequence.len() > MAX_REFERENCE_SEQUENCE_LEN {
+        return Err(Error::invalid_params(format!(
+            "Reference sequence exceeds the maximum of {} tracks",
+            MAX_REFERENCE_SEQUENCE_LEN
+        )));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_history_contains_full_reference_sequence(
+        auth_data,
+        sequence,
+        after_data[0] as i64,
+        gap_data[0],
+    )
+    .await
+    .map(|result| json!({"values": [result]}))
+    .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_user_has_n_playlists(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, _, threshold, _) = validate_and_extract_inputs(params).await?;
+    let threshold_data: Vec<u32> = threshold.iter().map(hex_to_u32).collect();
+
+    if threshold_data.is_empty() {
+        return Err(Error::invalid_params("Threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_user_has_n_playlists(auth_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_user_has_n_followed_podcasts(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, _, threshold, _) = validate_and_extract_inputs(params).await?;
+    let threshold_data: Vec<u32> = threshold.iter().map(hex_to_u32).collect();
+
+    if threshold_data.is_empty() {
+        return Err(Error::invalid_params("Threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_user_has_n_followed_podcasts(auth_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_artist_has_n_followers(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, artist, threshold) = validate_and_extract_three_inputs(params).await?;
+    let artist_data: String = artist.iter().map(hex_to_char).collect();
+    let threshold_data: Vec<u64> = threshold.iter().map(hex_to_u64).collect();
+
+    if artist_data.is_empty() || threshold_data.is_empty() {
+        return Err(Error::invalid_params("Artist or threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_artist_has_n_followers(auth_data, artist_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_top_tracks_stability(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, track, first_range, second_range) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let first_range_data: Vec<u8> = first_range.iter().map(hex_to_u8).collect();
+    let second_range_data: Vec<u8> = second_range.iter().map(hex_to_u8).collect();
+
+    if first_range_data.is_empty() || second_range_data.is_empty() {
+        return Err(Error::invalid_params("Time ranges are empty"));
+    }
+
+    let time_ranges = (
+        TimeRange::from_number(first_range_data[0])
+            .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?,
+        TimeRange::from_number(second_range_data[0])
+            .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?,
+    );
+    let list_range = env::var("STABILITY_LIST_RANGE")
+        .ok()
+        .and_then(|range| range.parse::<u8>().ok())
+        .unwrap_or(DEFAULT_STABILITY_LIST_RANGE);
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_top_tracks_stability(auth_data, track_data, time_ranges, list_range)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_is_podcast_episode(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, episode, _, _) = validate_and_extract_inputs(params).await?;
+    let episode_data: String = episode.iter().map(hex_to_char).collect();
+
+    if episode_data.is_empty() {
+        return Err(Error::invalid_params("Episode ID is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_is_podcast_episode(auth_data, episode_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_episode_in_show(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, episode, show, _) = validate_and_extract_inputs(params).await?;
+    let episode_data: String = episode.iter().map(hex_to_char).collect();
+    let show_data: String = show.iter().map(hex_to_char).collect();
+
+    if episode_data.is_empty() || show_data.is_empty() {
+        return Err(Error::invalid_params("Episode or show ID is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_episode_in_show(auth_data, episode_data, show_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_show_in_saved_shows(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, show, _, _) = validate_and_extract_inputs(params).await?;
+    let show_data: String = show.iter().map(hex_to_char).collect();
+
+    if show_data.is_empty() {
+        return Err(Error::invalid_params("Show ID is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_show_in_saved_shows(auth_data, show_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_episode_listened_to_completion(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, episode, _, _) = validate_and_extract_inputs(params).await?;
+    let episode_data: String = episode.iter().map(hex_to_char).collect();
+
+    if episode_data.is_empty() {
+        return Err(Error::invalid_params("Episode ID is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_episode_listened_to_completion(auth_data, episode_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_audiobook_saved(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, audiobook, _, _) = validate_and_extract_inputs(params).await?;
+    let audiobook_data: String = audiobook.iter().map(hex_to_char).collect();
+
+    if audiobook_data.is_empty() {
+        return Err(Error::invalid_params("Audiobook ID is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_audiobook_saved(auth_data, audiobook_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_audiobook_chapter_listened(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, audiobook, chapter, min_percent) = validate_and_extract_inputs(params).await?;
+    let audiobook_data: String = audiobook.iter().map(hex_to_char).collect();
+    let chapter_data: String = chapter.iter().map(hex_to_char).collect();
+    let min_percent_data: Vec<u8> = min_percent.iter().map(hex_to_u8).collect();
+
+    if audiobook_data.is_empty() || chapter_data.is_empty() || min_percent_data.is_empty() {
+        return Err(Error::invalid_params(
+            "Audiobook ID, chapter ID or progress percentage is empty",
+        ));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_audiobook_chapter_listened(
+        auth_data,
+        audiobook_data,
+        chapter_data,
+        min_percent_data[0],
+    )
+    .await
+    .map(|result| json!({"values": [result]}))
+    .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_available_in_n_markets(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, track, threshold) = validate_and_extract_three_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let threshold_data: Vec<u8> = threshold.iter().map(hex_to_u8).collect();
+
+    if track_data.is_empty() || threshold_data.is_empty() {
+        return Err(Error::invalid_params("Track or threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_available_in_n_markets(auth_data, track_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_album_available_in_n_markets(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, album, threshold) = validate_and_extract_three_inputs(params).await?;
+    let album_data: String = album.iter().map(hex_to_char).collect();
+    let threshold_data: Vec<u8> = threshold.iter().map(hex_to_u8).collect();
+
+    if album_data.is_empty() || threshold_data.is_empty() {
+        return Err(Error::invalid_params("Album or threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_album_available_in_n_markets(auth_data, album_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_artist_markets_count(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, artist, threshold) = validate_and_extract_three_inputs(params).await?;
+    let artist_data: String = artist.iter().map(hex_to_char).collect();
+    let threshold_data: Vec<u8> = threshold.iter().map(hex_to_u8).collect();
+
+    if artist_data.is_empty() || threshold_data.is_empty() {
+        return Err(Error::invalid_params("Artist or threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_artist_markets_count(auth_data, artist_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_not_in_recently_played(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, track, after_range, play_time_range) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let after_data: Vec<u64> = after_range.iter().map(hex_to_u64).collect();
+    let played_time_data: Vec<u8> = play_time_range.iter().map(hex_to_u8).collect();
+
+    if after_data.is_empty() || played_time_data.is_empty() {
+        return Err(Error::invalid_params(
+            "After range or played times is empty",
+        ));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_not_in_recently_played(
+        auth_data,
+        track_data,
+        after_data[0] as i64,
+        played_time_data[0],
+    )
+    .await
+    .map(|result| json!({"values": [result]}))
+    .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_artist_not_recently_played(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, artist, after_range, play_time_range) = validate_and_extract_inputs(params).await?;
+    let artist_data: String = artist.iter().map(hex_to_char).collect();
+    let after_data: Vec<u64> = after_range.iter().map(hex_to_u64).collect();
+    let played_time_data: Vec<u8> = play_time_range.iter().map(hex_to_u8).collect();
+
+    if after_data.is_empty() || played_time_data.is_empty() {
+        return Err(Error::invalid_params(
+            "After range or played times is empty",
+        ));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_artist_not_recently_played(
+        auth_data,
+        artist_data,
+        after_data[0] as i64,
+        played_time_data[0],
+    )
+    .await
+    .map(|result| json!({"values": [result]}))
+    .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_genre_not_in_top_artists(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, genre, time_range, list_range) = validate_and_extract_inputs(params).await?;
+    let genre_data: String = genre.iter().map(hex_to_char).collect();
+    let time_range_data: Vec<u8> = time_range.iter().map(hex_to_u8).collect();
+    let list_range_data: Vec<u8> = list_range.iter().map(hex_to_u8).collect();
+
+    if genre_data.is_empty() || time_range_data.is_empty() || list_range_data.is_empty() {
+        return Err(Error::invalid_params(
+            "Genre, time range or list range is empty",
+        ));
+    }
+
+    let time_range_type = TimeRange::from_number(time_range_data[0])
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_genre_not_in_top_artists(auth_data, genre_data, time_range_type, list_range_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+/// Observes the playlist's current `snapshot_id` before answering, so every
+/// evaluation also narrows down when the playlist last changed.
+async fn handle_can_claim_playlist_recently_updated(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, playlist, within_hours) = validate_and_extract_three_inputs(params).await?;
+    let playlist_data: String = playlist.iter().map(hex_to_char).collect();
+    let within_hours_data: Vec<u64> = within_hours.iter().map(hex_to_u64).collect();
+
+    if playlist_data.is_empty() || within_hours_data.is_empty() {
+        return Err(Error::invalid_params("Playlist or window is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    let snapshot_id = playlist_snapshot_id(auth_data, playlist_data.clone())
+        .await
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+
+    // An unreadable snapshot is treated like a playlist not seen before.
+    let previous = get_playlist_snapshot(playlist_data.clone())
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?
+        .and_then(|stored| serde_json::from_str::<PlaylistSnapshot>(&stored).ok());
+    let now = Utc::now().timestamp();
+    let snapshot = observe_playlist_snapshot(previous, snapshot_id, now);
+    let serialized = serde_json::to_string(&snapshot)
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    store_playlist_snapshot(
+        playlist_data.clone(),
+        serialized,
+        PLAYLIST_SNAPSHOT_RETENTION_SECS,
+    )
+    .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+
+    let cutoff = now.saturating_sub(within_hours_data[0].saturating_mul(3600) as i64);
+    can_claim_playlist_recently_updated(&snapshot, &playlist_data, cutoff)
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_playlist_created_before(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, playlist, before) = validate_and_extract_three_inputs(params).await?;
+    let playlist_data: String = playlist.iter().map(hex_to_char).collect();
+    let before_data: Vec<u64> = before.iter().map(hex_to_u64).collect();
+
+    if playlist_data.is_empty() || before_data.is_empty() {
+        return Err(Error::invalid_params("Playlist or before is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_playlist_created_before(auth_data, playlist_data, before_data[0] as i64)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_first_saved_track_before(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, _, before, _) = validate_and_extract_inputs(params).await?;
+    let before_data: Vec<u64> = before.iter().map(hex_to_u64).collect();
+
+    if before_data.is_empty() {
+        return Err(Error::invalid_params("Before is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_first_saved_track_before(auth_data, before_data[0] as i64)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_listening_location(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, location, _, _) = validate_and_extract_inputs(params).await?;
+    let location_data: String = location.iter().map(hex_to_char).collect();
+
+    if location_data.trim().is_empty() {
+        return Err(Error::invalid_params("Location is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_device_location_hint(auth_data, location_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_royalty_free(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, track, _, _) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+
+    if track_data.is_empty() {
+        return Err(Error::invalid_params("Track is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_royalty_free(auth_data, track_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_covers_original(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, track, original) = validate_and_extract_three_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let original_data: String = original.iter().map(hex_to_char).collect();
+
+    if track_data.is_empty() || original_data.is_empty() {
+        return Err(Error::invalid_params(
+            "Track or original recording is empty",
+        ));
+    }
+    // MBIDs are UUIDs; anything else would only be a wasted MusicBrainz request.
+    if !is_mbid(&original_data) {
+        return Err(Error::invalid_params_with_details(
+            "Original recording is not a MusicBrainz ID",
+            &original_data,
+        ));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_covers_original(auth_data, track_data, original_data.to_lowercase())
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_same_recording(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, track, other) = validate_and_extract_three_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let other_data: String = other.iter().map(hex_to_char).collect();
+
+    if track_data.is_empty() || other_data.is_empty() {
+        return Err(Error::invalid_params("Track or other recording is empty"));
+    }
+    // `<service>:<id>`; MusicBrainz recordings are the only other service so far.
+    let recording = match other_data.split_once(':') {
+        Some(("musicbrainz", id)) if is_mbid(id) => id.to_lowercase(),
+        Some(("musicbrainz", _)) => {
+            return Err(Error::invalid_params_with_details(
+                "Other recording is not a MusicBrainz ID",
+                &other_data,
+            ))
+        }
+        _ => {
+            return Err(Error::invalid_params_with_details(
+                "Unsupported service; expected musicbrainz:<recording MBID>",
+                &other_data,
+            ))
+        }
+    };
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_same_recording(auth_data, track_data, recording)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_sampling(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, sampling, sampled) = validate_and_extract_three_inputs(params).await?;
+    let sampling_data: String = sampling.iter().map(hex_to_char).collect();
+    let sampled_data: String = sampled.iter().map(hex_to_char).collect();
+
+    if sampling_data.is_empty() || sampled_data.is_empty() {
+        return Err(Error::invalid_params("Sampling or sampled track is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_sampling(auth_data, sampling_data, sampled_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_playlist_collaborative_with_user(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, playlist, _, _) = validate_and_extract_inputs(params).await?;
+    let playlist_data: String = playlist.iter().map(hex_to_char).collect();
+
+    if playlist_data.is_empty() {
+        return Err(Error::invalid_params("Playlist is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_playlist_collaborative_with_user(auth_data, playlist_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_added_by_curator(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, playlist, track, curator) = validate_and_extract_inputs(params).await?;
+    let playlist_data: String = playlist.iter().map(hex_to_char).collect();
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let curator_data: String = curator.iter().map(hex_to_char).collect();
+
+    if playlist_data.is_empty() || track_data.is_empty() || curator_data.is_empty() {
+        return Err(Error::invalid_params("Playlist, track or curator is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_added_by_curator(auth_data, playlist_data, track_data, curator_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_curator_track_count(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, playlist, _, _) = validate_and_extract_inputs(params).await?;
+    let playlist_data: String = playlist.iter().map(hex_to_char).collect();
+
+    if playlist_data.is_empty() {
+        return Err(Error::invalid_params("Playlist is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_curator_track_count(auth_data, playlist_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_playlist_has_no_explicit_tracks(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, playlist, _, _) = validate_and_extract_inputs(params).await?;
+    let playlist_data: String = playlist.iter().map(hex_to_char).collect();
+
+    if playlist_data.is_empty() {
+        return Err(Error::invalid_params("Playlist is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_playlist_has_no_explicit_tracks(auth_data, playlist_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_top_tracks_all_by_same_artist(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, artist, time_range, list_range) = validate_and_extract_inputs(params).await?;
+    let artist_data: String = artist.iter().map(hex_to_char).collect();
+    let time_range_data: Vec<u8> = time_range.iter().map(hex_to_u8).collect();
+    let list_range_data: Vec<u8> = list_range.iter().map(hex_to_u8).collect();
+
+    if artist_data.is_empty() || time_range_data.is_empty() || list_range_data.is_empty() {
+        return Err(Error::invalid_params(
+            "Artist, time range or list range is empty",
+        ));
+    }
+
+    let time_range_type = TimeRange::from_number(time_range_data[0])
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_top_tracks_all_by_same_artist(
+        auth_data,
+        artist_data,
+        time_range_type,
+        list_range_data[0],
+    )
+    .await
+    .map(|result| json!({"values": [result]}))
+    .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_top_tracks_span_multiple_decades(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, threshold, time_range, list_range) = validate_and_extract_inputs(params).await?;
+    let threshold_data: Vec<u8> = threshold.iter().map(hex_to_u8).collect();
+    let time_range_data: Vec<u8> = time_range.iter().map(hex_to_u8).collect();
+    let list_range_data: Vec<u8> = list_range.iter().map(hex_to_u8).collect();
+
+    if threshold_data.is_empty() || time_range_data.is_empty() || list_range_data.is_empty() {
+        return Err(Error::invalid_params(
+            "Threshold, time range or list range is empty",
+        ));
+    }
+
+    let time_range_type = TimeRange::from_number(time_range_data[0])
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_top_tracks_span_multiple_decades(
+        auth_data,
+        threshold_data[0],
+        time_range_type,
+        list_range_data[0],
+    )
+    .await
+    .map(|result| json!({"values": [result]}))
+    .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_top_tracks_all_explicit(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, time_range, list_range) = validate_and_extract_three_inputs(params).await?;
+    let time_range_data: Vec<u8> = time_range.iter().map(hex_to_u8).collect();
+    let list_range_data: Vec<u8> = list_range.iter().map(hex_to_u8).collect();
+
+    if time_range_data.is_empty() || list_range_data.is_empty() {
+        return Err(Error::invalid_params("Time range or list range is empty"));
+    }
+
+    let time_range_type = TimeRange::from_number(time_range_data[0])
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_top_tracks_all_explicit(auth_data, time_range_type, list_range_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_top_tracks_decade_count(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, time_range, decade, list_range) = validate_and_extract_inputs(params).await?;
+    let time_range_data: Vec<u8> = time_range.iter().map(hex_to_u8).collect();
+    let decade_data: Vec<u16> = decade.iter().map(hex_to_u16).collect();
+    let list_range_data: Vec<u8> = list_range.iter().map(hex_to_u8).collect();
+
+    if time_range_data.is_empty() || decade_data.is_empty() || list_range_data.is_empty() {
+        return Err(Error::invalid_params(
+            "Time range, decade or list range is empty",
+        ));
+    }
+    if !decade_data[0].is_multiple_of(10) {
+        return Err(Error::invalid_params("Decade must be a multiple of 10"));
+    }
+
+    let time_range_type = TimeRange::from_number(time_range_data[0])
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_top_tracks_decade_count(
+        auth_data,
+        time_range_type,
+        decade_data[0],
+        list_range_data[0],
+    )
+    .await
+    .map(|result| json!({"values": [result]}))
+    .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_artist_collaboration_count(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, artist, threshold) = validate_and_extract_three_inputs(params).await?;
+    let artist_data: String = artist.iter().map(hex_to_char).collect();
+    let threshold_data: Vec<u8> = threshold.iter().map(hex_to_u8).collect();
+
+    if artist_data.is_empty() || threshold_data.is_empty() {
+        return Err(Error::invalid_params("Artist or threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_artist_collaboration_count(auth_data, artist_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_genre_entropy(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, time_range, threshold, list_range) = validate_and_extract_inputs(params).await?;
+    let time_range_data: Vec<u8> = time_range.iter().map(hex_to_u8).collect();
+    let threshold_data: Vec<u8> = threshold.iter().map(hex_to_u8).collect();
+    let list_range_data: Vec<u8> = list_range.iter().map(hex_to_u8).collect();
+
+    if time_range_data.is_empty() || threshold_data.is_empty() || list_range_data.is_empty() {
+        return Err(Error::invalid_params(
+            "Time range, threshold or list range is empty",
+        ));
+    }
+
+    let time_range_type = TimeRange::from_number(time_range_data[0])
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_genre_entropy(
+        auth_data,
+        time_range_type,
+        threshold_data[0],
+        list_range_data[0],
+    )
+    .await
+    .map(|result| json!({"values": [result]}))
+    .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_discography_completed(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, artist, _, _) = validate_and_extract_inputs(params).await?;
+    let artist_data: String = artist.iter().map(hex_to_char).collect();
+
+    if artist_data.is_empty() {
+        return Err(Error::invalid_params("Artist is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_discography_completed(auth_data, artist_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_listening_evolved(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, artist, list_range, mode) = validate_and_extract_inputs(params).await?;
+    let artist_data: String = artist.iter().map(hex_to_char).collect();
+    let list_range_data: Vec<u8> = list_range.iter().map(hex_to_u8).collect();
+    let mode_data: Vec<u8> = mode.iter().map(hex_to_u8).collect();
+
+    if artist_data.is_empty() || list_range_data.is_empty() || mode_data.is_empty() {
+        return Err(Error::invalid_params("Artist, list range or mode is empty"));
+    }
+
+    let mode_type = ListeningEvolution::from_number(mode_data[0])
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_listening_evolved(auth_data, artist_data, list_range_data[0], mode_type)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_repeated_listening(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, track, threshold) = validate_and_extract_three_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let threshold_data: Vec<u8> = threshold.iter().map(hex_to_u8).collect();
+
+    if track_data.is_empty() || threshold_data.is_empty() {
+        return Err(Error::invalid_params("Track or threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_repeated_listening(auth_data, track_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_multi_track_session(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, _, threshold, after_range) = validate_and_extract_inputs(params).await?;
+    let threshold_data: Vec<u8> = threshold.iter().map(hex_to_u8).collect();
+    let after_data: Vec<u64> = after_range.iter().map(hex_to_u64).collect();
+
+    if threshold_data.is_empty() || after_data.is_empty() {
+        return Err(Error::invalid_params("Threshold or after range is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_multi_track_session(auth_data, threshold_data[0], after_data[0] as i64)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_in_morning_top(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, track, after_range, list_range) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let after_data: Vec<u64> = after_range.iter().map(hex_to_u64).collect();
+    let list_range_data: Vec<u8> = list_range.iter().map(hex_to_u8).collect();
+
+    if track_data.is_empty() || after_data.is_empty() || list_range_data.is_empty() {
+        return Err(Error::invalid_params(
+            "Track, after range or list range is empty",
+        ));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_in_morning_top(
+        auth_data,
+        track_data,
+        after_data[0] as i64,
+        list_range_data[0],
+    )
+    .await
+    .map(|result| json!({"values": [result]}))
+    .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_in_workout_context(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, track, after_range, _) = validate_and_extract_inputs(params).await?;
+    // An empty track matches plays of any track.
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let after_data: Vec<u64> = after_range.iter().map(hex_to_u64).collect();
+
+    if after_data.is_empty() {
+        return Err(Error::invalid_params("After range is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_in_workout_context(auth_data, track_data, after_data[0] as i64)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+fn max_shared_proof_users() -> usize {
+    env::var("MAX_SHARED_PROOF_USERS")
+        .ok()
+        .and_then(|users| users.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_SHARED_PROOF_USERS)
+}
+
+/// Inputs: the claimant's key, the track, then the keys of the other users.
+///
+/// At most `MAX_SHARED_PROOF_USERS` keys (default `DEFAULT_MAX_SHARED_PROOF_USERS`)
+/// may be given, the claimant's included. Every user must be outside the blocked
+/// markets, not just the claimant.
+async fn handle_can_claim_shared_top_track(params: &serde_json::Value) -> Result<Value, Error> {
+    let inputs = params
+        .get("inputs")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| Error::invalid_params("Missing or invalid 'inputs'"))?;
+    let max_users = max_shared_proof_users();
+    let users = inputs.len().saturating_sub(1);
+    if users < 2 || users > max_users {
+        return Err(Error::invalid_params(format!(
+            "Invalid input; requires a track and 2 to {} keys",
+            max_users
+        )));
+    }
+
+    let fields = inputs
+        .iter()
+        .map(|input| {
+            input
+                .as_array()
+                .ok_or_else(|| Error::invalid_params("Every input must be an array"))
+        })
+        .collect::<Result<Vec<&Vec<Value>>, Error>>()?;
+    let track_data: String = fields[1].iter().map(hex_to_char).collect();
+    if track_data.is_empty() {
+        return Err(Error::invalid_params("Track is empty"));
+    }
+
+    // Each key, and the account behind it, may only count once; otherwise a
+    // claimant could list their own key as every other user.
+    let user_keys: Vec<Vec<Value>> = fields
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != 1)
+        .map(|(_, key)| key.to_vec())
+        .collect();
+    let mut keys = HashSet::new();
+    if !user_keys.iter().all(|key| keys.insert(decode_key(key))) {
+        return Err(Error::invalid_params("Every key may only be given once"));
+    }
+
+    // Redis lookups block, so the tokens are resolved on the blocking pool, at
+    // most `MAX_CONCURRENT_REQUESTS` at a time and in the order of the keys.
+    let lookups = user_keys.into_iter().map(|key| async move {
+        tokio::task::spawn_blocking(move || key_token(&key))
+            .await
+            .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?
+            .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+    });
+    let auth_data: Vec<String> = stream::iter(lookups)
+        .buffered(MAX_CONCURRENT_REQUESTS)
+        .try_collect()
+        .await?;
+    let mut tokens = HashSet::new();
+    if !auth_data.iter().all(|token| tokens.insert(token)) {
+        return Err(Error::invalid_params(
+            "Every key must belong to a different account",
+        ));
+    }
+    can_claim_shared_top_track(auth_data, track_data.clone())
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_disjoint_top_artists(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, other_key, time_range, list_range) = validate_and_extract_inputs(params).await?;
+    let time_range_data: Vec<u8> = time_range.iter().map(hex_to_u8).collect();
+    let list_range_data: Vec<u8> = list_range.iter().map(hex_to_u8).collect();
+
+    if other_key.is_empty() || time_range_data.is_empty() || list_range_data.is_empty() {
+        return Err(Error::invalid_params(
+            "Other key, time range or list range is empty",
+        ));
+    }
+
+    let time_range_type = TimeRange::from_number(time_range_data[0])
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    let other_auth_data =
+        key_token(other_key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_disjoint_top_artists(
+        auth_data,
+        other_auth_data,
+        time_range_type,
+        list_range_data[0],
+    )
+    .await
+    .map(|result| json!({"values": [result]}))
+    .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_exclusive_to_spotify(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, track, _, _) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+
+    if track_data.is_empty() {
+        return Err(Error::invalid_params("Track is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_exclusive_to_spotify(auth_data, track_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_preview_url_accessible(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, track, _, _) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+
+    if track_data.is_empty() {
+        return Err(Error::invalid_params("Track is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_preview_url_accessible(auth_data, track_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_artist_tour_announced(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, artist, concert_artist) = validate_and_extract_three_inputs(params).await?;
+    let artist_data: String = artist.iter().map(hex_to_char).collect();
+    let concert_artist_data: String = concert_artist.iter().map(hex_to_char).collect();
+
+    if artist_data.is_empty() {
+        return Err(Error::invalid_params("Artist is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_artist_tour_announced(auth_data, artist_data, concert_artist_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_event_in_user_city(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, artist, city) = validate_and_extract_three_inputs(params).await?;
+    let artist_data: String = artist.iter().map(hex_to_char).collect();
+    let city_data: String = city.iter().map(hex_to_char).collect();
+
+    if artist_data.is_empty() || city_data.trim().is_empty() {
+        return Err(Error::invalid_params("Artist or city is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_event_in_user_city(auth_data, artist_data, city_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_in_tv_show(params: &serde_json::Value) -> Result<Value, Error> {
+    let (key, track, show) = validate_and_extract_three_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+    let show_data: String = show.iter().map(hex_to_char).collect();
+
+    if track_data.is_empty() || show_data.trim().is_empty() {
+        return Err(Error::invalid_params("Track or show is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_in_tv_show(auth_data, track_data, show_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_track_has_music_video(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, track, _, _) = validate_and_extract_inputs(params).await?;
+    let track_data: String = track.iter().map(hex_to_char).collect();
+
+    if track_data.is_empty() {
+        return Err(Error::invalid_params("Track is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_track_has_music_video(auth_data, track_data)
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+async fn handle_can_claim_artist_biography_length(
+    params: &serde_json::Value,
+) -> Result<Value, Error> {
+    let (key, artist, threshold) = validate_and_extract_three_inputs(params).await?;
+    let artist_data: String = artist.iter().map(hex_to_char).collect();
+    let threshold_data: Vec<u16> = threshold.iter().map(hex_to_u16).collect();
+
+    if artist_data.is_empty() || threshold_data.is_empty() {
+        return Err(Error::invalid_params("Artist or threshold is empty"));
+    }
+
+    let auth_data =
+        key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+    can_claim_artist_biography_length(auth_data, artist_data, threshold_data[0])
+        .await
+        .map(|result| json!({"values": [result]}))
+        .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))
+}
+
+/// Resolves a reference list input: either `list:<id>` naming a list stored with
+/// `store_reference_list`, or an inline comma-separated list of IDs.
+fn resolve_reference_list(reference: &str) -> Result<Vec<String>, Error> {
+    let reference_ids: Vec<String> = match reference.strip_prefix(STORED_REFERENCE_LIST_PREFIX) {
+        Some(id) => get_reference_list(id.to_string())
+            .map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?,
+        None => reference
+            .split(',')
+            .map(str::trim)
+            .filter(|id| {
+                if id.is_empty() {
+                    leniency::note(Leniency::EmptyReferenceEntries);
+                }
+                !id.is_empty()
+            })
+            .map(String::from)
+            .collect(),
+    };
+
+    if reference_ids.is_empty() {
+        return Err(Error::invalid_params("Reference list is empty or unknown"));
+    }
+    if reference_ids.len() > MAX_REFERENCE_LIST_LEN {
+        return Err(Error::invalid_params(format!(
+            "Reference list exceeds the maximum of {} IDs",
+            MAX_REFERENCE_LIST_LEN
+        )));
+    }
+    Ok(reference_ids)
+}
+
+/// The inputs of claim `function` that are keys of the users it is about: the
+/// claimant's, and the other users' for claims comparing several accounts.
+fn user_key_inputs<'a>(function: &str, inputs: &'a [Value]) -> Vec<&'a Vec<Value>> {
+    let is_key = |i: usize| match function {
+        CAN_CLAIM_SHARED_TOP_TRACK => i != 1,
+        CAN_CLAIM_DISJOINT_ARTISTS => i <= 1,
+        _ => i == 0,
+    };
+    inputs
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| is_key(*i))
+        .filter_map(|(_, input)| input.as_array())
+        .collect()
+}
+
+/// Rejects claims about any user in a blocked market, when markets are blocked.
+///
+/// Runs once per claim, before its handler, for every user the claim is about.
+async fn check_claim_market(function: &str, params: &serde_json::Value) -> Result<(), Error> {
+    if blocked_markets().is_empty() {
+        return Ok(());
+    }
+    // Missing or malformed key inputs are reported by the claim's handler.
+    let Some(inputs) = params.get("inputs").and_then(Value::as_array) else {
+        return Ok(());
+    };
+    let keys = user_key_inputs(function, inputs);
+    if keys.len() > max_shared_proof_users() {
+        return Ok(());
+    }
+    try_join_all(keys.into_iter().map(|key| async move {
+        let auth_data =
+            key_token(key).map_err(|e| Error::invalid_params_with_details(e.to_string(), ""))?;
+        check_market(auth_data).await
+    }))
+    .await
+    .map(|_| ())
+}
+
+/// Routes a `resolve_foreign_call` request to the handler of the named claim.
+async fn dispatch_claim(function: &str, params: &serde_json::Value) -> Result<Value, Error> {
+    match function {
+        CAN_CLAIM_TOP_TRACKS => handle_can_claim_top_tracks(params).await,
+        CAN_CLAIM_TOP_ARTISTS => handle_can_claim_top_artist(params).await,
+        CAN_CLAIM_TRACK_ALL_RANGES => handle_can_claim_track_all_ranges(params).await,
+        CAN_CLAIM_ARTIST_ALL_RANGES => handle_can_claim_artist_all_ranges(params).await,
+        CAN_CLAIM_RECENTLY_PLAYED_TRACK => handle_can_claim_recently_played_track(params).await,
+        CAN_CLAIM_LIBRARY_OVERLAP => handle_can_claim_library_overlap(params).await,
+        CAN_CLAIM_TRACK_SEGMENTS_COUNT => handle_can_claim_track_segments_count(params).await,
+        CAN_CLAIM_TRACK_BEATS_COUNT => handle_can_claim_track_beats_count(params).await,
+        CAN_CLAIM_TRACK_BARS_COUNT => handle_can_claim_track_bars_count(params).await,
+        CAN_CLAIM_TRACK_TATUMS_COUNT => handle_can_claim_track_tatums_count(params).await,
+        CAN_CLAIM_TRACK_ANALYSIS_SUMMARY => handle_can_claim_track_analysis_summary(params).await,
+        CAN_CLAIM_RECENT_PLAY_ON_RELEASE_DAY => {
+            handle_can_claim_recent_play_on_release_day(params).await
+        }
+        CAN_CLAIM_ALBUM_PLAYTHROUGH => handle_can_claim_album_playthrough(params).await,
+        CAN_CLAIM_TRACK_FADE_OUT => handle_can_claim_track_fade_out(params).await,
+        CAN_CLAIM_TRACK_FADE_IN => handle_can_claim_track_fade_in(params).await,
+        CAN_CLAIM_SAVED_SHOW_COUNT => handle_can_claim_saved_show_count(params).await,
+        CAN_CLAIM_DISTINCT_ALBUMS_IN_HISTORY => {
+            handle_can_claim_distinct_albums_in_history(params).await
+        }
+        CAN_CLAIM_PLAYED_DURING_EVENT => handle_can_claim_played_during_event(params).await,
+        CAN_CLAIM_ARTIST_TOP_TRACK_IN_HISTORY => {
+            handle_can_claim_artist_top_track_in_history(params).await
+        }
+        CAN_CLAIM_N_TRACKS_TOTAL => handle_can_claim_n_tracks_total(params).await,
+        CAN_CLAIM_N_ARTISTS_TOTAL => handle_can_claim_n_artists_total(params).await,
+        CAN_CLAIM_CONSECUTIVE_ARTIST_PLAYS => {
+            handle_can_claim_consecutive_artist_plays(params).await
+        }
+        CAN_CLAIM_TRACK_PLAYLIST_COUNT => handle_can_claim_track_playlist_count(params).await,
+        CAN_CLAIM_FOLLOWS_N_ARTISTS => handle_can_claim_follows_n_artists(params).await,
+        CAN_CLAIM_PLAYLIST_FOLLOWER_THRESHOLD => {
+            handle_can_claim_playlist_follower_threshold(params).await
+        }
+        CAN_CLAIM_N_SAVED_TRACKS => handle_can_claim_user_has_n_saved_tracks(params).await,
+        CAN_CLAIM_N_SAVED_ALBUMS => handle_can_claim_user_has_n_saved_albums(params).await,
+        CAN_CLAIM_REFERENCE_SEQUENCE => handle_can_claim_reference_sequence(params).await,
+        CAN_CLAIM_N_PLAYLISTS => handle_can_claim_user_has_n_playlists(params).await,
+        CAN_CLAIM_N_FOLLOWED_PODCASTS => {
+            handle_can_claim_user_has_n_followed_podcasts(params).await
+        }
+        CAN_CLAIM_ARTIST_N_FOLLOWERS => handle_can_claim_artist_has_n_followers(params).await,
+        CAN_CLAIM_TOP_TRACK_STABILITY => handle_can_claim_top_tracks_stability(params).await,
+        CAN_CLAIM_IS_PODCAST_EPISODE => handle_can_claim_track_is_podcast_episode(params).await,
+        CAN_CLAIM_EPISODE_IN_SHOW => handle_can_claim_episode_in_show(params).await,
+        CAN_CLAIM_SHOW_SAVED => handle_can_claim_show_in_saved_shows(params).await,
+        CAN_CLAIM_EPISODE_COMPLETED => {
+            handle_can_claim_episode_listened_to_completion(params).await
+        }
+        CAN_CLAIM_AUDIOBOOK_SAVED => handle_can_claim_audiobook_saved(params).await,
+        CAN_CLAIM_AUDIOBOOK_CHAPTER => handle_can_claim_audiobook_chapter_listened(params).await,
+        CAN_CLAIM_TRACK_N_MARKETS => handle_can_claim_track_available_in_n_markets(params).await,
+        CAN_CLAIM_ALBUM_N_MARKETS => handle_can_claim_album_available_in_n_markets(params).await,
+        CAN_CLAIM_ARTIST_MARKET_COUNT => handle_can_claim_artist_markets_count(params).await,
+        CAN_CLAIM_TRACK_NOT_RECENTLY_PLAYED => {
+            handle_can_claim_track_not_in_recently_played(params).await
+        }
+        CAN_CLAIM_ARTIST_NOT_RECENTLY_PLAYED => {
+            handle_can_claim_artist_not_recently_played(params).await
+        }
+        CAN_CLAIM_GENRE_NOT_IN_TOP => handle_can_claim_genre_not_in_top_artists(params).await,
+        CAN_CLAIM_PLAYLIST_RECENTLY_UPDATED => {
+            handle_can_claim_playlist_recently_updated(params).await
+        }
+        CAN_CLAIM_PLAYLIST_CREATED_BEFORE => handle_can_claim_playlist_created_before(params).await,
+        CAN_CLAIM_LIBRARY_AGE => handle_can_claim_first_saved_track_before(params).await,
+        CAN_CLAIM_DEVICE_LOCATION_HINT => handle_can_claim_listening_location(params).await,
+        CAN_CLAIM_ROYALTY_FREE => handle_can_claim_track_royalty_free(params).await,
+        CAN_CLAIM_TRACK_IS_COVER => handle_can_claim_track_covers_original(params).await,
+        CAN_CLAIM_SAME_RECORDING => handle_can_claim_track_same_recording(params).await,
+        CAN_CLAIM_TRACK_SAMPLING => handle_can_claim_track_sampling(params).await,
+        CAN_CLAIM_PLAYLIST_CO_CURATED => {
+            handle_can_claim_playlist_collaborative_with_user(params).await
+        }
+        CAN_CLAIM_TRACK_ADDED_BY => handle_can_claim_track_added_by_curator(params).await,
+        CAN_CLAIM_CURATOR_CONTRIBUTION_COUNT => handle_can_claim_curator_track_count(params).await,
+        CAN_CLAIM_PLAYLIST_CLEAN => handle_can_claim_playlist_has_no_explicit_tracks(params).await,
+        CAN_CLAIM_TOP_TRACKS_MONOARTIST => {
+            handle_can_claim_top_tracks_all_by_same_artist(params).await
+        }
+        CAN_CLAIM_TOP_TRACKS_TEMPORAL_DIVERSITY => {
+            handle_can_claim_top_tracks_span_multiple_decades(params).await
+        }
+        CAN_CLAIM_TOP_TRACKS_ALL_EXPLICIT => handle_can_claim_top_tracks_all_explicit(params).await,
+        CAN_CLAIM_TOP_TRACKS_DECADE => handle_can_claim_top_tracks_decade_count(params).await,
+        CAN_CLAIM_ARTIST_COLLAB_COUNT => handle_can_claim_artist_collaboration_count(params).await,
+        CAN_CLAIM_GENRE_ENTROPY => handle_can_claim_genre_entropy(params).await,
+        CAN_CLAIM_DISCOGRAPHY_COMPLETED => handle_can_claim_discography_completed(params).await,
+        CAN_CLAIM_LISTENING_EVOLVED => handle_can_claim_listening_evolved(params).await,
+        CAN_CLAIM_TRACK_REPEAT_COUNT => handle_can_claim_track_repeated_listening(params).await,
+        CAN_CLAIM_SESSION_TRACK_COUNT => handle_can_claim_multi_track_session(params).await,
+        CAN_CLAIM_MORNING_TOP_TRACK => handle_can_claim_track_in_morning_top(params).await,
+        CAN_CLAIM_WORKOUT_LISTENER => handle_can_claim_track_in_workout_context(params).await,
+        CAN_CLAIM_SHARED_TOP_TRACK => handle_can_claim_shared_top_track(params).await,
+        CAN_CLAIM_DISJOINT_ARTISTS => handle_can_claim_disjoint_top_artists(params).await,
+        CAN_CLAIM_TRACK_SPOTIFY_EXCLUSIVE => {
+            handle_can_claim_track_exclusive_to_spotify(params).await
+        }
+        CAN_CLAIM_PREVIEW_ACCESSIBLE => handle_can_claim_track_preview_url_accessible(params).await,
+        CAN_CLAIM_ARTIST_ON_TOUR => handle_can_claim_artist_tour_announced(params).await,
+        CAN_CLAIM_EVENT_LOCAL => handle_can_claim_event_in_user_city(params).await,
+        CAN_CLAIM_TRACK_IN_TV_SHOW => handle_can_claim_track_in_tv_show(params).await,
+        CAN_CLAIM_TRACK_HAS_MUSIC_VIDEO => handle_can_claim_track_has_music_video(params).await,
+        CAN_CLAIM_ARTIST_BIO_LENGTH => handle_can_claim_artist_biography_length(params).await,
+        _ => Err(Error::invalid_params("Invalid method")),
+    }
+}
+
+/// Decodes a claim's key input into the key and the account it selects, if any.
+///
+/// The key input holds the key's characters. To select an account bound with
+/// `store_key_account`, it instead holds two arrays: the key's characters
+/// first and the account name's second.
+fn decode_key(key: &[Value]) -> (String, Option<String>) {
+    let chars = |field: &Value| -> String {
+        field
+            .as_array()
+            .map(|field| field.iter().map(hex_to_char).collect())
+            .unwrap_or_default()
+    };
+    match key.first() {
+        Some(first @ Value::Array(_)) => {
+            let account = key.get(1).map(chars).filter(|account| !account.is_empty());
+            (chars(first), account)
+        }
+        _ => (key.iter().map(hex_to_char).collect(), None),
+    }
+}
+
+/// Fetches the token of a claim's key input; see [`decode_key`].
+fn key_token(key: &[Value]) -> RedisResult<String> {
+    match decode_key(key) {
+        (key, Some(account)) => get_account_token(key, account),
+        (key, None) => get_token(key),
+    }
+}
+
+/// Looks up the claim named by a request's `function` field.
+///
+/// Accepts the claim name, or its numeric registry id as a JSON number or as a
+/// decimal or `0x`-prefixed hex string.
+fn resolve_claim(function: &Value) -> Option<&'static Claim> {
+    match function {
+        Value::Number(id) => id
+            .as_u64()
+            .and_then(|id| u16::try_from(id).ok())
+            .and_then(claim_by_id),
+        Value::String(name) => claim_by_name(name).or_else(|| {
+            let id = match name.strip_prefix("0x") {
+                Some(hex) => u16::from_str_radix(hex, 16).ok(),
+                None => name.parse::<u16>().ok(),
+            };
+            id.and_then(claim_by_id)
+        }),
+        _ => None,
+    }
+}
+
+/// Adds `evaluated_at` and `valid_until` (Unix seconds) to a claim response,
+/// and its `signature` when signing is enabled (see [`crate::signing`]).
+///
+/// The validity window comes from the claim's registry entry, overridable with
+/// a `CLAIM_VALIDITY_SECS_<FUNCTION>` environment variable, e.g.
+/// `CLAIM_VALIDITY_SECS_CAN_CLAIM_TOP_TRACK=3600`. An override that is not a
+/// positive number of seconds is ignored, so an answer is never already
+/// expired when it is issued.
+fn stamp_validity(claim: &Claim, clock: &dyn Clock, mut result: Value) -> Result<Value, Error> {
+    let validity_secs = env::var(format!(
+        "CLAIM_VALIDITY_SECS_{}",
+        claim.name.to_ascii_uppercase()
+    ))
+    .ok()
+    .and_then(|secs| {
+        let parsed = secs.parse::<i64>().ok().filter(|secs| *secs > 0);
+        if parsed.is_none() {
+            leniency::note(Leniency::InvalidValidityOverride);
+        }
+        parsed
+    })
+    .unwrap_or(claim.validity_secs);
+
+    let evaluated_at = clock.now();
+    let signing_key = signing_key().map_err(Error::invalid_params)?;
+    if let Some(object) = result.as_object_mut() {
+        object.insert("evaluated_at".to_string(), json!(evaluated_at));
+        object.insert(
+            "valid_until".to_string(),
+            json!(evaluated_at.saturating_add(validity_secs)),
+        );
+    }
+    if let Some(key) = signing_key {
+        let signature = sign(&key, claim.name, &result);
+        if let Some(object) = result.as_object_mut() {
+            object.insert("signature".to_string(), json!(signature));
+        }
+    }
+    Ok(result)
+}
+
+/// Registers the oracle's methods.
+///
+/// `resolve_foreign_call`, `list_claims` and `health` are open to everyone;
+/// every other method is an admin method and needs the admin token (see
+/// [`crate::admin`]).
+pub fn create_io() -> IoHandler {
+    create_io_with_clock(Arc::new(SystemClock))
+}
+
+/// Registers the oracle's methods, stamping claim answers with `clock`'s time.
+pub fn create_io_with_clock(clock: Arc<dyn Clock>) -> IoHandler {
+    let mut io = IoHandler::default();
+    io.add_method("resolve_foreign_call", move |params: Params| {
+        let clock = clock.clone();
+        async move {
+            match params {
+                Params::Array(items) if items.len() == 1 => {
+                    let params = &items[0];
+
+                    if !params.is_object() {
+                        return Err(Error::invalid_params("Invalid params; expected an object"));
+                    }
+
+                    let function = params.get("function");
+                    if let Some(function) = function {
+                        let claim = resolve_claim(function)
+                            .ok_or_else(|| Error::invalid_params("Invalid method"))?;
+                        let strict = match params.get("strict") {
+                            None => false,
+                            Some(strict) => strict.as_bool().ok_or_else(|| {
+                                Error::invalid_params("'strict' must be a boolean")
+                            })?,
+                        };
+                        let policy = Arc::new(Policy::for_request(StrictMode::from_env(), strict)?);
+
+                        let evaluation = async {
+                            check_claim_market(claim.name, params).await?;
+                            let result = dispatch_claim(claim.name, params).await?;
+                            stamp_validity(claim, clock.as_ref(), result)
+                        };
+                        let evaluation = in_span(claim.name, SpanKind::Server, evaluation);
+                        let result = POLICY
+                            .scope(policy.clone(), CLAIM_FUNCTION.scope(claim.name, evaluation))
+                            .await;
+                        match policy.violation() {
+                            Some(error) => Err(error),
+                            None => result,
+                        }
+                    } else {
+                        Err(Error::invalid_params("Missing 'function' field"))
+                    }
+                }
+                _ => Err(Error::invalid_params(
+                    "Invalid params; expected a single-item array",
+                )),
+            }
+        }
+    });
+
+    io.add_method("store_key", |params: Params| async move {
+        require_admin()?;
+        // Parse the parameters into a tuple of two strings
+        let (id, token): (String, String) = params
+            .parse::<(String, String)>()
+            .map_err(|e| Error::invalid_params(e.message))?;
+        if id.is_empty() || token.is_empty() {
+            return Err(Error::invalid_params("ID or token cannot be empty"));
+        }
+        // Every reserved Redis prefix ends with the separator, so an ID containing
+        // it could overwrite other data.
+        if id.contains(KEY_PREFIX_SEPARATOR) {
+            return Err(Error::invalid_params(format!(
+                "ID cannot contain `{}`",
+                KEY_PREFIX_SEPARATOR
+            )));
+        }
+        store_key_and_token(id.clone(), token.clone())
+            .map_err(|e| Error::invalid_params(e.to_string()))?;
+
+        Ok(Value::String(id))
+    });
+
+    io.add_method("rotate_token", |params: Params| async move {
+        require_admin()?;
+        let (id, old_token_prefix, new_token): (String, String, String) = params
+            .parse::<(String, String, String)>()
+            .map_err(|e| Error::invalid_params(e.message))?;
+        if id.is_empty() || new_token.is_empty() {
+            return Err(Error::invalid_params("ID or token cannot be empty"));
+        }
+        if id.contains(KEY_PREFIX_SEPARATOR) {
+            return Err(Error::invalid_params(format!(
+                "ID cannot contain `{}`",
+                KEY_PREFIX_SEPARATOR
+            )));
+        }
+        if old_token_prefix.chars().count() < ROTATE_TOKEN_PREFIX_LEN {
+            return Err(Error::invalid_params(format!(
+                "Old token prefix must be at least the first {} characters of the token, without its scheme",
+                ROTATE_TOKEN_PREFIX_LEN
+            )));
+        }
+        match rotate_token(id.clone(), old_token_prefix, new_token)
+            .map_err(|e| Error::invalid_params(e.to_string()))?
+        {
+            TokenRotation::Rotated { expires_in } => Ok(json!({
+                "id": id,
+                "old_token_expires_in": expires_in,
+            })),
+            // Someone else rotated the token first; the caller should re-read it.
+            TokenRotation::PrefixMismatch => Err(Error::invalid_params(
+                "Old token prefix does not match the stored token",
+            )),
+            TokenRotation::UnknownKey => Err(Error::invalid_params("Unknown key")),
+        }
+    });
+
+    io.add_method("store_key_account", |params: Params| async move {
+        require_admin()?;
+        let (id, account, token): (String, String, String) = params
+            .parse::<(String, String, String)>()
+            .map_err(|e| Error::invalid_params(e.message))?;
+        if id.is_empty() || account.is_empty() || token.is_empty() {
+            return Err(Error::invalid_params(
+                "ID, account name or token cannot be empty",
+            ));
+        }
+        if id.contains(KEY_PREFIX_SEPARATOR) {
+            return Err(Error::invalid_params(format!(
+                "ID cannot contain `{}`",
+                KEY_PREFIX_SEPARATOR
+            )));
+        }
+        store_key_account(id.clone(), account, token)
+            .map_err(|e| Error::invalid_params(e.to_string()))?;
+
+        Ok(Value::String(id))
+    });
+
+    io.add_method("delete_key", |params: Params| async move {
+        require_admin()?;
+        // Parse the parameters into a tuple of two strings
+        let (id,): (String,) = params
+            .parse::<(String,)>()
+            .map_err(|e| Error::invalid_params(e.message))?;
+        if id.is_empty() {
+            return Err(Error::invalid_params("ID or token cannot be empty"));
+        }
+        // Deleting an unknown key succeeds too, so retries are safe.
+        delete_token(id.clone()).map_err(|e| Error::invalid_params(e.to_string()))?;
+
+        Ok(Value::String(id))
+    });
+
+    io.add_method("upsert_event", |params: Params| async move {
+        require_admin()?;
+        let (id, track_id, start, end, ttl_secs): (String, String, i64, i64, u64) = params
+            .parse::<(String, String, i64, i64, u64)>()
+            .map_err(|e| Error::invalid_params(e.message))?;
+        if id.is_empty() || track_id.is_empty() {
+            return Err(Error::invalid_params("ID or track ID cannot be empty"));
+        }
+        if end < start {
+            return Err(Error::invalid_params("Event window ends before it starts"));
+        }
+        if ttl_secs == 0 {
+            return Err(Error::invalid_params("TTL must be at least one second"));
+        }
+        let event = serde_json::to_string(&ListeningEvent {
+            track_id,
+            start,
+            end,
+        })
+        .map_err(|e| Error::invalid_params(e.to_string()))?;
+        store_event(id.clone(), event, ttl_secs)
+            .map_err(|e| Error::invalid_params(e.to_string()))?;
+
+        Ok(Value::String(id))
+    });
+
+    io.add_method("storage_usage", |_params: Params| async move {
+        require_admin()?;
+        let usage = storage_usage().map_err(|e| Error::invalid_params(e.to_string()))?;
+        let report: serde_json::Map<String, Value> = usage
+            .into_iter()
+            .map(|(feature, usage)| (feature.to_string(), json!(usage)))
+            .collect();
+
+        Ok(Value::Object(report))
+    });
+
+    io.add_method("provider_usage", |params: Params| async move {
+        require_admin()?;
+        let (from, to): (String, String) = params
+            .parse::<(String, String)>()
+            .map_err(|e| Error::invalid_params(e.message))?;
+        let from = NaiveDate::parse_from_str(&from, "%Y-%m-%d")
+            .map_err(|e| Error::invalid_params(format!("Invalid start date: {}", e)))?;
+        let to = NaiveDate::parse_from_str(&to, "%Y-%m-%d")
+            .map_err(|e| Error::invalid_params(format!("Invalid end date: {}", e)))?;
+        if to < from || (to - from).num_days() > MAX_USAGE_RANGE_DAYS {
+            return Err(Error::invalid_params(format!(
+                "Date range must be ordered and span at most {} days",
+                MAX_USAGE_RANGE_DAYS
+            )));
+        }
+
+        let records = usage_between(from, to).map_err(|e| Error::invalid_params(e.to_string()))?;
+        serde_json::to_value(records).map_err(|e| Error::invalid_params(e.to_string()))
+    });
+
+    io.add_method("recent_provider_errors", |params: Params| async move {
+        require_admin()?;
+        // An optional limit on the failures returned per provider.
+        let limit = match params {
+            Params::None => usize::MAX,
+            params => {
+                params
+                    .parse::<(usize,)>()
+                    .map_err(|e| Error::invalid_params(e.message))?
+                    .0
+            }
+        };
+        serde_json::to_value(recent_failures(limit))
+            .map_err(|e| Error::invalid_params(e.to_string()))
+    });
+
+    io.add_method("health", |_params: Params| async move {
+        serde_json::to_value(health()).map_err(|e| Error::invalid_params(e.to_string()))
+    });
+
+    io.add_method("list_claims", |_params: Params| async move {
+        serde_json::to_value(CLAIMS).map_err(|e| Error::invalid_params(e.to_string()))
+    });
+
+    io.add_method("store_reference_list", |params: Params| async move {
+        require_admin()?;
+        let (id, track_ids): (String, Vec<String>) = params
+            .parse::<(String, Vec<String>)>()
+            .map_err(|e| Error::invalid_params(e.message))?;
+        if id.is_empty() || track_ids.is_empty() {
+            return Err(Error::invalid_params(
+                "ID or reference list cannot be empty",
+            ));
+        }
+        if track_ids.len() > MAX_REFERENCE_LIST_LEN {
+            return Err(Error::invalid_params(format!(
+                "Reference list exceeds the maximum of {} IDs",
+                MAX_REFERENCE_LIST_LEN
+            )));
+        }
+        store_reference_list(id.clone(), track_ids)
+            .map_err(|e| Error::invalid_params(e.to_string()))?;
 
         Ok(Value::String(id))
     });
@@ -1,17 +1,30 @@
-use crate::middleware::logger::LoggerMiddleware;
+use crate::middleware::{
+    logger::LoggerMiddleware,
+    metrics::MetricsMiddleware,
+    strict_json::{StrictJsonMiddleware, MAX_REQUEST_BODY_SIZE},
+};
 use jsonrpc_core::IoHandler;
-use jsonrpc_http_server::{AccessControlAllowOrigin, DomainsValidation, ServerBuilder};
+use jsonrpc_http_server::{AccessControlAllowOrigin, DomainsValidation, Server, ServerBuilder};
+use std::net::SocketAddr;
 
-pub fn create_server(io: IoHandler) {
-    let server = ServerBuilder::new(io)
+/// Starts serving `io` on `addr` with the oracle's CORS and middleware setup.
+pub fn start_server(io: IoHandler, addr: &SocketAddr) -> std::io::Result<Server> {
+    ServerBuilder::new(io.clone())
         .cors(DomainsValidation::AllowOnly(vec![
             AccessControlAllowOrigin::Any,
             AccessControlAllowOrigin::Null,
         ]))
-        .request_middleware(LoggerMiddleware)
+        .max_request_body_size(MAX_REQUEST_BODY_SIZE)
+        .request_middleware(StrictJsonMiddleware::new(
+            io,
+            MetricsMiddleware::new(LoggerMiddleware),
+        ))
         .threads(10)
-        .start_http(&"0.0.0.0:3000".parse().unwrap())
-        .unwrap();
+        .start_http(addr)
+}
+
+pub fn create_server(io: IoHandler) {
+    let server = start_server(io, &"0.0.0.0:3000".parse().unwrap()).unwrap();
     println!("Server started...");
     server.wait();
 }
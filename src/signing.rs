@@ -0,0 +1,100 @@
+//! Signing of claim answers.
+//!
+//! When `ORACLE_SIGNING_KEY` is set (32 bytes as 64 hex digits), every claim
+//! answer carries a `signature` over its canonical payload, so a relayer can
+//! neither change the values nor stretch `valid_until`. The canonical payload
+//! is the answer object without `signature`, with the claim name added as
+//! `function`, serialized as compact JSON with sorted keys.
+//!
+//! The signature is an AES-256-GCM tag over the payload as associated data,
+//! with no plaintext and a fresh random nonce: the hex of nonce and tag.
+//! Verifiers holding the key check it with [`verify`].
+
+use aes_gcm::aead::{Aead, Generate, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use serde_json::Value;
+use std::env;
+
+use crate::redis::decode_hex;
+
+const NONCE_LEN: usize = 12;
+
+/// Parses an `ORACLE_SIGNING_KEY` value: 32 bytes as 64 hex digits.
+pub fn parse_signing_key(hex: &str) -> Option<[u8; 32]> {
+    decode_hex(hex.trim())?.try_into().ok()
+}
+
+/// Checks the signing settings once at startup; signing is off when unset.
+pub fn check_signing_config(signing_key: Option<&str>) -> Result<(), &'static str> {
+    match signing_key {
+        Some(hex) if parse_signing_key(hex).is_none() => {
+            Err("ORACLE_SIGNING_KEY must be 32 bytes of hex")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The key answers are signed with, if `ORACLE_SIGNING_KEY` is set.
+pub fn signing_key() -> Result<Option<[u8; 32]>, &'static str> {
+    match env::var("ORACLE_SIGNING_KEY") {
+        Ok(hex) => parse_signing_key(&hex)
+            .map(Some)
+            .ok_or("ORACLE_SIGNING_KEY must be 32 bytes of hex"),
+        Err(_) => Ok(None),
+    }
+}
+
+/// The bytes a signature of `function`'s `answer` covers.
+pub fn canonical_payload(function: &str, answer: &Value) -> Vec<u8> {
+    let mut payload = answer.as_object().cloned().unwrap_or_default();
+    payload.remove("signature");
+    payload.insert("function".to_string(), Value::from(function));
+    Value::Object(payload).to_string().into_bytes()
+}
+
+/// Signs `function`'s `answer`.
+pub fn sign(key: &[u8; 32], function: &str, answer: &Value) -> String {
+    let cipher = Aes256Gcm::new(&Key::<Aes256Gcm>::from(*key));
+    let nonce = Nonce::generate();
+    let payload = canonical_payload(function, answer);
+    let tag = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: &[],
+                aad: &payload,
+            },
+        )
+        .expect("AES-GCM over an empty plaintext cannot fail");
+
+    nonce
+        .iter()
+        .chain(&tag)
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Whether `answer` of `function` carries a valid signature under `key`.
+pub fn verify(key: &[u8; 32], function: &str, answer: &Value) -> bool {
+    let Some(bytes) = answer["signature"].as_str().and_then(decode_hex) else {
+        return false;
+    };
+    if bytes.len() <= NONCE_LEN {
+        return false;
+    }
+    let (nonce, tag) = bytes.split_at(NONCE_LEN);
+    let Ok(nonce) = Nonce::try_from(nonce) else {
+        return false;
+    };
+    let cipher = Aes256Gcm::new(&Key::<Aes256Gcm>::from(*key));
+    let payload = canonical_payload(function, answer);
+    cipher
+        .decrypt(
+            &nonce,
+            Payload {
+                msg: tag,
+                aad: &payload,
+            },
+        )
+        .is_ok()
+}
@@ -0,0 +1,261 @@
+//! Trace spans for claim evaluations and provider calls, exported over OTLP/HTTP.
+//!
+//! A request continues the trace named by its `traceparent` and `tracestate`
+//! headers (W3C Trace Context), or starts a new one. Every claim evaluation is a
+//! span named after the claim function, with a child span per Spotify call.
+//!
+//! Only sampled traces are recorded: an incoming `traceparent` whose sampled flag
+//! is clear is propagated as it is, without spans of its own.
+//!
+//! Finished spans are queued and posted in batches as OTLP JSON to
+//! `{OTEL_EXPORTER_OTLP_ENDPOINT}/v1/traces` by a background thread, every
+//! `OTEL_BSP_SCHEDULE_DELAY` milliseconds (default 5000), so span callers never
+//! wait on the collector. When no endpoint is configured nothing is recorded or
+//! sent.
+//!
+//! This is a small exporter of its own rather than the OpenTelemetry SDK, which
+//! the build cannot depend on yet; it follows the SDK's batch span processor
+//! defaults and environment variable so switching over later changes nothing
+//! for operators.
+
+use serde_json::{json, Value};
+use std::env;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::{LazyLock, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SERVICE_NAME: &str = "noir-oracles";
+
+/// Most spans waiting for export; spans finished while the queue is full are dropped.
+const MAX_QUEUE_SIZE: usize = 2048;
+
+/// Most spans sent in one export request.
+const MAX_EXPORT_BATCH_SIZE: usize = 512;
+
+/// Export interval when `OTEL_BSP_SCHEDULE_DELAY` is unset.
+const DEFAULT_SCHEDULE_DELAY: Duration = Duration::from_millis(5000);
+
+/// OTLP span kinds.
+#[derive(Clone, Copy, Debug)]
+pub enum SpanKind {
+    Server = 2,
+    Client = 3,
+}
+
+/// The trace a task belongs to and its current span.
+#[derive(Clone, Debug)]
+pub struct TraceContext {
+    /// 32 lowercase hex digits.
+    pub trace_id: String,
+    /// 16 lowercase hex digits; `None` before any span was started.
+    pub span_id: Option<String>,
+    pub tracestate: Option<String>,
+    /// Whether spans of this trace are recorded and exported.
+    pub sampled: bool,
+}
+
+tokio::task_local! {
+    /// The trace context of the request being served by the current task.
+    pub static TRACE: TraceContext;
+}
+
+static TRACES_ENDPOINT: LazyLock<Option<String>> = LazyLock::new(|| {
+    env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+        .ok()
+        .filter(|endpoint| !endpoint.is_empty())
+        .map(|endpoint| format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+});
+
+/// Spans waiting for the exporter thread, which is started with the queue.
+static QUEUE: LazyLock<Mutex<Vec<Value>>> = LazyLock::new(|| {
+    thread::spawn(run_exporter);
+    Mutex::new(Vec::new())
+});
+
+impl TraceContext {
+    /// Continues the trace of a valid `traceparent`, or starts a new trace.
+    pub fn from_headers(traceparent: Option<&str>, tracestate: Option<&str>) -> Self {
+        match traceparent.and_then(parse_traceparent) {
+            Some((trace_id, span_id, sampled)) => TraceContext {
+                trace_id,
+                span_id: Some(span_id),
+                tracestate: tracestate.map(str::to_string),
+                sampled,
+            },
+            None => TraceContext {
+                trace_id: random_hex(16),
+                span_id: None,
+                tracestate: None,
+                sampled: true,
+            },
+        }
+    }
+
+    /// The `traceparent` header naming the current span, if there is one.
+    pub fn traceparent(&self) -> Option<String> {
+        let span_id = self.span_id.as_ref()?;
+        let flags = if self.sampled { "01" } else { "00" };
+        Some(format!("00-{}-{}-{}", self.trace_id, span_id, flags))
+    }
+}
+
+/// The `traceparent` to send with outgoing requests, when spans are exported.
+pub fn current_traceparent() -> Option<String> {
+    TRACES_ENDPOINT.as_ref()?;
+    TRACE.try_with(TraceContext::traceparent).ok().flatten()
+}
+
+/// Splits a version 00 `traceparent` into its trace and parent span IDs and
+/// its sampled flag.
+fn parse_traceparent(header: &str) -> Option<(String, String, bool)> {
+    let mut fields = header.trim().split('-');
+    let (version, trace_id, span_id, flags) = (
+        fields.next()?,
+        fields.next()?,
+        fields.next()?,
+        fields.next()?,
+    );
+    let is_hex = |field: &str, len: usize| {
+        field.len() == len
+            && field
+                .bytes()
+                .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+    };
+    let all_zero = |field: &str| field.bytes().all(|b| b == b'0');
+    if version != "00"
+        || fields.next().is_some()
+        || !is_hex(trace_id, 32)
+        || !is_hex(span_id, 16)
+        || !is_hex(flags, 2)
+        || all_zero(trace_id)
+        || all_zero(span_id)
+    {
+        return None;
+    }
+    let sampled = u8::from_str_radix(flags, 16).ok()? & 1 == 1;
+    Some((trace_id.to_string(), span_id.to_string(), sampled))
+}
+
+fn random_hex(bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    // Without OS randomness, fall back to the clock; IDs need not be secret.
+    if getrandom::fill(&mut buf).is_err() {
+        let nanos = unix_nanos().to_le_bytes();
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte = nanos[i % nanos.len()] ^ (i as u8);
+        }
+    }
+    buf.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unix_nanos() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default()
+}
+
+/// Runs `future` as a span named `name`, a child of the current span.
+///
+/// An `Err` result marks the span as failed. Without a configured endpoint, or
+/// in a trace that is not sampled, this just awaits `future`.
+pub async fn in_span<F, T, E>(name: &str, kind: SpanKind, future: F) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>>,
+    E: Display,
+{
+    if TRACES_ENDPOINT.is_none() {
+        return future.await;
+    }
+
+    let parent = TRACE
+        .try_with(TraceContext::clone)
+        .unwrap_or_else(|_| TraceContext::from_headers(None, None));
+    if !parent.sampled {
+        return future.await;
+    }
+    let context = TraceContext {
+        span_id: Some(random_hex(8)),
+        ..parent.clone()
+    };
+
+    let start = unix_nanos();
+    let result = TRACE.scope(context.clone(), future).await;
+    let end = unix_nanos();
+
+    let status = match &result {
+        Ok(_) => json!({"code": 1}),
+        Err(e) => json!({"code": 2, "message": e.to_string()}),
+    };
+    let span = json!({
+        "traceId": context.trace_id,
+        "spanId": context.span_id,
+        "parentSpanId": parent.span_id.unwrap_or_default(),
+        "traceState": parent.tracestate.unwrap_or_default(),
+        "name": name,
+        "kind": kind as u8,
+        "startTimeUnixNano": start.to_string(),
+        "endTimeUnixNano": end.to_string(),
+        "status": status,
+    });
+    enqueue(span);
+    result
+}
+
+fn enqueue(span: Value) {
+    let mut queue = QUEUE.lock().unwrap_or_else(|e| e.into_inner());
+    if queue.len() < MAX_QUEUE_SIZE {
+        queue.push(span);
+    }
+}
+
+fn schedule_delay() -> Duration {
+    env::var("OTEL_BSP_SCHEDULE_DELAY")
+        .ok()
+        .and_then(|millis| millis.trim().parse().ok())
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_SCHEDULE_DELAY)
+}
+
+/// Posts the queued spans every schedule delay, in batches of at most
+/// `MAX_EXPORT_BATCH_SIZE`. A batch the collector does not accept is dropped.
+fn run_exporter() {
+    let Some(endpoint) = TRACES_ENDPOINT.as_ref() else {
+        return;
+    };
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start the trace exporter: {}", e);
+            return;
+        }
+    };
+    let client = reqwest::Client::new();
+    let delay = schedule_delay();
+    loop {
+        thread::sleep(delay);
+        let spans = std::mem::take(&mut *QUEUE.lock().unwrap_or_else(|e| e.into_inner()));
+        for batch in spans.chunks(MAX_EXPORT_BATCH_SIZE) {
+            let sent = runtime.block_on(client.post(endpoint).json(&payload(batch)).send());
+            if let Err(e) = sent.and_then(reqwest::Response::error_for_status) {
+                eprintln!("Failed to export {} trace spans: {}", batch.len(), e);
+            }
+        }
+    }
+}
+
+fn payload(spans: &[Value]) -> Value {
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [{"key": "service.name", "value": {"stringValue": SERVICE_NAME}}],
+            },
+            "scopeSpans": [{"scope": {"name": SERVICE_NAME}, "spans": spans}],
+        }],
+    })
+}
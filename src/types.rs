@@ -1,14 +1,277 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 pub const CAN_CLAIM_TOP_TRACKS: &str = "can_claim_top_track";
 pub const CAN_CLAIM_TOP_ARTISTS: &str = "can_claim_top_artist";
 pub const CAN_CLAIM_RECENTLY_PLAYED_TRACK: &str = "can_claim_recently_played_track";
+pub const CAN_CLAIM_LIBRARY_OVERLAP: &str = "can_claim_library_overlap_with_reference_threshold";
+pub const CAN_CLAIM_TRACK_SEGMENTS_COUNT: &str = "can_claim_track_analysis_segments_count";
+pub const CAN_CLAIM_TRACK_BEATS_COUNT: &str = "can_claim_track_analysis_beats_count";
+pub const CAN_CLAIM_TRACK_BARS_COUNT: &str = "can_claim_track_analysis_bars_count";
+pub const CAN_CLAIM_TRACK_TATUMS_COUNT: &str = "can_claim_track_analysis_tatums_count";
+pub const CAN_CLAIM_TRACK_FADE_OUT: &str = "can_claim_track_analysis_start_of_fade_out";
+pub const CAN_CLAIM_TRACK_FADE_IN: &str = "can_claim_track_analysis_end_of_fade_in";
+pub const CAN_CLAIM_TRACK_ANALYSIS_SUMMARY: &str = "can_claim_track_analysis_summary";
+pub const CAN_CLAIM_RECENT_PLAY_ON_RELEASE_DAY: &str = "can_claim_recent_play_on_release_day";
+pub const CAN_CLAIM_ALBUM_PLAYTHROUGH: &str = "can_claim_multi_track_ordered_playthrough";
+pub const CAN_CLAIM_SAVED_SHOW_COUNT: &str = "can_claim_saved_show_count_threshold";
+pub const CAN_CLAIM_DISTINCT_ALBUMS_IN_HISTORY: &str =
+    "can_claim_distinct_albums_in_history_threshold";
+pub const CAN_CLAIM_PLAYED_DURING_EVENT: &str = "can_claim_played_during_event";
+pub const CAN_CLAIM_TRACK_ALL_RANGES: &str = "can_claim_track_in_all_top_ranges";
+pub const CAN_CLAIM_ARTIST_ALL_RANGES: &str = "can_claim_artist_in_all_top_ranges";
+pub const CAN_CLAIM_ARTIST_TOP_TRACK_IN_HISTORY: &str =
+    "can_claim_artist_top_track_in_user_history";
+pub const CAN_CLAIM_N_TRACKS_TOTAL: &str = "can_claim_user_has_listened_to_n_tracks_total";
+pub const CAN_CLAIM_N_ARTISTS_TOTAL: &str = "can_claim_user_has_listened_to_n_artists_total";
+pub const CAN_CLAIM_CONSECUTIVE_ARTIST_PLAYS: &str = "can_claim_consecutive_same_artist_plays";
+pub const CAN_CLAIM_TRACK_PLAYLIST_COUNT: &str = "can_claim_track_on_n_user_playlists";
+pub const CAN_CLAIM_FOLLOWS_N_ARTISTS: &str = "can_claim_user_follows_n_artists";
+pub const CAN_CLAIM_PLAYLIST_FOLLOWER_THRESHOLD: &str = "can_claim_playlist_follower_threshold";
+pub const CAN_CLAIM_N_SAVED_TRACKS: &str = "can_claim_user_has_n_saved_tracks";
+pub const CAN_CLAIM_N_SAVED_ALBUMS: &str = "can_claim_user_has_n_saved_albums";
+pub const CAN_CLAIM_REFERENCE_SEQUENCE: &str = "can_claim_history_contains_full_reference_sequence";
+pub const CAN_CLAIM_N_PLAYLISTS: &str = "can_claim_user_has_n_playlists";
+pub const CAN_CLAIM_N_FOLLOWED_PODCASTS: &str = "can_claim_user_has_n_followed_podcasts";
+/// Takes three inputs (key, artist ID, threshold); prefer it to four-input
+/// variants in new circuits.
+pub const CAN_CLAIM_ARTIST_N_FOLLOWERS: &str = "can_claim_artist_has_n_followers";
+pub const CAN_CLAIM_TOP_TRACK_STABILITY: &str = "can_claim_top_tracks_stability";
+pub const CAN_CLAIM_IS_PODCAST_EPISODE: &str = "can_claim_track_is_podcast_episode";
+pub const CAN_CLAIM_EPISODE_IN_SHOW: &str = "can_claim_episode_in_show";
+pub const CAN_CLAIM_SHOW_SAVED: &str = "can_claim_show_in_saved_shows";
+pub const CAN_CLAIM_EPISODE_COMPLETED: &str = "can_claim_episode_listened_to_completion";
+pub const CAN_CLAIM_AUDIOBOOK_SAVED: &str = "can_claim_audiobook_saved";
+pub const CAN_CLAIM_AUDIOBOOK_CHAPTER: &str = "can_claim_audiobook_chapter_listened";
+pub const CAN_CLAIM_TRACK_N_MARKETS: &str = "can_claim_track_available_in_n_markets";
+pub const CAN_CLAIM_ALBUM_N_MARKETS: &str = "can_claim_album_available_in_n_markets";
+pub const CAN_CLAIM_ARTIST_MARKET_COUNT: &str = "can_claim_artist_markets_count";
+pub const CAN_CLAIM_TRACK_NOT_RECENTLY_PLAYED: &str = "can_claim_track_not_in_recently_played";
+pub const CAN_CLAIM_ARTIST_NOT_RECENTLY_PLAYED: &str = "can_claim_artist_not_recently_played";
+pub const CAN_CLAIM_GENRE_NOT_IN_TOP: &str = "can_claim_genre_not_in_top_artists";
+pub const CAN_CLAIM_PLAYLIST_RECENTLY_UPDATED: &str = "can_claim_playlist_recently_updated";
+pub const CAN_CLAIM_PLAYLIST_CREATED_BEFORE: &str = "can_claim_playlist_created_before";
+pub const CAN_CLAIM_LIBRARY_AGE: &str = "can_claim_first_saved_track_before";
+pub const CAN_CLAIM_DEVICE_LOCATION_HINT: &str = "can_claim_listening_location";
+pub const CAN_CLAIM_ROYALTY_FREE: &str = "can_claim_track_royalty_free";
+pub const CAN_CLAIM_TRACK_IS_COVER: &str = "can_claim_track_covers_original";
+pub const CAN_CLAIM_SAME_RECORDING: &str = "can_claim_track_same_recording";
+pub const CAN_CLAIM_TRACK_SAMPLING: &str = "can_claim_track_sampling";
+pub const CAN_CLAIM_PLAYLIST_CO_CURATED: &str = "can_claim_playlist_collaborative_with_user";
+pub const CAN_CLAIM_TRACK_ADDED_BY: &str = "can_claim_track_added_by_curator";
+pub const CAN_CLAIM_CURATOR_CONTRIBUTION_COUNT: &str = "can_claim_curator_track_count";
+pub const CAN_CLAIM_PLAYLIST_CLEAN: &str = "can_claim_playlist_has_no_explicit_tracks";
+pub const CAN_CLAIM_TOP_TRACKS_MONOARTIST: &str = "can_claim_top_tracks_all_by_same_artist";
+pub const CAN_CLAIM_TOP_TRACKS_TEMPORAL_DIVERSITY: &str =
+    "can_claim_top_tracks_span_multiple_decades";
+pub const CAN_CLAIM_TOP_TRACKS_ALL_EXPLICIT: &str = "can_claim_top_tracks_all_explicit";
+pub const CAN_CLAIM_TOP_TRACKS_DECADE: &str = "can_claim_top_tracks_decade_count";
+pub const CAN_CLAIM_ARTIST_COLLAB_COUNT: &str = "can_claim_artist_collaboration_count";
+pub const CAN_CLAIM_GENRE_ENTROPY: &str = "can_claim_genre_entropy";
+pub const CAN_CLAIM_DISCOGRAPHY_COMPLETED: &str = "can_claim_discography_completed";
+pub const CAN_CLAIM_LISTENING_EVOLVED: &str = "can_claim_listening_evolved";
+pub const CAN_CLAIM_TRACK_REPEAT_COUNT: &str = "can_claim_track_repeated_listening";
+pub const CAN_CLAIM_SESSION_TRACK_COUNT: &str = "can_claim_multi_track_session";
+pub const CAN_CLAIM_MORNING_TOP_TRACK: &str = "can_claim_track_in_morning_top";
+pub const CAN_CLAIM_WORKOUT_LISTENER: &str = "can_claim_track_in_workout_context";
+pub const CAN_CLAIM_SHARED_TOP_TRACK: &str = "can_claim_shared_top_track";
+pub const CAN_CLAIM_DISJOINT_ARTISTS: &str = "can_claim_disjoint_top_artists";
+pub const CAN_CLAIM_TRACK_SPOTIFY_EXCLUSIVE: &str = "can_claim_track_exclusive_to_spotify";
+pub const CAN_CLAIM_PREVIEW_ACCESSIBLE: &str = "can_claim_track_preview_url_accessible";
+pub const CAN_CLAIM_ARTIST_ON_TOUR: &str = "can_claim_artist_tour_announced";
+pub const CAN_CLAIM_EVENT_LOCAL: &str = "can_claim_event_in_user_city";
+pub const CAN_CLAIM_TRACK_IN_TV_SHOW: &str = "can_claim_track_in_tv_show";
+pub const CAN_CLAIM_TRACK_HAS_MUSIC_VIDEO: &str = "can_claim_track_has_music_video";
+pub const CAN_CLAIM_ARTIST_BIO_LENGTH: &str = "can_claim_artist_biography_length";
+
+const MINUTE: i64 = 60;
+const HOUR: i64 = 60 * MINUTE;
+const DAY: i64 = 24 * HOUR;
+
+/// Registry metadata for a claim.
+#[derive(Serialize, Debug)]
+pub struct Claim {
+    /// Stable numeric identifier, accepted on the wire in place of `name`.
+    pub id: u16,
+    pub name: &'static str,
+    pub version: u8,
+    pub deprecated: bool,
+    /// How long an answer stays valid, in seconds, unless overridden by config.
+    pub validity_secs: i64,
+}
+
+const fn claim(id: u16, name: &'static str, validity_secs: i64) -> Claim {
+    Claim {
+        id,
+        name,
+        version: 1,
+        deprecated: false,
+        validity_secs,
+    }
+}
+
+/// Every claim the oracle resolves.
+///
+/// IDs are part of the wire format and baked into deployed circuits: assign the
+/// next unused id to a new claim, and never renumber or reuse one, even after a
+/// claim is deprecated. Answers derived from stable catalog data stay valid much
+/// longer than answers about the user's recent listening.
+pub const CLAIMS: &[Claim] = &[
+    claim(1, CAN_CLAIM_TOP_TRACKS, DAY),
+    claim(2, CAN_CLAIM_TOP_ARTISTS, DAY),
+    claim(3, CAN_CLAIM_RECENTLY_PLAYED_TRACK, HOUR),
+    claim(4, CAN_CLAIM_LIBRARY_OVERLAP, HOUR),
+    claim(5, CAN_CLAIM_TRACK_SEGMENTS_COUNT, 7 * DAY),
+    claim(6, CAN_CLAIM_TRACK_BEATS_COUNT, 7 * DAY),
+    claim(7, CAN_CLAIM_RECENT_PLAY_ON_RELEASE_DAY, DAY),
+    claim(8, CAN_CLAIM_TRACK_BARS_COUNT, 7 * DAY),
+    claim(9, CAN_CLAIM_TRACK_TATUMS_COUNT, 7 * DAY),
+    claim(10, CAN_CLAIM_TRACK_ANALYSIS_SUMMARY, 7 * DAY),
+    claim(11, CAN_CLAIM_ALBUM_PLAYTHROUGH, HOUR),
+    claim(12, CAN_CLAIM_TRACK_FADE_OUT, 7 * DAY),
+    claim(13, CAN_CLAIM_TRACK_FADE_IN, 7 * DAY),
+    claim(14, CAN_CLAIM_SAVED_SHOW_COUNT, DAY),
+    claim(15, CAN_CLAIM_DISTINCT_ALBUMS_IN_HISTORY, HOUR),
+    claim(16, CAN_CLAIM_PLAYED_DURING_EVENT, HOUR),
+    claim(17, CAN_CLAIM_TRACK_ALL_RANGES, DAY),
+    claim(18, CAN_CLAIM_ARTIST_ALL_RANGES, DAY),
+    claim(19, CAN_CLAIM_ARTIST_TOP_TRACK_IN_HISTORY, HOUR),
+    claim(20, CAN_CLAIM_N_TRACKS_TOTAL, HOUR),
+    claim(21, CAN_CLAIM_N_ARTISTS_TOTAL, HOUR),
+    claim(22, CAN_CLAIM_CONSECUTIVE_ARTIST_PLAYS, HOUR),
+    claim(23, CAN_CLAIM_TRACK_PLAYLIST_COUNT, HOUR),
+    claim(24, CAN_CLAIM_FOLLOWS_N_ARTISTS, DAY),
+    claim(25, CAN_CLAIM_PLAYLIST_FOLLOWER_THRESHOLD, HOUR),
+    claim(26, CAN_CLAIM_N_SAVED_TRACKS, DAY),
+    claim(27, CAN_CLAIM_N_SAVED_ALBUMS, DAY),
+    claim(28, CAN_CLAIM_REFERENCE_SEQUENCE, HOUR),
+    claim(29, CAN_CLAIM_N_PLAYLISTS, DAY),
+    claim(30, CAN_CLAIM_N_FOLLOWED_PODCASTS, DAY),
+    claim(31, CAN_CLAIM_ARTIST_N_FOLLOWERS, DAY),
+    claim(32, CAN_CLAIM_TOP_TRACK_STABILITY, DAY),
+    claim(33, CAN_CLAIM_IS_PODCAST_EPISODE, 7 * DAY),
+    claim(34, CAN_CLAIM_EPISODE_IN_SHOW, 7 * DAY),
+    claim(35, CAN_CLAIM_SHOW_SAVED, DAY),
+    claim(36, CAN_CLAIM_EPISODE_COMPLETED, HOUR),
+    claim(37, CAN_CLAIM_AUDIOBOOK_SAVED, DAY),
+    claim(38, CAN_CLAIM_AUDIOBOOK_CHAPTER, HOUR),
+    claim(39, CAN_CLAIM_TRACK_N_MARKETS, DAY),
+    claim(40, CAN_CLAIM_ALBUM_N_MARKETS, DAY),
+    claim(41, CAN_CLAIM_ARTIST_MARKET_COUNT, DAY),
+    claim(42, CAN_CLAIM_TRACK_NOT_RECENTLY_PLAYED, HOUR),
+    claim(43, CAN_CLAIM_ARTIST_NOT_RECENTLY_PLAYED, HOUR),
+    claim(44, CAN_CLAIM_GENRE_NOT_IN_TOP, DAY),
+    claim(45, CAN_CLAIM_PLAYLIST_RECENTLY_UPDATED, HOUR),
+    claim(46, CAN_CLAIM_PLAYLIST_CREATED_BEFORE, DAY),
+    claim(47, CAN_CLAIM_LIBRARY_AGE, DAY),
+    claim(48, CAN_CLAIM_DEVICE_LOCATION_HINT, HOUR),
+    claim(49, CAN_CLAIM_ROYALTY_FREE, DAY),
+    claim(50, CAN_CLAIM_TRACK_IS_COVER, 7 * DAY),
+    claim(51, CAN_CLAIM_SAME_RECORDING, 7 * DAY),
+    claim(52, CAN_CLAIM_TRACK_SAMPLING, DAY),
+    claim(53, CAN_CLAIM_PLAYLIST_CO_CURATED, HOUR),
+    claim(54, CAN_CLAIM_TRACK_ADDED_BY, HOUR),
+    claim(55, CAN_CLAIM_CURATOR_CONTRIBUTION_COUNT, HOUR),
+    claim(56, CAN_CLAIM_PLAYLIST_CLEAN, HOUR),
+    claim(57, CAN_CLAIM_TOP_TRACKS_MONOARTIST, DAY),
+    claim(58, CAN_CLAIM_TOP_TRACKS_TEMPORAL_DIVERSITY, DAY),
+    claim(59, CAN_CLAIM_TOP_TRACKS_ALL_EXPLICIT, DAY),
+    claim(60, CAN_CLAIM_TOP_TRACKS_DECADE, DAY),
+    claim(61, CAN_CLAIM_ARTIST_COLLAB_COUNT, 7 * DAY),
+    claim(62, CAN_CLAIM_GENRE_ENTROPY, DAY),
+    claim(63, CAN_CLAIM_DISCOGRAPHY_COMPLETED, DAY),
+    claim(64, CAN_CLAIM_LISTENING_EVOLVED, DAY),
+    claim(65, CAN_CLAIM_TRACK_REPEAT_COUNT, HOUR),
+    claim(66, CAN_CLAIM_SESSION_TRACK_COUNT, HOUR),
+    claim(67, CAN_CLAIM_MORNING_TOP_TRACK, HOUR),
+    claim(68, CAN_CLAIM_WORKOUT_LISTENER, HOUR),
+    claim(69, CAN_CLAIM_SHARED_TOP_TRACK, DAY),
+    claim(70, CAN_CLAIM_DISJOINT_ARTISTS, DAY),
+    claim(71, CAN_CLAIM_TRACK_SPOTIFY_EXCLUSIVE, 7 * DAY),
+    claim(72, CAN_CLAIM_PREVIEW_ACCESSIBLE, DAY),
+    claim(73, CAN_CLAIM_ARTIST_ON_TOUR, DAY),
+    claim(74, CAN_CLAIM_EVENT_LOCAL, DAY),
+    claim(75, CAN_CLAIM_TRACK_IN_TV_SHOW, 7 * DAY),
+    claim(76, CAN_CLAIM_TRACK_HAS_MUSIC_VIDEO, 7 * DAY),
+    claim(77, CAN_CLAIM_ARTIST_BIO_LENGTH, 7 * DAY),
+];
+
+pub fn claim_by_name(name: &str) -> Option<&'static Claim> {
+    CLAIMS.iter().find(|claim| claim.name == name)
+}
+
+pub fn claim_by_id(id: u16) -> Option<&'static Claim> {
+    CLAIMS.iter().find(|claim| claim.id == id)
+}
+
+/// Default number of top tracks considered in each window of the stability claim.
+pub const DEFAULT_STABILITY_LIST_RANGE: u8 = 50;
+
+/// Default number of users, the claimant included, a shared top track claim may name.
+pub const DEFAULT_MAX_SHARED_PROOF_USERS: usize = 5;
+
+/// Default longest pause, in minutes, between two tracks of an album playthrough.
+pub const DEFAULT_PLAYTHROUGH_MAX_GAP_MINUTES: u8 = 30;
+
+/// Redis key prefix for reference lists stored through `store_reference_list`.
+pub const REFERENCE_LIST_KEY_PREFIX: &str = "reference_list:";
+/// Claim inputs starting with this prefix name a stored reference list instead of
+/// carrying an inline, comma-separated list of IDs.
+pub const STORED_REFERENCE_LIST_PREFIX: &str = "list:";
+/// Upper bound on the number of IDs in a reference list.
+pub const MAX_REFERENCE_LIST_LEN: usize = 500;
+
+/// Upper bound on the number of tracks in an ordered reference sequence.
+pub const MAX_REFERENCE_SEQUENCE_LEN: usize = 50;
+
+/// Least number of leading characters of the raw token, without its `Bearer `
+/// scheme, that `rotate_token` compares before swapping.
+pub const ROTATE_TOKEN_PREFIX_LEN: usize = 8;
+
+/// Redis key prefix for the hashes of Spotify accounts bound through `store_key_account`.
+pub const ACCOUNTS_KEY_PREFIX: &str = "accounts:";
+/// Ends every reserved Redis key prefix, so key IDs may not contain it.
+pub const KEY_PREFIX_SEPARATOR: char = ':';
+
+/// Redis key prefix for listening events stored through `upsert_event`.
+pub const EVENT_KEY_PREFIX: &str = "event:";
+
+/// Redis key prefix for the last seen `snapshot_id` of each playlist.
+pub const PLAYLIST_SNAPSHOT_KEY_PREFIX: &str = "playlist_snapshot:";
+/// How long a playlist snapshot is kept after it was last seen, in seconds.
+pub const PLAYLIST_SNAPSHOT_RETENTION_SECS: u64 = 90 * 24 * 60 * 60;
+
+/// Redis key prefix for the daily buckets of provider call counts.
+pub const PROVIDER_USAGE_KEY_PREFIX: &str = "provider_usage:";
+/// How long daily provider call buckets are kept, in seconds.
+pub const PROVIDER_USAGE_RETENTION_SECS: u64 = 400 * 24 * 60 * 60;
+
+/// Number of keys and approximate bytes a feature occupies in Redis.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct KeyUsage {
+    pub keys: u64,
+    /// Extrapolated from the `sampled` keys that were sized.
+    pub bytes: u64,
+    pub sampled: u64,
+}
+
+/// A synced listening event: the track to play and the window it is played in.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListeningEvent {
+    pub track_id: String,
+    /// Start of the window, as a Unix timestamp in milliseconds.
+    pub start: i64,
+    /// End of the window, as a Unix timestamp in milliseconds.
+    pub end: i64,
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RecentlyPlayed {
     href: String,             // A link to the full result
-    limit: u32,               // Maximum number of items in the response
-    next: Option<String>,     // URL to the next page of items
+    pub limit: u32,           // Maximum number of items in the response
+    pub next: Option<String>, // URL to the next page of items
     cursors: Option<Cursors>, // Cursors for pagination
     pub items: Vec<PlayedTrack>,
 }
@@ -21,17 +284,25 @@ pub struct Cursors {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PlayedTrack {
-    played_at: String,
-    context: Option<TrackContext>,
+    pub played_at: DateTime<Utc>,
+    pub context: Option<TrackContext>,
     pub track: Track,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct TrackContext {
-    r#type: String,       // The type of context (e.g., "artist", "playlist")
+    pub r#type: String,   // The type of context (e.g., "artist", "playlist")
     href: Option<String>, // Link to full details of the context
     external_urls: Option<ExternalUrls>, // External URLs for the context
-    uri: Option<String>,  // Spotify URI for the context
+    pub uri: Option<String>, // Spotify URI for the context
+}
+
+impl TrackContext {
+    /// The ID of the playlist a track was played from, parsed from a
+    /// `spotify:playlist:<id>` URI.
+    pub fn playlist_id(&self) -> Option<&str> {
+        self.uri.as_deref()?.strip_prefix("spotify:playlist:")
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -56,9 +327,194 @@ pub struct TracksStatsResponse {
     pub items: Vec<Track>,
 }
 
+/// Spotify's offset-based paging object.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Paging<T> {
+    pub limit: u32,
+    pub offset: u32,
+    pub total: u32,
+    pub next: Option<String>,
+    pub items: Vec<T>,
+}
+
+/// Spotify's cursor-based paging object.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CursorPaging<T> {
+    pub limit: u32,
+    pub next: Option<String>,
+    pub items: Vec<T>,
+}
+
+/// Response of `GET /v1/me/following?type=artist`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FollowedArtists {
+    pub artists: CursorPaging<Artist>,
+}
+
+/// Just the `total` of a paging object, for endpoints queried with `limit=1`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PagingTotal {
+    pub total: u32,
+}
+
+/// A simplified track as listed by `GET /v1/albums/{id}/tracks`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AlbumTrack {
+    pub id: String,
+    pub name: String,
+    pub disc_number: u32,
+    pub track_number: u32,
+}
+
+/// An album as listed by `GET /v1/artists/{id}/albums`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ArtistAlbum {
+    pub id: String,
+}
+
+/// A track of `GET /v1/albums/{id}/tracks` reduced to its credited artists.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AlbumTrackCredits {
+    pub artists: Vec<CreditedArtist>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreditedArtist {
+    pub id: String,
+}
+
+/// A playlist as listed by `GET /v1/me/playlists`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SimplifiedPlaylist {
+    pub id: String,
+    pub name: String,
+}
+
+/// An entry of a playlist; `track` is null for unavailable items, and `id` is
+/// null for local files.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlaylistItem {
+    pub track: Option<PlaylistItemTrack>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlaylistItemTrack {
+    pub id: Option<String>,
+}
+
+/// A page of playlist items or saved tracks, reduced to when each was added.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AddedAtPage {
+    pub total: u32,
+    pub items: Vec<AddedAt>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AddedAt {
+    /// Null for playlist items added before Spotify recorded it.
+    pub added_at: Option<DateTime<Utc>>,
+}
+
+/// Response of `GET /v1/playlists/{id}?fields=followers.total,owner.id`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlaylistFollowers {
+    pub followers: Followers,
+    pub owner: PlaylistOwner,
+}
+
+/// Response of `GET /v1/playlists/{id}?fields=collaborative`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlaylistCollaborative {
+    pub collaborative: bool,
+}
+
+/// A playlist item reduced to who added it, and optionally what.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlaylistItemAddedBy {
+    /// Null for items added before Spotify recorded it.
+    pub added_by: Option<PlaylistAdder>,
+    /// Only present when requested through `fields`.
+    pub track: Option<PlaylistItemTrack>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlaylistAdder {
+    pub id: String,
+}
+
+/// A playlist item reduced to whether it is explicit.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlaylistItemExplicit {
+    /// Null for unavailable items.
+    pub track: Option<ExplicitFlag>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExplicitFlag {
+    pub explicit: bool,
+}
+
+/// Response of `GET /v1/playlists/{id}?fields=name`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlaylistName {
+    pub name: String,
+}
+
+/// Response of `GET /v1/playlists/{id}?fields=snapshot_id`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlaylistSnapshotId {
+    pub snapshot_id: String,
+}
+
+/// What the oracle has observed of a playlist's `snapshot_id`, which changes on
+/// every edit. Times are Unix seconds.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PlaylistSnapshot {
+    pub snapshot_id: String,
+    /// When `snapshot_id` was first observed.
+    pub first_seen: i64,
+    /// When `snapshot_id` was last observed.
+    pub last_seen: i64,
+    /// When the previous snapshot was last observed, if one was: the playlist
+    /// changed between this time and `first_seen`.
+    pub changed_after: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Followers {
+    /// Null for some playlists, e.g. ones Spotify generates.
+    pub total: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlaylistOwner {
+    pub id: String,
+}
+
+/// Response of `GET /v1/me/player/devices`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Devices {
+    pub devices: Vec<Device>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Device {
+    pub id: Option<String>,
+    /// Chosen by the user, e.g. "Kitchen speaker".
+    pub name: String,
+}
+
+/// The parts of `GET /v1/me` used by claims.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CurrentUser {
+    pub id: String,
+    /// The user's market; only returned with the `user-read-private` scope.
+    pub country: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ExternalIds {
-    isrc: Option<String>,
+    pub isrc: Option<String>,
     ean: Option<String>,
     upc: Option<String>,
 }
@@ -84,6 +540,9 @@ pub struct Album {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Artist {
     pub external_urls: ExternalUrls,
+    /// Only full artist objects, e.g. top artists, list genres.
+    #[serde(default)]
+    pub genres: Vec<String>,
     pub href: String,
     pub id: String,
     pub name: String,
@@ -123,8 +582,120 @@ pub struct Track {
     pub track_number: u32,
     pub r#type: String,
     pub uri: String,
+    /// Set when the track was relinked for the requested market; names the original track.
+    pub linked_from: Option<LinkedTrack>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LinkedTrack {
+    pub id: String,
+}
+
+/// Response of `GET /v1/artists/{id}/top-tracks`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ArtistTopTracks {
+    pub tracks: Vec<Track>,
+}
+
+/// Response of `GET /v1/artists?ids=...`; unknown IDs are null.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeveralArtists {
+    pub artists: Vec<Option<Artist>>,
+}
+
+/// The parts of `GET /v1/episodes/{id}` used by claims.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Episode {
+    pub id: String,
+    pub r#type: String,
+    pub show: EpisodeShow,
+    /// Only present with the `user-read-playback-position` scope.
+    pub resume_point: Option<ResumePoint>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResumePoint {
+    pub fully_played: bool,
+    pub resume_position_ms: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EpisodeShow {
+    pub id: String,
+}
+
+/// An item of `GET /v1/me/audiobooks`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SimplifiedAudiobook {
+    pub id: String,
+}
+
+/// An item of `GET /v1/audiobooks/{id}/chapters`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SimplifiedChapter {
+    pub id: String,
+    pub duration_ms: u64,
+    /// Only present with the `user-read-playback-position` scope.
+    pub resume_point: Option<ResumePoint>,
+}
+
+/// An item of `GET /v1/me/shows`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SavedShow {
+    pub show: EpisodeShow,
+}
+
+/// The parts of `GET /v1/artists/{id}` used by claims.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ArtistFollowers {
+    pub followers: Followers,
+}
+
+/// Response of `GET /v1/markets`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Markets {
+    pub markets: Vec<String>,
+}
+
+/// The parts of `GET /v1/albums/{id}` used by claims.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AlbumMarkets {
+    pub available_markets: Vec<String>,
+}
+
+/// Response of `GET /v1/audio-analysis/{id}`; only the parts used by claims are modelled.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AudioAnalysis {
+    pub track: AnalysisTrack,
+    pub bars: Vec<TimeInterval>,
+    pub beats: Vec<TimeInterval>,
+    pub segments: Vec<AnalysisSegment>,
+    pub tatums: Vec<TimeInterval>,
+}
+
+/// Track-level attributes of an audio analysis; times are in seconds.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AnalysisTrack {
+    pub duration: f64,
+    pub end_of_fade_in: f64,
+    pub start_of_fade_out: f64,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TimeInterval {
+    pub start: f64,
+    pub duration: f64,
+    pub confidence: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AnalysisSegment {
+    pub start: f64,
+    pub duration: f64,
+    pub confidence: f64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum TimeRange {
     ShortTerm = 0,
     MediumTerm = 1,
@@ -144,3 +715,51 @@ impl TimeRange {
         }
     }
 }
+
+impl fmt::Display for TimeRange {
+    /// Formats the range as the `time_range` value expected by the Spotify API.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            TimeRange::ShortTerm => "short_term",
+            TimeRange::MediumTerm => "medium_term",
+            TimeRange::LongTerm => "long_term",
+        };
+        f.write_str(value)
+    }
+}
+
+impl FromStr for TimeRange {
+    type Err = String;
+
+    /// Parses a Spotify `time_range` value back into a TimeRange.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "short_term" => Ok(TimeRange::ShortTerm),
+            "medium_term" => Ok(TimeRange::MediumTerm),
+            "long_term" => Ok(TimeRange::LongTerm),
+            _ => Err(format!("Invalid value for TimeRange: {}", value)),
+        }
+    }
+}
+
+/// How an artist's place in the user's top artists changed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ListeningEvolution {
+    /// In the short-term top artists but not the long-term ones.
+    NewlyDiscovered = 0,
+    /// In the long-term top artists but not the short-term ones.
+    Faded = 1,
+}
+
+impl ListeningEvolution {
+    /// Converts a numerical value to a ListeningEvolution.
+    ///
+    /// Returns an error if the given value is not a valid ListeningEvolution.
+    pub fn from_number(value: u8) -> Result<Self, String> {
+        match value {
+            0 => Ok(ListeningEvolution::NewlyDiscovered),
+            1 => Ok(ListeningEvolution::Faded),
+            _ => Err(format!("Invalid value for ListeningEvolution: {}", value)),
+        }
+    }
+}
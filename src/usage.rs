@@ -0,0 +1,432 @@
+//! Per-request accounting of outbound provider calls, for quota forecasting.
+//!
+//! Calls are counted in memory, labelled by provider, endpoint template, claim
+//! function and outcome class, and periodically flushed to daily buckets in Redis.
+//! Recording only touches an in-process map, so it adds no round trip to a call.
+//!
+//! Failed calls are also kept in a small per-provider ring buffer for on-call
+//! debugging. Entries carry no user identifiers: endpoints are reduced to their
+//! templates and tokens are never recorded.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use redis::RedisResult;
+use reqwest::StatusCode;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::env;
+use std::sync::{LazyLock, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::redis::{
+    get_provider_usage, incr_provider_usage, last_storage_usage, plaintext_token_reads,
+};
+
+/// How often buffered counts are written to Redis.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Label used for calls made outside of a claim, e.g. by admin methods.
+const NO_CLAIM: &str = "none";
+
+/// Failures kept per provider unless `PROVIDER_ERROR_BUFFER_SIZE` says otherwise.
+const DEFAULT_FAILURE_BUFFER_SIZE: usize = 100;
+
+tokio::task_local! {
+    /// The claim being resolved by the current task, used to label provider calls.
+    pub static CLAIM_FUNCTION: &'static str;
+    /// ID of the HTTP request being served, echoed in its `x-request-id` header.
+    pub static REQUEST_ID: String;
+}
+
+/// Outcome class of a provider call.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Outcome {
+    Success,
+    ClientError,
+    ServerError,
+    /// A redirect or non-API (e.g. HTML) response.
+    Unavailable,
+    /// No response at all: connection, TLS or timeout failures.
+    Transport,
+}
+
+impl Outcome {
+    /// Classifies a call that got a response by its status code.
+    pub fn from_status(status: StatusCode) -> Self {
+        if status.is_success() {
+            Outcome::Success
+        } else if status.is_client_error() {
+            Outcome::ClientError
+        } else {
+            Outcome::ServerError
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Success => "success",
+            Outcome::ClientError => "client_error",
+            Outcome::ServerError => "server_error",
+            Outcome::Unavailable => "unavailable",
+            Outcome::Transport => "transport",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+struct UsageKey {
+    provider: &'static str,
+    endpoint: String,
+    function: &'static str,
+    outcome: Outcome,
+}
+
+impl UsageKey {
+    /// The Redis hash field the count is stored under.
+    fn field(&self) -> String {
+        format!(
+            "{}|{}|{}|{}",
+            self.provider,
+            self.endpoint,
+            self.function,
+            self.outcome.as_str()
+        )
+    }
+}
+
+static PENDING: LazyLock<Mutex<HashMap<UsageKey, u64>>> = LazyLock::new(Mutex::default);
+
+/// Calls counted since the process started, for the Prometheus export.
+static TOTALS: LazyLock<Mutex<HashMap<UsageKey, u64>>> = LazyLock::new(Mutex::default);
+
+/// One failed provider call, as reported by `recent_provider_errors`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ProviderFailure {
+    pub at: DateTime<Utc>,
+    pub provider: String,
+    pub endpoint: String,
+    /// HTTP status, absent for transport failures.
+    pub status: Option<u16>,
+    pub outcome: String,
+    /// Attempts made before this one; provider calls are not retried yet.
+    pub retries: u32,
+    pub function: String,
+    pub request_id: Option<String>,
+}
+
+static FAILURE_BUFFER_SIZE: LazyLock<usize> = LazyLock::new(|| {
+    env::var("PROVIDER_ERROR_BUFFER_SIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(DEFAULT_FAILURE_BUFFER_SIZE)
+});
+
+/// The most recent failures per provider, oldest first.
+static RECENT_FAILURES: LazyLock<Mutex<HashMap<&'static str, VecDeque<ProviderFailure>>>> =
+    LazyLock::new(Mutex::default);
+
+/// Top-level API collections whose second path segment is a resource ID.
+const ID_COLLECTIONS: &[&str] = &[
+    "albums",
+    "artists",
+    "audio-analysis",
+    "audio-features",
+    "episodes",
+    "playlists",
+    "shows",
+    "tracks",
+    "users",
+];
+
+/// Reduces a request URL to its parameterized path, e.g.
+/// `https://api.spotify.com/v1/albums/4aaw/tracks?limit=50` to `/v1/albums/{id}/tracks`.
+pub fn endpoint_template(url: &str) -> String {
+    let path = url
+        .split_once("://")
+        .map(|(_, rest)| rest.find('/').map(|i| &rest[i..]).unwrap_or("/"))
+        .unwrap_or(url);
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+
+    let segments: Vec<&str> = path.split('/').collect();
+    // ["", "v1", collection, id, ...]
+    let template: Vec<&str> = segments
+        .iter()
+        .enumerate()
+        .map(|(i, segment)| {
+            if i == 3 && ID_COLLECTIONS.contains(&segments[2]) {
+                "{id}"
+            } else {
+                segment
+            }
+        })
+        .collect();
+    template.join("/")
+}
+
+/// Counts one call to `url` on `provider`, labelled with the claim being resolved.
+///
+/// Unsuccessful calls are also added to the provider's recent failures, with
+/// `status` when the provider answered at all.
+pub fn record(provider: &'static str, url: &str, outcome: Outcome, status: Option<u16>) {
+    let key = UsageKey {
+        provider,
+        endpoint: endpoint_template(url),
+        function: CLAIM_FUNCTION.try_with(|f| *f).unwrap_or(NO_CLAIM),
+        outcome,
+    };
+    if outcome != Outcome::Success {
+        record_failure(&key, status);
+    }
+    *TOTALS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(key.clone())
+        .or_insert(0) += 1;
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    *pending.entry(key).or_insert(0) += 1;
+}
+
+/// Renders the calls counted since the process started in the Prometheus text
+/// exposition format, as `oracle_provider_calls_total` with the usual labels,
+/// along with `oracle_plaintext_token_reads_total` and the storage gauges.
+pub fn prometheus_metrics() -> String {
+    let totals = TOTALS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut lines: Vec<String> = totals
+        .iter()
+        .map(|(key, count)| {
+            format!(
+                "oracle_provider_calls_total{{provider=\"{}\",endpoint=\"{}\",function=\"{}\",outcome=\"{}\"}} {}",
+                escape_label(key.provider),
+                escape_label(&key.endpoint),
+                escape_label(key.function),
+                key.outcome.as_str(),
+                count
+            )
+        })
+        .collect();
+    lines.sort();
+
+    let mut metrics = String::from(
+        "# HELP oracle_provider_calls_total Outbound provider calls since the process started.\n\
+         # TYPE oracle_provider_calls_total counter\n",
+    );
+    for line in lines {
+        metrics.push_str(&line);
+        metrics.push('\n');
+    }
+    metrics.push_str(&format!(
+        "# HELP oracle_plaintext_token_reads_total Stored tokens read unencrypted since the process started.\n\
+         # TYPE oracle_plaintext_token_reads_total counter\n\
+         oracle_plaintext_token_reads_total {}\n",
+        plaintext_token_reads()
+    ));
+
+    // Sizing the keyspace is too slow for a scrape, so the gauges show the
+    // last `storage_usage` report.
+    let storage = last_storage_usage();
+    if !storage.is_empty() {
+        metrics.push_str(
+            "# HELP oracle_storage_keys Keys per feature at the last storage_usage report.\n\
+             # TYPE oracle_storage_keys gauge\n",
+        );
+        for (feature, usage) in &storage {
+            metrics.push_str(&format!(
+                "oracle_storage_keys{{feature=\"{}\"}} {}\n",
+                feature, usage.keys
+            ));
+        }
+        metrics.push_str(
+            "# HELP oracle_storage_bytes Approximate bytes per feature at the last storage_usage report.\n\
+             # TYPE oracle_storage_bytes gauge\n",
+        );
+        for (feature, usage) in &storage {
+            metrics.push_str(&format!(
+                "oracle_storage_bytes{{feature=\"{}\"}} {}\n",
+                feature, usage.bytes
+            ));
+        }
+    }
+    metrics
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn record_failure(key: &UsageKey, status: Option<u16>) {
+    let failure = ProviderFailure {
+        at: Utc::now(),
+        provider: key.provider.to_string(),
+        endpoint: key.endpoint.clone(),
+        status,
+        outcome: key.outcome.as_str().to_string(),
+        retries: 0,
+        function: key.function.to_string(),
+        request_id: REQUEST_ID.try_with(String::clone).ok(),
+    };
+    let capacity = *FAILURE_BUFFER_SIZE;
+    let mut recent = RECENT_FAILURES.lock().unwrap_or_else(|e| e.into_inner());
+    let buffer = recent.entry(key.provider).or_default();
+    if buffer.len() >= capacity {
+        buffer.pop_front();
+    }
+    if capacity > 0 {
+        buffer.push_back(failure);
+    }
+}
+
+/// Recent failures of one provider, as reported by `recent_provider_errors`.
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct ProviderFailures {
+    /// Buffered failures per outcome class.
+    pub counts: BTreeMap<String, u64>,
+    /// Up to the requested number of failures, newest first.
+    pub errors: Vec<ProviderFailure>,
+}
+
+/// Returns the buffered failures of every provider, at most `limit` each.
+pub fn recent_failures(limit: usize) -> BTreeMap<String, ProviderFailures> {
+    let recent = RECENT_FAILURES.lock().unwrap_or_else(|e| e.into_inner());
+    recent
+        .iter()
+        .map(|(provider, buffer)| {
+            let mut counts = BTreeMap::new();
+            for failure in buffer {
+                *counts.entry(failure.outcome.clone()).or_insert(0) += 1;
+            }
+            let errors = buffer.iter().rev().take(limit).cloned().collect();
+            (provider.to_string(), ProviderFailures { counts, errors })
+        })
+        .collect()
+}
+
+/// How far back `health` looks for provider failures, in seconds.
+const HEALTH_WINDOW_SECS: i64 = 5 * 60;
+
+/// A provider that failed recently, as reported by `health`.
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct DegradedReason {
+    pub provider: String,
+    /// Buffered failures within the health window, per outcome class.
+    pub counts: BTreeMap<String, u64>,
+}
+
+/// The oracle's health, as reported by `health`.
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct Health {
+    /// `ok`, or `degraded` when any provider failed within the health window.
+    pub status: String,
+    pub degraded_reasons: Vec<DegradedReason>,
+}
+
+/// Summarizes the buffered failures of the last `HEALTH_WINDOW_SECS` per provider.
+///
+/// Counts come from the ring buffers, so they top out at their size.
+pub fn health() -> Health {
+    let since = Utc::now() - chrono::Duration::seconds(HEALTH_WINDOW_SECS);
+    let recent = RECENT_FAILURES.lock().unwrap_or_else(|e| e.into_inner());
+    let mut degraded_reasons: Vec<DegradedReason> = recent
+        .iter()
+        .filter_map(|(provider, buffer)| {
+            let mut counts = BTreeMap::new();
+            for failure in buffer.iter().filter(|failure| failure.at >= since) {
+                *counts.entry(failure.outcome.clone()).or_insert(0) += 1;
+            }
+            (!counts.is_empty()).then(|| DegradedReason {
+                provider: provider.to_string(),
+                counts,
+            })
+        })
+        .collect();
+    degraded_reasons.sort_by(|a, b| a.provider.cmp(&b.provider));
+
+    let status = if degraded_reasons.is_empty() {
+        "ok"
+    } else {
+        "degraded"
+    };
+    Health {
+        status: status.to_string(),
+        degraded_reasons,
+    }
+}
+
+/// Writes buffered counts to today's Redis bucket.
+///
+/// On failure the counts are put back so they are retried on the next flush.
+pub fn flush() -> RedisResult<()> {
+    let counts = std::mem::take(&mut *PENDING.lock().unwrap_or_else(|e| e.into_inner()));
+    if counts.is_empty() {
+        return Ok(());
+    }
+
+    let day = Utc::now().date_naive();
+    let fields: Vec<(String, u64)> = counts.iter().map(|(k, n)| (k.field(), *n)).collect();
+    if let Err(e) = incr_provider_usage(day, fields) {
+        let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+        for (key, count) in counts {
+            *pending.entry(key).or_insert(0) += count;
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Starts a background thread flushing buffered counts every `FLUSH_INTERVAL`.
+///
+/// A failed flush keeps its counts for the next one, so while Redis is down
+/// the buffer grows with the number of distinct labels, not with calls.
+pub fn spawn_flusher() {
+    thread::spawn(|| loop {
+        thread::sleep(FLUSH_INTERVAL);
+        if let Err(e) = flush() {
+            eprintln!(
+                "Failed to flush provider usage, keeping it for the next flush: {}",
+                e
+            );
+        }
+    });
+}
+
+/// One row of the `provider_usage` report.
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct UsageRecord {
+    pub date: NaiveDate,
+    pub provider: String,
+    pub endpoint: String,
+    pub function: String,
+    pub outcome: String,
+    pub count: u64,
+}
+
+/// Returns the recorded calls for every day from `from` to `to`, inclusive.
+///
+/// Buffered counts are flushed first so the report includes them.
+pub fn usage_between(from: NaiveDate, to: NaiveDate) -> RedisResult<Vec<UsageRecord>> {
+    flush()?;
+
+    let mut records = Vec::new();
+    for date in from.iter_days().take_while(|date| *date <= to) {
+        let mut counts: Vec<(String, u64)> = get_provider_usage(date)?.into_iter().collect();
+        counts.sort();
+        for (field, count) in counts {
+            let mut labels = field.splitn(4, '|').map(String::from);
+            let (Some(provider), Some(endpoint), Some(function), Some(outcome)) =
+                (labels.next(), labels.next(), labels.next(), labels.next())
+            else {
+                continue;
+            };
+            records.push(UsageRecord {
+                date,
+                provider,
+                endpoint,
+                function,
+                outcome,
+                count,
+            });
+        }
+    }
+    Ok(records)
+}
@@ -0,0 +1,4296 @@
+//! Regression baseline for every method registered by `create_io`.
+//!
+//! Requests go through the real `IoHandler`. Redis is replaced by a tiny RESP
+//! server holding its data in memory, and the Spotify Web API by a local HTTP
+//! server answering with fixtures, selected through `REDIS_HOSTNAME` and
+//! `SPOTIFY_API_URL`.
+
+use jsonrpc_core::{Error, ErrorCode};
+use jsonrpc_http_server::hyper::service::{make_service_fn, service_fn};
+use jsonrpc_http_server::hyper::{Body, Request, Response, Server};
+use jsonrpc_http_server::ServerBuilder;
+use noir_oracles::admin::AUTHORIZATION;
+use noir_oracles::cache::{PendingRequestCache, TtlCache};
+use noir_oracles::client::{ClaimParams, ClientError, OracleClient};
+use noir_oracles::clock::FixedClock;
+use noir_oracles::leniency::{Leniency, Policy, StrictMode, LENIENCIES};
+use noir_oracles::middleware::{logger::LoggerMiddleware, strict_json::StrictJsonMiddleware};
+use noir_oracles::providers::songkick;
+use noir_oracles::query_builder::all_windows_hold;
+use noir_oracles::query_builder::library::{
+    can_claim_playlist_recently_updated, observe_playlist_snapshot,
+};
+use noir_oracles::redis::{
+    check_encryption_config, check_redis_hostname, decrypt_token, discover_master, encrypt_token,
+    parse_encryption_key, plaintext_token_reads, store_playlist_snapshot, SentinelConfig,
+};
+use noir_oracles::rpc_methods::{create_io, create_io_with_clock};
+use noir_oracles::server::start_server;
+use noir_oracles::signing::{check_signing_config, parse_signing_key, verify};
+use noir_oracles::types::{PlaylistSnapshot, TimeRange, CLAIMS};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::env;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::thread;
+
+const KEY: &str = "alice";
+const TOKEN: &str = "Bearer test-token";
+const NO_ARTIST_KEY: &str = "no-artist";
+const NO_ARTIST_TOKEN: &str = "Bearer no-artist-token";
+/// A third account, whose only top track is track1.
+const FAN_KEY: &str = "fan";
+const FAN_TOKEN: &str = "Bearer fan-token";
+const BLOCKED_MARKET_KEY: &str = "blocked-market";
+const BLOCKED_MARKET_TOKEN: &str = "Bearer blocked-market-token";
+/// A token without the `user-read-private` scope, so its profile has no market.
+const NO_COUNTRY_KEY: &str = "no-country";
+const NO_COUNTRY_TOKEN: &str = "Bearer no-country-token";
+/// MusicBrainz recordings: the original of a work, an unrelated one, and the
+/// cover every mock track's ISRC points to.
+const ORIGINAL_RECORDING: &str = "11111111-1111-4111-8111-111111111111";
+const OTHER_RECORDING: &str = "22222222-2222-4222-8222-222222222222";
+const COVER_RECORDING: &str = "33333333-3333-4333-8333-333333333333";
+/// The MusicBrainz artist linked to artist1's Spotify page.
+const ARTIST_MBID: &str = "44444444-4444-4444-8444-444444444444";
+const ADMIN_TOKEN: &str = "test-admin-token";
+const ENCRYPTION_KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+const SIGNING_KEY: &str = "1f1e1d1c1b1a191817161514131211100f0e0d0c0b0a09080706050403020100";
+/// The time claims resolved through [`resolve_at`] are stamped with.
+const TEST_NOW: i64 = 1_700_000_000;
+
+/// Starts the fake Redis and Spotify servers once and points the oracle at them.
+fn mock_backends() {
+    static BACKENDS: OnceLock<()> = OnceLock::new();
+    BACKENDS.get_or_init(|| {
+        let redis_addr = start_fake_redis();
+        let spotify_addr = start_mock_spotify();
+
+        env::set_var("REDIS_HOSTNAME", redis_addr.to_string());
+        env::remove_var("REDIS_PASSWORD");
+        env::remove_var("IS_TLS");
+        env::set_var("SPOTIFY_API_URL", format!("http://{}", spotify_addr));
+        env::set_var(
+            "OTEL_EXPORTER_OTLP_ENDPOINT",
+            format!("http://{}/otlp", spotify_addr),
+        );
+        env::set_var("OTEL_BSP_SCHEDULE_DELAY", "20");
+        env::set_var("STRICT_MODE", "allow");
+        env::set_var("ORACLE_ENCRYPTION_KEY", ENCRYPTION_KEY);
+        env::set_var("ORACLE_SIGNING_KEY", SIGNING_KEY);
+        env::set_var("ORACLE_ADMIN_TOKEN", ADMIN_TOKEN);
+        env::set_var("ORACLE_BLOCKED_MARKETS", "KP, cu");
+        env::set_var(
+            "YOUTUBE_API_URL",
+            format!("http://{}/youtube", spotify_addr),
+        );
+        env::set_var("YOUTUBE_API_KEY", "test-youtube-key");
+        env::set_var(
+            "TUNEFIND_API_URL",
+            format!("http://{}/tunefind", spotify_addr),
+        );
+        env::set_var("TUNEFIND_API_USERNAME", "test-tunefind");
+        env::set_var("TUNEFIND_API_PASSWORD", "test-secret");
+        env::set_var(
+            "SONGKICK_API_URL",
+            format!("http://{}/songkick", spotify_addr),
+        );
+        env::set_var("SONGKICK_API_KEY", "test-songkick-key");
+        env::set_var(
+            "APPLE_MUSIC_API_URL",
+            format!("http://{}/apple-music", spotify_addr),
+        );
+        env::set_var("APPLE_MUSIC_DEVELOPER_TOKEN", "apple-developer-token");
+        env::set_var(
+            "MUSICBRAINZ_API_URL",
+            format!("http://{}/musicbrainz", spotify_addr),
+        );
+        let royalty_free = env::temp_dir().join("noir-oracles-royalty-free-tracks.txt");
+        std::fs::write(&royalty_free, "# Creative Commons releases\ntrack2\n").unwrap();
+        env::set_var("ROYALTY_FREE_TRACKS_FILE", royalty_free);
+        let sampling = env::temp_dir().join("noir-oracles-sampling-database.txt");
+        std::fs::write(&sampling, "# sampling_id,sampled_id\ntrack1, track3\n").unwrap();
+        env::set_var("SAMPLING_DATABASE_FILE", sampling);
+    });
+}
+
+// --- Fake Redis -----------------------------------------------------------
+
+type Store = Arc<Mutex<HashMap<String, Entry>>>;
+
+enum Entry {
+    String(String),
+    List(Vec<String>),
+    Hash(HashMap<String, i64>),
+    Fields(HashMap<String, String>),
+}
+
+fn start_fake_redis() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind fake redis");
+    let addr = listener.local_addr().unwrap();
+    let store: Store = Arc::default();
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let store = store.clone();
+            thread::spawn(move || serve_redis_connection(stream, store));
+        }
+    });
+    addr
+}
+
+fn serve_redis_connection(stream: TcpStream, store: Store) {
+    let mut writer = stream.try_clone().unwrap();
+    let mut reader = BufReader::new(stream);
+
+    while let Some(command) = read_resp_command(&mut reader) {
+        let reply = execute_redis_command(&command, &store);
+        if writer.write_all(reply.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+fn read_resp_command(reader: &mut impl BufRead) -> Option<Vec<String>> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let count: usize = line.trim().strip_prefix('*')?.parse().ok()?;
+
+    let mut args = Vec::with_capacity(count);
+    for _ in 0..count {
+        line.clear();
+        reader.read_line(&mut line).ok()?;
+        let len: usize = line.trim().strip_prefix('$')?.parse().ok()?;
+        let mut bytes = vec![0; len + 2];
+        reader.read_exact(&mut bytes).ok()?;
+        bytes.truncate(len);
+        args.push(String::from_utf8(bytes).ok()?);
+    }
+    Some(args)
+}
+
+fn bulk(value: &str) -> String {
+    format!("${}\r\n{}\r\n", value.len(), value)
+}
+
+fn execute_redis_command(command: &[String], store: &Store) -> String {
+    let mut data = store.lock().unwrap();
+    match command[0].to_ascii_uppercase().as_str() {
+        "SET" => {
+            data.insert(command[1].clone(), Entry::String(command[2].clone()));
+            "+OK\r\n".to_string()
+        }
+        "GET" => match data.get(&command[1]) {
+            Some(Entry::String(value)) => bulk(value),
+            _ => "$-1\r\n".to_string(),
+        },
+        "DEL" => {
+            let removed = command[1..]
+                .iter()
+                .filter(|key| data.remove(*key).is_some())
+                .count();
+            format!(":{}\r\n", removed)
+        }
+        "RPUSH" => {
+            let entry = data
+                .entry(command[1].clone())
+                .or_insert_with(|| Entry::List(Vec::new()));
+            match entry {
+                Entry::List(items) => {
+                    items.extend(command[2..].iter().cloned());
+                    format!(":{}\r\n", items.len())
+                }
+                _ => "-WRONGTYPE\r\n".to_string(),
+            }
+        }
+        "LRANGE" => match data.get(&command[1]) {
+            Some(Entry::List(items)) => {
+                let mut reply = format!("*{}\r\n", items.len());
+                for item in items {
+                    reply.push_str(&bulk(item));
+                }
+                reply
+            }
+            _ => "*0\r\n".to_string(),
+        },
+        "HINCRBY" => {
+            let entry = data
+                .entry(command[1].clone())
+                .or_insert_with(|| Entry::Hash(HashMap::new()));
+            match entry {
+                Entry::Hash(fields) => {
+                    let value = fields.entry(command[2].clone()).or_insert(0);
+                    *value += command[3].parse::<i64>().unwrap_or(0);
+                    format!(":{}\r\n", value)
+                }
+                _ => "-WRONGTYPE\r\n".to_string(),
+            }
+        }
+        "HGETALL" => match data.get(&command[1]) {
+            Some(Entry::Hash(fields)) => {
+                let mut reply = format!("*{}\r\n", fields.len() * 2);
+                for (field, value) in fields {
+                    reply.push_str(&bulk(field));
+                    reply.push_str(&bulk(&value.to_string()));
+                }
+                reply
+            }
+            _ => "*0\r\n".to_string(),
+        },
+        "HSET" => {
+            let entry = data
+                .entry(command[1].clone())
+                .or_insert_with(|| Entry::Fields(HashMap::new()));
+            match entry {
+                Entry::Fields(fields) => {
+                    let added = command[2..]
+                        .chunks(2)
+                        .filter(|pair| fields.insert(pair[0].clone(), pair[1].clone()).is_none())
+                        .count();
+                    format!(":{}\r\n", added)
+                }
+                _ => "-WRONGTYPE\r\n".to_string(),
+            }
+        }
+        "HGET" => match data.get(&command[1]) {
+            Some(Entry::Fields(fields)) => match fields.get(&command[2]) {
+                Some(value) => bulk(value),
+                None => "$-1\r\n".to_string(),
+            },
+            _ => "$-1\r\n".to_string(),
+        },
+        // The oracle's scripts are check-and-swaps of a token, in a hash field
+        // when one is given.
+        "EVALSHA" => {
+            let (key, expected, new_token) = (&command[3], &command[4], &command[5]);
+            if let Some(field) = command.get(6) {
+                return match data.get_mut(key) {
+                    Some(Entry::Fields(fields)) => match fields.get_mut(field) {
+                        Some(token) if token == expected => {
+                            *token = new_token.clone();
+                            ":-1\r\n".to_string()
+                        }
+                        Some(_) => ":-3\r\n".to_string(),
+                        None => ":-2\r\n".to_string(),
+                    },
+                    _ => ":-2\r\n".to_string(),
+                };
+            }
+            match data.get(key) {
+                Some(Entry::String(token)) if token == expected => {
+                    data.insert(key.clone(), Entry::String(new_token.clone()));
+                    ":-1\r\n".to_string()
+                }
+                Some(Entry::String(_)) => ":-3\r\n".to_string(),
+                _ => ":-2\r\n".to_string(),
+            }
+        }
+        // Answers as a sentinel monitoring a single master, `mymaster`.
+        "SENTINEL" => match command.get(2).map(String::as_str) {
+            Some("mymaster") => format!("*2\r\n{}{}", bulk("10.0.0.5"), bulk("6380")),
+            _ => "*-1\r\n".to_string(),
+        },
+        "EXPIRE" => ":1\r\n".to_string(),
+        // A single pass over the whole keyspace.
+        "SCAN" => {
+            let mut reply = format!("*2\r\n{}*{}\r\n", bulk("0"), data.len());
+            for key in data.keys() {
+                reply.push_str(&bulk(key));
+            }
+            reply
+        }
+        "MEMORY" => match data.get(&command[2]) {
+            Some(Entry::String(value)) => format!(":{}\r\n", value.len()),
+            Some(Entry::List(items)) => {
+                format!(":{}\r\n", items.iter().map(String::len).sum::<usize>())
+            }
+            Some(Entry::Hash(fields)) => {
+                format!(":{}\r\n", fields.keys().map(|f| f.len() + 8).sum::<usize>())
+            }
+            Some(Entry::Fields(fields)) => format!(
+                ":{}\r\n",
+                fields.iter().map(|(f, v)| f.len() + v.len()).sum::<usize>()
+            ),
+            None => "$-1\r\n".to_string(),
+        },
+        // Connection setup (AUTH, SELECT, CLIENT SETINFO) and anything else.
+        _ => "+OK\r\n".to_string(),
+    }
+}
+
+// --- Mock Spotify ---------------------------------------------------------
+
+fn start_mock_spotify() -> SocketAddr {
+    let (addr_tx, addr_rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async move {
+            let make_service = make_service_fn(|_| async {
+                Ok::<_, Infallible>(service_fn(|request: Request<Body>| async move {
+                    if request.uri().path() == "/otlp/v1/traces" {
+                        return Ok::<_, Infallible>(collect_spans(request).await);
+                    }
+                    if let Some(path) = request.uri().path().strip_prefix("/previews") {
+                        return Ok::<_, Infallible>(preview_response(path));
+                    }
+                    if let Some(path) = request.uri().path().strip_prefix("/youtube") {
+                        let query = request.uri().query().unwrap_or_default();
+                        return Ok::<_, Infallible>(youtube_response(path, query));
+                    }
+                    if let Some(path) = request.uri().path().strip_prefix("/tunefind") {
+                        return Ok::<_, Infallible>(tunefind_response(&request, path));
+                    }
+                    if let Some(path) = request.uri().path().strip_prefix("/songkick") {
+                        let query = request.uri().query().unwrap_or_default();
+                        return Ok::<_, Infallible>(songkick_response(path, query));
+                    }
+                    if let Some(path) = request.uri().path().strip_prefix("/apple-music") {
+                        let query = request.uri().query().unwrap_or_default();
+                        return Ok::<_, Infallible>(apple_music_response(&request, path, query));
+                    }
+                    if let Some(path) = request.uri().path().strip_prefix("/musicbrainz") {
+                        let query = request.uri().query().unwrap_or_default();
+                        return Ok::<_, Infallible>(musicbrainz_response(path, query));
+                    }
+                    Ok::<_, Infallible>(spotify_response(&request))
+                }))
+            });
+            let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_service);
+            addr_tx.send(server.local_addr()).unwrap();
+            server.await.unwrap();
+        });
+    });
+
+    addr_rx.recv().unwrap()
+}
+
+/// Spans exported by the oracle to the mock OTLP collector.
+static EXPORTED_SPANS: Mutex<Vec<Value>> = Mutex::new(Vec::new());
+/// YouTube searches made for the `video-cached` track.
+static CACHED_VIDEO_SEARCHES: AtomicUsize = AtomicUsize::new(0);
+/// Requests for the `maintenance-once` analysis, whose first answer is an HTML page.
+static MAINTENANCE_ONCE_REQUESTS: AtomicUsize = AtomicUsize::new(0);
+
+async fn collect_spans(request: Request<Body>) -> Response<Body> {
+    let body = jsonrpc_http_server::hyper::body::to_bytes(request.into_body())
+        .await
+        .unwrap_or_default();
+    let payload: Value = serde_json::from_slice(&body).unwrap_or_default();
+    let spans = payload["resourceSpans"][0]["scopeSpans"][0]["spans"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    EXPORTED_SPANS.lock().unwrap().extend(spans);
+    json_response(200, json!({}))
+}
+
+fn spotify_response(request: &Request<Body>) -> Response<Body> {
+    let path = request.uri().path();
+    let query = request.uri().query().unwrap_or_default();
+
+    let authorization = request.headers().get("Authorization");
+    if authorization.is_some_and(|value| value == NO_ARTIST_TOKEN) {
+        return no_artist_response(path, query);
+    }
+    if authorization.is_some_and(|value| value == FAN_TOKEN) {
+        return match path {
+            "/v1/me" => json_response(200, json!({"id": "fan-user", "country": "US"})),
+            "/v1/me/top/tracks" => json_response(200, stats_page(vec![track_fixture("track1")])),
+            // The oldest of two saved tracks is removed between the two reads.
+            "/v1/me/tracks" => match query_param(query, "offset").as_deref() {
+                Some("1") => json_response(200, json!({"total": 1, "items": []})),
+                _ => json_response(
+                    200,
+                    json!({"total": 2, "items": [{"added_at": "2024-01-01T00:00:00Z"}]}),
+                ),
+            },
+            _ => json_response(
+                404,
+                json!({"error": {"status": 404, "message": "Not found"}}),
+            ),
+        };
+    }
+    if authorization.is_some_and(|value| value == NO_COUNTRY_TOKEN) {
+        if path == "/v1/me" {
+            return json_response(200, json!({"id": "no-country-user"}));
+        }
+        return json_response(
+            404,
+            json!({"error": {"status": 404, "message": "Not found"}}),
+        );
+    }
+    if authorization.is_some_and(|value| value == BLOCKED_MARKET_TOKEN) {
+        // A user whose only request that succeeds is the blocked market's profile.
+        if path == "/v1/me" {
+            return json_response(200, json!({"id": "blocked-user", "country": "CU"}));
+        }
+        return json_response(
+            404,
+            json!({"error": {"status": 404, "message": "Not found"}}),
+        );
+    }
+    let authorized = authorization.map(|value| value == TOKEN).unwrap_or(false);
+    if !authorized {
+        return json_response(
+            401,
+            json!({"error": {"status": 401, "message": "Invalid token"}}),
+        );
+    }
+
+    let body = match path {
+        "/v1/me/top/tracks" => {
+            let mut older = track_fixture("track20");
+            older["album"]["release_date"] = json!("1994");
+            older["album"]["release_date_precision"] = json!("year");
+            stats_page(vec![track_fixture("track1"), older])
+        }
+        "/v1/me/top/artists" => {
+            let mut artist = artist_fixture("artist1");
+            artist["genres"] = json!(["indie pop", "bedroom pop"]);
+            stats_page(vec![artist])
+        }
+        "/v1/me/player/recently-played" => json!({
+            "href": "https://api.spotify.com/v1/me/player/recently-played",
+            "limit": 50,
+            "next": null,
+            "cursors": null,
+            "items": [
+                {"played_at": "2024-05-01T10:04:00Z", "context": null, "track": track_fixture("track2")},
+                {
+                    "played_at": "2024-05-01T10:00:00Z",
+                    "context": {"type": "playlist", "uri": "spotify:playlist:playlist12"},
+                    "track": track_fixture("track1"),
+                },
+            ],
+        }),
+        "/v1/me/tracks/contains" => {
+            let ids = query_param(query, "ids").unwrap_or_default();
+            let saved: Vec<bool> = ids.split(',').map(|id| id.starts_with("track")).collect();
+            json!(saved)
+        }
+        // The oldest of 120 saved tracks is only returned at the last offset.
+        "/v1/me/tracks" => match query_param(query, "offset").as_deref() {
+            Some("119") => json!({"total": 120, "items": [{"added_at": "2019-05-01T00:00:00Z"}]}),
+            _ => json!({"total": 120, "items": [{"added_at": "2024-01-01T00:00:00Z"}]}),
+        },
+        "/v1/me/albums" => json!({"total": 12}),
+        "/v1/me/audiobooks" => json!({
+            "limit": 50,
+            "offset": 0,
+            "total": 1,
+            "next": null,
+            "items": [{"id": "audiobook1", "name": "Audiobook audiobook1"}],
+        }),
+        "/v1/audiobooks/audiobook1" | "/v1/audiobooks/audiobook2" => {
+            json!({"id": path.trim_start_matches("/v1/audiobooks/")})
+        }
+        "/v1/audiobooks/audiobook1/chapters" => json!({
+            "limit": 50,
+            "offset": 0,
+            "total": 3,
+            "next": null,
+            "items": [
+                {"id": "chapter1", "duration_ms": 600000, "resume_point": {"fully_played": true, "resume_position_ms": 0}},
+                {"id": "chapter2", "duration_ms": 600000, "resume_point": {"fully_played": false, "resume_position_ms": 300000}},
+                {"id": "chapter3", "duration_ms": 600000, "resume_point": {"fully_played": false, "resume_position_ms": 0}},
+            ],
+        }),
+        "/v1/me/shows" => json!({
+            "limit": 50,
+            "offset": 0,
+            "total": 7,
+            "next": null,
+            "items": [{"added_at": "2024-05-01T10:00:00Z", "show": {"id": "show1", "name": "Show show1"}}],
+        }),
+        "/v1/albums/album1/tracks" => json!({
+            "limit": 50,
+            "offset": 0,
+            "total": 2,
+            "next": null,
+            "items": [
+                {
+                    "id": "track1", "name": "Track track1", "disc_number": 1, "track_number": 1,
+                    "artists": [artist_fixture("artist1"), artist_fixture("artist2")],
+                },
+                {
+                    "id": "track2", "name": "Track track2", "disc_number": 1, "track_number": 2,
+                    "artists": [artist_fixture("artist1"), artist_fixture("artist3")],
+                },
+            ],
+        }),
+        "/v1/artists" if query_param(query, "ids").as_deref() == Some("artist1") => {
+            let mut artist = artist_fixture("artist1");
+            artist["genres"] = json!(["indie pop", "bedroom pop"]);
+            json!({"artists": [artist]})
+        }
+        "/v1/artists/artist2/albums" => json!({
+            "limit": 50,
+            "offset": 0,
+            "total": 1,
+            "next": null,
+            "items": [{"id": "album9"}],
+        }),
+        "/v1/albums/album9/tracks" => json!({
+            "limit": 50,
+            "offset": 0,
+            "total": 1,
+            "next": null,
+            "items": [{"id": "track99", "name": "Track track99", "disc_number": 1, "track_number": 1}],
+        }),
+        "/v1/artists/artist1/albums" => json!({
+            "limit": 50,
+            "offset": 0,
+            "total": 1,
+            "next": null,
+            "items": [{"id": "album1"}],
+        }),
+        "/v1/albums/album1" => json!({"id": "album1", "available_markets": ["DE", "GB", "US"]}),
+        // track1 is relinked for the market, so it is listed under another ID.
+        "/v1/markets" => json!({"markets": ["DE", "GB", "US"]}),
+        // artist1 has no top tracks in DE.
+        "/v1/artists/artist1/top-tracks" if query.contains("market=DE") => json!({"tracks": []}),
+        "/v1/artists/artist1/top-tracks" => {
+            let mut relinked = track_fixture("track1-relinked");
+            relinked["linked_from"] = json!({"id": "track1"});
+            json!({"tracks": [relinked, track_fixture("track7")]})
+        }
+        "/v1/episodes/episode1" => json!({
+            "id": "episode1",
+            "type": "episode",
+            "name": "Episode episode1",
+            "show": {"id": "show1", "name": "Show show1"},
+            "resume_point": {"fully_played": true, "resume_position_ms": 0},
+        }),
+        "/v1/episodes/episode2" => json!({
+            "id": "episode2",
+            "type": "episode",
+            "name": "Episode episode2",
+            "show": {"id": "show1", "name": "Show show1"},
+            "resume_point": {"fully_played": false, "resume_position_ms": 60000},
+        }),
+        "/v1/artists/artist1" => {
+            let mut artist = artist_fixture("artist1");
+            artist["followers"] = json!({"href": null, "total": 1500});
+            artist
+        }
+        "/v1/artists/artist2" => artist_fixture("artist2"),
+        "/v1/artists/artist2/top-tracks" => json!({"tracks": [track_fixture("track9")]}),
+        "/v1/me/following" => json!({
+            "artists": {
+                "limit": 50,
+                "next": null,
+                "cursors": {"after": null},
+                "total": 2,
+                "items": [artist_fixture("artist1"), artist_fixture("artist2")],
+            },
+        }),
+        "/v1/me" => json!({"id": "alice-user", "country": "US"}),
+        "/v1/me/player/devices" => json!({
+            "devices": [
+                {"id": "device1", "name": "iPhone in Berlin"},
+                {"id": null, "name": "Romeo's Mac"},
+            ],
+        }),
+        "/v1/playlists/playlist12" => json!({"name": "Morning Gym Mix"}),
+        "/v1/playlists/playlist1" => {
+            json!({
+                "followers": {"total": 42},
+                "owner": {"id": "alice-user"},
+                "snapshot_id": "snap2",
+                "collaborative": false,
+            })
+        }
+        "/v1/playlists/playlist2" => {
+            json!({"followers": {"total": null}, "owner": {"id": "curator"}})
+        }
+        "/v1/playlists/playlist3" => {
+            json!({"followers": {"total": 900}, "owner": {"id": "curator"}})
+        }
+        "/v1/me/playlists" => json!({
+            "limit": 50,
+            "offset": 0,
+            "total": 2,
+            "next": null,
+            "items": [{"id": "playlist1", "name": "Mix"}, {"id": "playlist2", "name": "Chill"}],
+        }),
+        "/v1/playlists/playlist1/tracks" => {
+            playlist_page(vec![json!({"id": "track1"}), json!({"id": "track2"})])
+        }
+        // An unavailable item and a local file alongside track2.
+        "/v1/playlists/playlist2/tracks" => playlist_page(vec![
+            Value::Null,
+            json!({"id": null}),
+            json!({"id": "track2"}),
+        ]),
+        // Three items, the last one added first.
+        "/v1/playlists/playlist4/tracks" => {
+            let added_at = match query_param(query, "offset").as_deref() {
+                Some("2") => "2020-03-01T00:00:00Z",
+                _ => "2021-06-01T00:00:00Z",
+            };
+            json!({"total": 3, "items": [{"added_at": added_at}]})
+        }
+        "/v1/playlists/playlist5/tracks" => json!({"total": 0, "items": []}),
+        // Two clean pages, the first with an unavailable item.
+        "/v1/playlists/playlist10/tracks" => match query_param(query, "offset").as_deref() {
+            Some("100") => explicit_page(&[Some(false)], None),
+            _ => explicit_page(&[Some(false), None], Some(path)),
+        },
+        // Explicit on the first page; the second page does not exist.
+        "/v1/playlists/playlist11/tracks" => {
+            explicit_page(&[Some(true)], Some("/v1/playlists/missing/tracks"))
+        }
+        // Collaborative playlists, with and without an item added by alice.
+        "/v1/playlists/playlist6" | "/v1/playlists/playlist7" => json!({"collaborative": true}),
+        "/v1/playlists/playlist6/tracks" => added_by_page(&[
+            ("curator", "track1"),
+            ("alice-user", "track2"),
+            ("alice-user", "track3"),
+        ]),
+        "/v1/playlists/playlist7/tracks" => added_by_page(&[("curator", "track3")]),
+        "/v1/audio-analysis/maintenance" => {
+            return Response::builder()
+                .header("Content-Type", "text/html")
+                .body(Body::from("<html>Down for maintenance</html>"))
+                .unwrap()
+        }
+        // A captured Spotify error page and a redirect to a bot challenge.
+        "/v1/audio-analysis/error-page" => {
+            return Response::builder()
+                .status(503)
+                .header("Content-Type", "text/html; charset=utf-8")
+                .body(Body::from(
+                    "<!DOCTYPE html>\n<html lang=\"en\"><head><title>Spotify - Error</title></head>\
+                     <body><div class=\"error\"><h1>Oops! Something went wrong</h1>\
+                     <p>Please try again later.</p></div></body></html>",
+                ))
+                .unwrap()
+        }
+        "/v1/audio-analysis/challenge" => {
+            return Response::builder()
+                .status(302)
+                .header("Location", "https://challenge.spotify.com/c/verify")
+                .header("Content-Type", "text/html")
+                .body(Body::from(
+                    "<html><body><a href=\"https://challenge.spotify.com/c/verify\">Found</a></body></html>",
+                ))
+                .unwrap()
+        }
+        "/v1/audio-analysis/maintenance-once"
+            if MAINTENANCE_ONCE_REQUESTS.fetch_add(1, Ordering::SeqCst) == 0 =>
+        {
+            return Response::builder()
+                .status(503)
+                .header("Content-Type", "text/html")
+                .body(Body::from("<html>Down for maintenance</html>"))
+                .unwrap()
+        }
+        "/v1/audio-analysis/unavailable" => {
+            return json_response(
+                503,
+                json!({"error": {"status": 503, "message": "Service unavailable"}}),
+            )
+        }
+        // A long track with more segments and tatums than a byte holds.
+        "/v1/audio-analysis/track-long" => json!({
+            "track": {"duration": 900.0, "end_of_fade_in": 1.5, "start_of_fade_out": 890.0},
+            "segments": intervals(300),
+            "beats": intervals(255),
+            "bars": intervals(64),
+            "tatums": intervals(1000),
+        }),
+        _ if path.starts_with("/v1/audio-analysis/") => json!({
+            "track": {"duration": 200.0, "end_of_fade_in": 1.5, "start_of_fade_out": 190.0},
+            "segments": intervals(3),
+            "beats": intervals(4),
+            "bars": intervals(2),
+            "tatums": intervals(8),
+        }),
+        "/v1/tracks/preview1" | "/v1/tracks/preview2" => {
+            let id = &path["/v1/tracks/".len()..];
+            let clip = if id == "preview1" { "clip" } else { "blocked" };
+            let mut track = track_fixture(id);
+            track["preview_url"] = json!(format!(
+                "{}/previews/{}",
+                env::var("SPOTIFY_API_URL").unwrap(),
+                clip
+            ));
+            track
+        }
+        // A Spotify original, whose ISRC no other catalog knows.
+        "/v1/tracks/original1" => {
+            let mut track = track_fixture("original1");
+            track["external_ids"]["isrc"] = json!("SEXXX2400099");
+            track
+        }
+        "/v1/tracks/no-such-track" => {
+            return json_response(
+                404,
+                json!({"error": {"status": 404, "message": "Non existing id"}}),
+            );
+        }
+        _ if path.starts_with("/v1/tracks/") => track_fixture(&path["/v1/tracks/".len()..]),
+        _ => {
+            return json_response(
+                404,
+                json!({"error": {"status": 404, "message": "Not found"}}),
+            )
+        }
+    };
+    json_response(200, body)
+}
+
+/// Preview clips on the CDN; `blocked` is refused as in a region without rights.
+fn preview_response(path: &str) -> Response<Body> {
+    match path {
+        "/blocked" => json_response(403, json!({})),
+        _ => json_response(200, json!({})),
+    }
+}
+
+/// track1 has an official video on its artist's Vevo channel; other searches
+/// only find fan uploads.
+fn youtube_response(path: &str, query: &str) -> Response<Body> {
+    if query_param(query, "key").as_deref() != Some("test-youtube-key") {
+        return json_response(
+            400,
+            json!({"error": {"code": 400, "message": "API key not valid"}}),
+        );
+    }
+    if path != "/youtube/v3/search" {
+        return json_response(404, json!({"error": {"code": 404, "message": "Not Found"}}));
+    }
+    let search = query_param(query, "q").unwrap_or_default();
+    if search.contains("video-cached") {
+        CACHED_VIDEO_SEARCHES.fetch_add(1, Ordering::SeqCst);
+    }
+    let channel = match search.as_str() {
+        "Artist+artist1+Track+track1+official+video" => "Artist artist1VEVO",
+        _ => "Artist artist1 - Topic",
+    };
+    json_response(
+        200,
+        json!({"items": [
+            {"snippet": {"title": "Lyrics", "channelTitle": "Lyrics Hub"}},
+            {"snippet": {"title": "Official Video", "channelTitle": channel}},
+        ]}),
+    )
+}
+
+/// Track track1 by artist1 was synced to one show.
+fn tunefind_response(request: &Request<Body>, path: &str) -> Response<Body> {
+    let authorization = request.headers().get("Authorization");
+    if authorization.is_none_or(|value| value != "Basic dGVzdC10dW5lZmluZDp0ZXN0LXNlY3JldA==") {
+        return json_response(401, json!({"error": "Unauthorized"}));
+    }
+    if path != "/api/v2/song" {
+        return json_response(404, json!({"error": "Not Found"}));
+    }
+    let query = request.uri().query().unwrap_or_default();
+    let artist = query_param(query, "artist");
+    let title = query_param(query, "name");
+    // A response in some other shape, as an endpoint change would produce.
+    if title.as_deref() == Some("Track+track3") {
+        return json_response(200, json!({"results": []}));
+    }
+    let songs = match (artist.as_deref(), title.as_deref()) {
+        (Some("Artist+artist1"), Some("Track+track1")) => json!([{
+            "appearances": [{"production": {"name": "Grey's Anatomy", "type": "tv"}}],
+        }]),
+        _ => json!([]),
+    };
+    json_response(200, json!({"songs": songs}))
+}
+
+/// artist1 is listed as Songkick artist 510, on tour in Austin and Berlin;
+/// artist2 is 511, with no upcoming events.
+fn songkick_response(path: &str, query: &str) -> Response<Body> {
+    if query_param(query, "apikey").as_deref() != Some("test-songkick-key") {
+        return json_response(403, json!({"resultsPage": {"status": "error"}}));
+    }
+    let results = match path {
+        "/api/3.0/search/artists.json" => match query_param(query, "query").as_deref() {
+            Some("Artist+artist1") => {
+                json!({"artist": [{"id": 510, "displayName": "Artist artist1"}]})
+            }
+            _ => json!({}),
+        },
+        "/api/3.0/artists/510/calendar.json" => json!({"event": [
+            {"location": {"city": "Austin, TX, US"}},
+            {"location": {"city": "Berlin, Germany"}},
+        ]}),
+        "/api/3.0/artists/511/calendar.json" => json!({}),
+        // A calendar that never ends: every page claims more events follow.
+        "/api/3.0/artists/512/calendar.json" => {
+            let page = query_param(query, "page").unwrap_or_default();
+            let event = json!({"location": {"city": format!("Town {}, Germany", page)}});
+            return json_response(
+                200,
+                json!({"resultsPage": {"status": "ok", "totalEntries": 100_000, "results": {"event": [event]}}}),
+            );
+        }
+        "/api/3.0/artists/510.json" => {
+            json!({"artist": {"id": 510, "displayName": "Artist artist1"}})
+        }
+        "/api/3.0/artists/511.json" => {
+            json!({"artist": {"id": 511, "displayName": "Artist artist2"}})
+        }
+        _ => return json_response(404, json!({"resultsPage": {"status": "error"}})),
+    };
+    let total = results["event"].as_array().map_or(0, Vec::len);
+    json_response(
+        200,
+        json!({"resultsPage": {"status": "ok", "totalEntries": total, "results": results}}),
+    )
+}
+
+fn apple_music_response(request: &Request<Body>, path: &str, query: &str) -> Response<Body> {
+    let authorization = request.headers().get("Authorization");
+    if authorization.is_none_or(|value| value != "Bearer apple-developer-token") {
+        return json_response(401, json!({"errors": [{"status": "401"}]}));
+    }
+    if path != "/v1/catalog/us/songs" {
+        return json_response(404, json!({"errors": [{"status": "404"}]}));
+    }
+    // Every fixture track's ISRC is in the catalog.
+    let songs = match query {
+        "filter[isrc]=USXXX2400001" | "filter%5Bisrc%5D=USXXX2400001" => json!([{"id": "1"}]),
+        _ => json!([]),
+    };
+    json_response(200, json!({"data": songs}))
+}
+
+fn musicbrainz_response(path: &str, query: &str) -> Response<Body> {
+    let performance = |work: &str, attributes: Value| json!({"type": "performance", "attributes": attributes, "work": {"id": work}});
+    let body = match path {
+        "/ws/2/isrc/USXXX2400001" => json!({"recordings": [{"id": COVER_RECORDING}]}),
+        // Only artist1 is linked from MusicBrainz, with a 38-character annotation.
+        "/ws/2/url" if query.contains("artist%2Fartist1&") => {
+            json!({"relations": [{"artist": {"id": ARTIST_MBID}}]})
+        }
+        "/ws/2/annotation" if query.contains(ARTIST_MBID) => json!({"annotations": [
+            {"entity": ARTIST_MBID, "type": "artist", "text": " Indie pop duo formed in 2019. Touring. "},
+            {"entity": OTHER_RECORDING, "type": "recording", "text": "A much longer recording note."},
+        ]}),
+        _ if path == format!("/ws/2/recording/{}", ORIGINAL_RECORDING) => {
+            json!({"relations": [performance("work1", json!([]))]})
+        }
+        _ if path == format!("/ws/2/recording/{}", OTHER_RECORDING) => {
+            json!({"relations": [performance("work2", json!([]))]})
+        }
+        _ if path == format!("/ws/2/recording/{}", COVER_RECORDING) => {
+            json!({"relations": [performance("work1", json!(["cover"]))]})
+        }
+        _ => return json_response(404, json!({"error": "Not Found"})),
+    };
+    json_response(200, body)
+}
+
+/// A second account whose only recent play has no artist listed.
+fn no_artist_response(path: &str, query: &str) -> Response<Body> {
+    if path == "/v1/me" {
+        return json_response(200, json!({"id": "no-artist-user", "country": "DE"}));
+    }
+    if path == "/v1/me/top/artists" {
+        // artist2 is a recent discovery.
+        let artists = match query_param(query, "time_range").as_deref() {
+            Some("short_term") => vec![artist_fixture("artist2")],
+            _ => vec![],
+        };
+        return json_response(200, stats_page(artists));
+    }
+    if path == "/v1/me/top/tracks" {
+        let mut track = track_fixture("track3");
+        track["explicit"] = json!(true);
+        return json_response(200, stats_page(vec![track]));
+    }
+    // The catalog knows audiobook1, but the user's market has no audiobooks.
+    if path == "/v1/audiobooks/audiobook1" {
+        return json_response(200, json!({"id": "audiobook1"}));
+    }
+    if path != "/v1/me/player/recently-played" {
+        return json_response(
+            404,
+            json!({"error": {"status": 404, "message": "Not found"}}),
+        );
+    }
+    let mut track = track_fixture("track3");
+    track["artists"] = json!([]);
+    json_response(
+        200,
+        json!({
+            "href": "https://api.spotify.com/v1/me/player/recently-played",
+            "limit": 50,
+            "next": null,
+            "cursors": null,
+            // Played from a playlist the user cannot read.
+            "items": [{
+                "played_at": "2024-05-01T08:00:00Z",
+                "context": {"type": "playlist", "uri": "spotify:playlist:hidden"},
+                "track": track,
+            }],
+        }),
+    )
+}
+
+fn json_response(status: u16, body: Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json; charset=utf-8")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        (key == name).then(|| value.replace("%2C", ","))
+    })
+}
+
+fn stats_page(items: Vec<Value>) -> Value {
+    json!({
+        "total": items.len(),
+        "limit": 20,
+        "offset": 0,
+        "href": "https://api.spotify.com/v1/me/top",
+        "next": null,
+        "previous": null,
+        "items": items,
+    })
+}
+
+/// A page of items with the given explicit flags (`None` for unavailable
+/// items), followed by offset 100 of `next_path` if given.
+fn explicit_page(flags: &[Option<bool>], next_path: Option<&str>) -> Value {
+    let items: Vec<Value> = flags
+        .iter()
+        .map(|flag| match flag {
+            Some(explicit) => json!({"track": {"explicit": explicit}}),
+            None => json!({"track": null}),
+        })
+        .collect();
+    let next = next_path.map(|path| {
+        format!(
+            "{}{}?offset=100",
+            env::var("SPOTIFY_API_URL").unwrap(),
+            path
+        )
+    });
+    json!({"limit": 100, "offset": 0, "total": items.len(), "next": next, "items": items})
+}
+
+fn added_by_page(additions: &[(&str, &str)]) -> Value {
+    let items: Vec<Value> = additions
+        .iter()
+        .map(|(user, track)| json!({"added_by": {"id": user}, "track": {"id": track}}))
+        .collect();
+    json!({"limit": 100, "offset": 0, "total": items.len(), "next": null, "items": items})
+}
+
+fn playlist_page(tracks: Vec<Value>) -> Value {
+    let items: Vec<Value> = tracks
+        .into_iter()
+        .map(|track| json!({"track": track}))
+        .collect();
+    json!({"limit": 100, "offset": 0, "total": items.len(), "next": null, "items": items})
+}
+
+fn intervals(count: usize) -> Vec<Value> {
+    (0..count)
+        .map(|i| json!({"start": i as f64, "duration": 1.0, "confidence": 0.5}))
+        .collect()
+}
+
+fn artist_fixture(id: &str) -> Value {
+    json!({
+        "external_urls": {"spotify": format!("https://open.spotify.com/artist/{}", id)},
+        "href": format!("https://api.spotify.com/v1/artists/{}", id),
+        "id": id,
+        "name": format!("Artist {}", id),
+        "type": "artist",
+        "uri": format!("spotify:artist:{}", id),
+    })
+}
+
+fn track_fixture(id: &str) -> Value {
+    json!({
+        "album": {
+            "album_type": "album",
+            "artists": [artist_fixture("artist1")],
+            "available_markets": ["US"],
+            "external_urls": {"spotify": "https://open.spotify.com/album/album1"},
+            "href": "https://api.spotify.com/v1/albums/album1",
+            "id": "album1",
+            "images": [],
+            "name": "Album album1",
+            "release_date": "2024-05-01",
+            "release_date_precision": "day",
+            "total_tracks": 2,
+            "type": "album",
+            "uri": "spotify:album:album1",
+        },
+        "artists": [artist_fixture("artist1")],
+        "available_markets": ["US"],
+        "disc_number": 1,
+        "duration_ms": 200000,
+        "explicit": false,
+        "external_ids": {"isrc": "USXXX2400001"},
+        "external_urls": {"spotify": format!("https://open.spotify.com/track/{}", id)},
+        "href": format!("https://api.spotify.com/v1/tracks/{}", id),
+        "id": id,
+        "is_local": false,
+        "name": format!("Track {}", id),
+        "popularity": 50,
+        "preview_url": null,
+        "track_number": 1,
+        "type": "track",
+        "uri": format!("spotify:track:{}", id),
+    })
+}
+
+// --- Request helpers ------------------------------------------------------
+
+/// Encodes a string the way a Noir circuit passes it: one hex field per character.
+fn hex_chars(value: &str) -> Vec<String> {
+    value
+        .chars()
+        .map(|c| format!("0x{:02x}", c as u32))
+        .collect()
+}
+
+fn hex_number(value: u64) -> Vec<String> {
+    vec![format!("0x{:x}", value)]
+}
+
+/// Calls `method` as the admin, as every call the tests make may.
+async fn call(method: &str, params: Value) -> Value {
+    call_as(Some(format!("Bearer {}", ADMIN_TOKEN)), method, params).await
+}
+
+/// Calls `method` with `authorization` as the request's `Authorization` header.
+async fn call_as(authorization: Option<String>, method: &str, params: Value) -> Value {
+    let request = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params});
+    let response = AUTHORIZATION
+        .scope(
+            authorization,
+            create_io().handle_request(&request.to_string()),
+        )
+        .await
+        .expect("a response for a request with an id");
+    serde_json::from_str(&response).unwrap()
+}
+
+async fn resolve(function: &str, inputs: Vec<Vec<String>>) -> Value {
+    call(
+        "resolve_foreign_call",
+        json!([{"function": function, "inputs": inputs}]),
+    )
+    .await
+}
+
+/// Resolves a claim against an oracle whose clock is stopped at `now`.
+async fn resolve_at(now: i64, function: &str, inputs: Vec<Vec<String>>) -> Value {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "resolve_foreign_call",
+        "params": [{"function": function, "inputs": inputs}],
+    });
+    let response = create_io_with_clock(Arc::new(FixedClock(now)))
+        .handle_request(&request.to_string())
+        .await
+        .expect("a response for a request with an id");
+    serde_json::from_str(&response).unwrap()
+}
+
+async fn store_test_key() {
+    let response = call("store_key", json!([KEY, TOKEN])).await;
+    assert_eq!(response["result"], json!(KEY), "store_key: {}", response);
+}
+
+// --- Tests ----------------------------------------------------------------
+
+#[tokio::test]
+async fn store_and_delete_key_round_trip() {
+    mock_backends();
+
+    store_test_key().await;
+    let response = call("delete_key", json!(["key-to-delete"])).await;
+    assert_eq!(response["result"], json!("key-to-delete"), "{}", response);
+}
+
+#[tokio::test]
+async fn store_reference_list_returns_id() {
+    mock_backends();
+
+    let response = call(
+        "store_reference_list",
+        json!(["favourites", ["track1", "track9"]]),
+    )
+    .await;
+    assert_eq!(response["result"], json!("favourites"), "{}", response);
+}
+
+#[tokio::test]
+async fn every_claim_answers_with_values_and_validity_window() {
+    mock_backends();
+    store_test_key().await;
+    call("store_reference_list", json!(["stored", ["track1"]])).await;
+    let response = call("store_key", json!([NO_ARTIST_KEY, NO_ARTIST_TOKEN])).await;
+    assert_eq!(response["result"], json!(NO_ARTIST_KEY), "{}", response);
+    call("store_key", json!([FAN_KEY, FAN_TOKEN])).await;
+
+    let key = hex_chars(KEY);
+    let cases: Vec<(&str, Vec<Vec<String>>, Value)> = vec![
+        (
+            "can_claim_top_track",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(0),
+                hex_number(20),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_track_in_all_top_ranges",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(0),
+                hex_number(20),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_track_in_all_top_ranges",
+            vec![
+                key.clone(),
+                hex_chars("track9"),
+                hex_number(0),
+                hex_number(20),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_top_artist",
+            vec![
+                key.clone(),
+                hex_chars("artist1"),
+                hex_number(1),
+                hex_number(20),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_artist_in_all_top_ranges",
+            vec![
+                key.clone(),
+                hex_chars("artist1"),
+                hex_number(0),
+                hex_number(20),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_recently_played_track",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(0),
+                hex_number(1),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_library_overlap_with_reference_threshold",
+            vec![
+                key.clone(),
+                hex_chars("track1,other"),
+                hex_number(0),
+                hex_number(1),
+            ],
+            json!(["1"]),
+        ),
+        (
+            // One saved track listed three times is a single hit.
+            "can_claim_library_overlap_with_reference_threshold",
+            vec![
+                key.clone(),
+                hex_chars("track1,track1,track1"),
+                hex_number(0),
+                hex_number(3),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_library_overlap_with_reference_threshold",
+            vec![
+                key.clone(),
+                hex_chars("list:stored"),
+                hex_number(0),
+                hex_number(2),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_track_analysis_segments_count",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(3),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_track_analysis_beats_count",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(5),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_track_analysis_bars_count",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(2),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_track_analysis_tatums_count",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(8),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_track_analysis_summary",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["3", "4", "2", "8"]),
+        ),
+        (
+            // Counts above 255 saturate, so each fits its output byte.
+            "can_claim_track_analysis_summary",
+            vec![
+                key.clone(),
+                hex_chars("track-long"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["255", "255", "64", "255"]),
+        ),
+        (
+            "can_claim_track_analysis_start_of_fade_out",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(180),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_track_analysis_end_of_fade_in",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(1),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_recent_play_on_release_day",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_artist_top_track_in_user_history",
+            vec![
+                key.clone(),
+                hex_chars("artist1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_artist_top_track_in_user_history",
+            vec![
+                key.clone(),
+                hex_chars("artist2"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_multi_track_ordered_playthrough",
+            vec![
+                key.clone(),
+                hex_chars("album1"),
+                hex_number(0),
+                hex_number(5),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_saved_show_count_threshold",
+            vec![key.clone(), hex_chars(""), hex_number(5), hex_number(0)],
+            json!(["1"]),
+        ),
+        (
+            // track1 and track2 from history, track1 and track20 from every top range.
+            "can_claim_user_has_listened_to_n_tracks_total",
+            vec![key.clone(), hex_chars(""), hex_number(3), hex_number(0)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_user_has_listened_to_n_tracks_total",
+            vec![key.clone(), hex_chars(""), hex_number(4), hex_number(0)],
+            json!(["0"]),
+        ),
+        (
+            // Every fixture track and top artist is artist1.
+            "can_claim_user_has_listened_to_n_artists_total",
+            vec![key.clone(), hex_chars(""), hex_number(1), hex_number(0)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_user_has_listened_to_n_artists_total",
+            vec![key.clone(), hex_chars(""), hex_number(2), hex_number(0)],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_consecutive_same_artist_plays",
+            vec![
+                key.clone(),
+                hex_chars("artist1"),
+                hex_number(0),
+                hex_number(2),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_consecutive_same_artist_plays",
+            vec![
+                key.clone(),
+                hex_chars("artist1"),
+                hex_number(0),
+                hex_number(3),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_track_on_n_user_playlists",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(1),
+                hex_number(0),
+            ],
+            json!(["1", "1"]),
+        ),
+        (
+            "can_claim_track_on_n_user_playlists",
+            vec![
+                key.clone(),
+                hex_chars("track2"),
+                hex_number(3),
+                hex_number(0),
+            ],
+            json!(["0", "2"]),
+        ),
+        (
+            "can_claim_user_follows_n_artists",
+            vec![key.clone(), hex_chars(""), hex_number(2), hex_number(0)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_user_follows_n_artists",
+            vec![key.clone(), hex_chars(""), hex_number(3), hex_number(0)],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_playlist_follower_threshold",
+            vec![
+                key.clone(),
+                hex_chars("playlist1"),
+                hex_number(40),
+                hex_number(1),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_playlist_follower_threshold",
+            vec![
+                key.clone(),
+                hex_chars("playlist3"),
+                hex_number(1000),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_user_has_n_saved_tracks",
+            vec![key.clone(), hex_chars(""), hex_number(120), hex_number(0)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_user_has_n_saved_tracks",
+            vec![key.clone(), hex_chars(""), hex_number(121), hex_number(0)],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_user_has_n_saved_albums",
+            vec![key.clone(), hex_chars(""), hex_number(12), hex_number(0)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_user_has_n_saved_albums",
+            vec![key.clone(), hex_chars(""), hex_number(13), hex_number(0)],
+            json!(["0"]),
+        ),
+        (
+            // track1 was played, then track2 right after it.
+            "can_claim_history_contains_full_reference_sequence",
+            vec![
+                key.clone(),
+                hex_chars("track1,track2"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_history_contains_full_reference_sequence",
+            vec![
+                key.clone(),
+                hex_chars("track2,track1"),
+                hex_number(0),
+                hex_number(255),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_user_has_n_playlists",
+            vec![key.clone(), hex_chars(""), hex_number(2), hex_number(0)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_user_has_n_playlists",
+            vec![key.clone(), hex_chars(""), hex_number(3), hex_number(0)],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_user_has_n_followed_podcasts",
+            vec![key.clone(), hex_chars(""), hex_number(7), hex_number(0)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_user_has_n_followed_podcasts",
+            vec![key.clone(), hex_chars(""), hex_number(8), hex_number(0)],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_artist_has_n_followers",
+            vec![key.clone(), hex_chars("artist1"), hex_number(1500)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_artist_has_n_followers",
+            vec![key.clone(), hex_chars("artist1"), hex_number(1501)],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_top_tracks_stability",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(0),
+                hex_number(2),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_top_tracks_stability",
+            vec![
+                key.clone(),
+                hex_chars("track2"),
+                hex_number(0),
+                hex_number(2),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_track_is_podcast_episode",
+            vec![
+                key.clone(),
+                hex_chars("episode1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            // Music tracks are not found under /v1/episodes.
+            "can_claim_track_is_podcast_episode",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_episode_in_show",
+            vec![
+                key.clone(),
+                hex_chars("episode1"),
+                hex_chars("show1"),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_episode_in_show",
+            vec![
+                key.clone(),
+                hex_chars("episode1"),
+                hex_chars("show2"),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_show_in_saved_shows",
+            vec![
+                key.clone(),
+                hex_chars("show1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_show_in_saved_shows",
+            vec![
+                key.clone(),
+                hex_chars("show2"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_episode_listened_to_completion",
+            vec![
+                key.clone(),
+                hex_chars("episode1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_episode_listened_to_completion",
+            vec![
+                key.clone(),
+                hex_chars("episode2"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_audiobook_saved",
+            vec![
+                key.clone(),
+                hex_chars("audiobook1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_audiobook_saved",
+            vec![
+                key.clone(),
+                hex_chars("audiobook2"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            // Completed chapters count as 100%.
+            "can_claim_audiobook_chapter_listened",
+            vec![
+                key.clone(),
+                hex_chars("audiobook1"),
+                hex_chars("chapter1"),
+                hex_number(100),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_audiobook_chapter_listened",
+            vec![
+                key.clone(),
+                hex_chars("audiobook1"),
+                hex_chars("chapter2"),
+                hex_number(50),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_audiobook_chapter_listened",
+            vec![
+                key.clone(),
+                hex_chars("audiobook1"),
+                hex_chars("chapter2"),
+                hex_number(51),
+            ],
+            json!(["0"]),
+        ),
+        (
+            // Never started.
+            "can_claim_audiobook_chapter_listened",
+            vec![
+                key.clone(),
+                hex_chars("audiobook1"),
+                hex_chars("chapter3"),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_track_available_in_n_markets",
+            vec![key.clone(), hex_chars("track1"), hex_number(1)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_track_available_in_n_markets",
+            vec![key.clone(), hex_chars("track1"), hex_number(184)],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_album_available_in_n_markets",
+            vec![key.clone(), hex_chars("album1"), hex_number(3)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_album_available_in_n_markets",
+            vec![key.clone(), hex_chars("album1"), hex_number(4)],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_artist_markets_count",
+            vec![key.clone(), hex_chars("artist1"), hex_number(2)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_artist_markets_count",
+            vec![key.clone(), hex_chars("artist1"), hex_number(3)],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_track_not_in_recently_played",
+            vec![
+                key.clone(),
+                hex_chars("track9"),
+                hex_number(0),
+                hex_number(1),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_track_not_in_recently_played",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(0),
+                hex_number(1),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_artist_not_recently_played",
+            vec![
+                key.clone(),
+                hex_chars("artist9"),
+                hex_number(0),
+                hex_number(1),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_artist_not_recently_played",
+            vec![
+                key.clone(),
+                hex_chars("artist1"),
+                hex_number(0),
+                hex_number(1),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_genre_not_in_top_artists",
+            vec![
+                key.clone(),
+                hex_chars("death metal"),
+                hex_number(1),
+                hex_number(20),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_genre_not_in_top_artists",
+            vec![
+                key.clone(),
+                hex_chars("Indie Pop"),
+                hex_number(1),
+                hex_number(20),
+            ],
+            json!(["0"]),
+        ),
+        (
+            // 2019-06-01 and 2019-04-01, in milliseconds.
+            "can_claim_first_saved_track_before",
+            vec![
+                key.clone(),
+                hex_chars(""),
+                hex_number(1_559_347_200_000),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_first_saved_track_before",
+            vec![
+                key.clone(),
+                hex_chars(""),
+                hex_number(1_554_076_800_000),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_listening_location",
+            vec![
+                key.clone(),
+                hex_chars("berlin"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            // Only whole words of a device name match.
+            "can_claim_listening_location",
+            vec![key.clone(), hex_chars("Rome"), hex_number(0), hex_number(0)],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_track_royalty_free",
+            vec![
+                key.clone(),
+                hex_chars("track2"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_track_royalty_free",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_track_covers_original",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_chars(ORIGINAL_RECORDING),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_track_covers_original",
+            vec![key.clone(), hex_chars("track1"), hex_chars(OTHER_RECORDING)],
+            json!(["0"]),
+        ),
+        (
+            // A recording is not a cover of itself.
+            "can_claim_track_covers_original",
+            vec![key.clone(), hex_chars("track1"), hex_chars(COVER_RECORDING)],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_track_same_recording",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_chars(&format!("musicbrainz:{}", COVER_RECORDING)),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_track_same_recording",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_chars(&format!("musicbrainz:{}", ORIGINAL_RECORDING)),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_track_sampling",
+            vec![key.clone(), hex_chars("track1"), hex_chars("track3")],
+            json!(["1"]),
+        ),
+        (
+            // Sampling is directed.
+            "can_claim_track_sampling",
+            vec![key.clone(), hex_chars("track3"), hex_chars("track1")],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_playlist_collaborative_with_user",
+            vec![
+                key.clone(),
+                hex_chars("playlist6"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_playlist_collaborative_with_user",
+            vec![
+                key.clone(),
+                hex_chars("playlist7"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            // Not collaborative, even though alice owns it.
+            "can_claim_playlist_collaborative_with_user",
+            vec![
+                key.clone(),
+                hex_chars("playlist1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_track_added_by_curator",
+            vec![
+                key.clone(),
+                hex_chars("playlist6"),
+                hex_chars("track1"),
+                hex_chars("curator"),
+            ],
+            json!(["1"]),
+        ),
+        (
+            // track2 is on the playlist, but alice added it.
+            "can_claim_track_added_by_curator",
+            vec![
+                key.clone(),
+                hex_chars("playlist6"),
+                hex_chars("track2"),
+                hex_chars("curator"),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_curator_track_count",
+            vec![
+                key.clone(),
+                hex_chars("playlist6"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["2"]),
+        ),
+        (
+            "can_claim_curator_track_count",
+            vec![
+                key.clone(),
+                hex_chars("playlist7"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_playlist_has_no_explicit_tracks",
+            vec![
+                key.clone(),
+                hex_chars("playlist10"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            // Answered without requesting the missing second page.
+            "can_claim_playlist_has_no_explicit_tracks",
+            vec![
+                key.clone(),
+                hex_chars("playlist11"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_top_tracks_all_by_same_artist",
+            vec![
+                key.clone(),
+                hex_chars("artist1"),
+                hex_number(0),
+                hex_number(20),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_top_tracks_all_by_same_artist",
+            vec![
+                key.clone(),
+                hex_chars("artist2"),
+                hex_number(0),
+                hex_number(20),
+            ],
+            json!(["0"]),
+        ),
+        (
+            // The top tracks are from 2024 and 1994.
+            "can_claim_top_tracks_span_multiple_decades",
+            vec![key.clone(), hex_number(2), hex_number(0), hex_number(20)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_top_tracks_span_multiple_decades",
+            vec![key.clone(), hex_number(3), hex_number(0), hex_number(20)],
+            json!(["0"]),
+        ),
+        (
+            // No fixture track is explicit.
+            "can_claim_top_tracks_all_explicit",
+            vec![key.clone(), hex_number(0), hex_number(20)],
+            json!(["0"]),
+        ),
+        (
+            // The second account's only top track is explicit.
+            "can_claim_top_tracks_all_explicit",
+            vec![hex_chars(NO_ARTIST_KEY), hex_number(0), hex_number(20)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_top_tracks_decade_count",
+            vec![key.clone(), hex_number(0), hex_number(1990), hex_number(20)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_top_tracks_decade_count",
+            vec![key.clone(), hex_number(0), hex_number(1980), hex_number(20)],
+            json!(["0"]),
+        ),
+        (
+            // album1 credits artist2 and artist3 alongside artist1.
+            "can_claim_artist_collaboration_count",
+            vec![key.clone(), hex_chars("artist1"), hex_number(2)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_artist_collaboration_count",
+            vec![key.clone(), hex_chars("artist1"), hex_number(3)],
+            json!(["0"]),
+        ),
+        (
+            // Both top tracks are by artist1, tagged with two genres: 1 bit.
+            "can_claim_genre_entropy",
+            vec![key.clone(), hex_number(0), hex_number(16), hex_number(20)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_genre_entropy",
+            vec![key.clone(), hex_number(0), hex_number(17), hex_number(20)],
+            json!(["0"]),
+        ),
+        (
+            // track1 of artist1's only album was played.
+            "can_claim_discography_completed",
+            vec![
+                key.clone(),
+                hex_chars("artist1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_discography_completed",
+            vec![
+                key.clone(),
+                hex_chars("artist2"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            // artist1 is a top artist in every range.
+            "can_claim_listening_evolved",
+            vec![
+                key.clone(),
+                hex_chars("artist1"),
+                hex_number(20),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_listening_evolved",
+            vec![
+                hex_chars(NO_ARTIST_KEY),
+                hex_chars("artist2"),
+                hex_number(20),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_listening_evolved",
+            vec![
+                hex_chars(NO_ARTIST_KEY),
+                hex_chars("artist2"),
+                hex_number(20),
+                hex_number(1),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_track_repeated_listening",
+            vec![key.clone(), hex_chars("track1"), hex_number(1)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_track_repeated_listening",
+            vec![key.clone(), hex_chars("track1"), hex_number(2)],
+            json!(["0"]),
+        ),
+        (
+            // The two recent plays are four minutes apart.
+            "can_claim_multi_track_session",
+            vec![key.clone(), hex_chars(""), hex_number(2), hex_number(0)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_multi_track_session",
+            vec![key.clone(), hex_chars(""), hex_number(3), hex_number(0)],
+            json!(["0"]),
+        ),
+        (
+            // Neither recent play is before 10:00 UTC.
+            "can_claim_track_in_morning_top",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(0),
+                hex_number(5),
+            ],
+            json!(["0"]),
+        ),
+        (
+            // The second account played track3 at 08:00 UTC.
+            "can_claim_track_in_morning_top",
+            vec![
+                hex_chars(NO_ARTIST_KEY),
+                hex_chars("track3"),
+                hex_number(0),
+                hex_number(1),
+            ],
+            json!(["1"]),
+        ),
+        (
+            // track1 was played from "Morning Gym Mix".
+            "can_claim_track_in_workout_context",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_track_in_workout_context",
+            vec![
+                key.clone(),
+                hex_chars("track2"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_shared_top_track",
+            vec![key.clone(), hex_chars("track1"), hex_chars(FAN_KEY)],
+            json!(["1"]),
+        ),
+        (
+            // The second account's only top track is track3.
+            "can_claim_shared_top_track",
+            vec![key.clone(), hex_chars("track1"), hex_chars(NO_ARTIST_KEY)],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_disjoint_top_artists",
+            vec![key.clone(), key.clone(), hex_number(0), hex_number(20)],
+            json!(["0"]),
+        ),
+        (
+            // artist1 against the second account's artist2.
+            "can_claim_disjoint_top_artists",
+            vec![
+                key.clone(),
+                hex_chars(NO_ARTIST_KEY),
+                hex_number(0),
+                hex_number(20),
+            ],
+            json!(["1"]),
+        ),
+        (
+            // The fixture ISRC is on Apple Music and MusicBrainz.
+            "can_claim_track_exclusive_to_spotify",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_track_exclusive_to_spotify",
+            vec![
+                key.clone(),
+                hex_chars("original1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_track_preview_url_accessible",
+            vec![
+                key.clone(),
+                hex_chars("preview1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            // Listed, but refused with a 403.
+            "can_claim_track_preview_url_accessible",
+            vec![
+                key.clone(),
+                hex_chars("preview2"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            // No preview_url at all.
+            "can_claim_track_preview_url_accessible",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_artist_tour_announced",
+            vec![key.clone(), hex_chars("artist1"), hex_chars("510")],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_artist_tour_announced",
+            vec![key.clone(), hex_chars("artist2"), hex_chars("511")],
+            json!(["0"]),
+        ),
+        (
+            // Resolved to 510 by the artist's name.
+            "can_claim_artist_tour_announced",
+            vec![key.clone(), hex_chars("artist1"), hex_chars("")],
+            json!(["1"]),
+        ),
+        (
+            // The user is in the US.
+            "can_claim_event_in_user_city",
+            vec![key.clone(), hex_chars("510"), hex_chars("austin")],
+            json!(["1"]),
+        ),
+        (
+            // Berlin is not in the user's country.
+            "can_claim_event_in_user_city",
+            vec![key.clone(), hex_chars("510"), hex_chars("Berlin")],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_event_in_user_city",
+            vec![key.clone(), hex_chars("511"), hex_chars("Austin")],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_track_in_tv_show",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_chars("grey's anatomy"),
+            ],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_track_in_tv_show",
+            vec![key.clone(), hex_chars("track1"), hex_chars("The Office")],
+            json!(["0"]),
+        ),
+        (
+            // Not listed on TuneFind.
+            "can_claim_track_in_tv_show",
+            vec![
+                key.clone(),
+                hex_chars("track2"),
+                hex_chars("Grey's Anatomy"),
+            ],
+            json!(["0"]),
+        ),
+        (
+            "can_claim_track_has_music_video",
+            vec![
+                key.clone(),
+                hex_chars("track1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["1"]),
+        ),
+        (
+            // Only the artist's auto-generated Topic channel turns up.
+            "can_claim_track_has_music_video",
+            vec![
+                key.clone(),
+                hex_chars("track2"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            json!(["0"]),
+        ),
+        (
+            // Surrounding whitespace is not counted.
+            "can_claim_artist_biography_length",
+            vec![key.clone(), hex_chars("artist1"), hex_number(38)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_artist_biography_length",
+            vec![key.clone(), hex_chars("artist1"), hex_number(39)],
+            json!(["0"]),
+        ),
+        (
+            // Both recent plays are from album1, so it counts once.
+            "can_claim_distinct_albums_in_history_threshold",
+            vec![key.clone(), hex_chars(""), hex_number(0), hex_number(1)],
+            json!(["1"]),
+        ),
+        (
+            "can_claim_distinct_albums_in_history_threshold",
+            vec![key.clone(), hex_chars(""), hex_number(0), hex_number(2)],
+            json!(["0"]),
+        ),
+    ];
+
+    let signing_key = parse_signing_key(SIGNING_KEY).unwrap();
+    for (function, inputs, expected) in cases {
+        let response = resolve_at(TEST_NOW, function, inputs).await;
+        let result = &response["result"];
+
+        assert_eq!(result["values"], expected, "{}: {}", function, response);
+        let claim = CLAIMS.iter().find(|claim| claim.name == function).unwrap();
+        assert_eq!(result["evaluated_at"], json!(TEST_NOW), "{}", function);
+        assert_eq!(
+            result["valid_until"],
+            json!(TEST_NOW + claim.validity_secs),
+            "{}",
+            function
+        );
+        assert!(
+            verify(&signing_key, function, result),
+            "{}: {}",
+            function,
+            response
+        );
+    }
+}
+
+#[tokio::test]
+async fn validity_windows_are_bounded_and_covered_by_the_signature() {
+    mock_backends();
+    store_test_key().await;
+    let function = "can_claim_playlist_created_before";
+    let override_var = "CLAIM_VALIDITY_SECS_CAN_CLAIM_PLAYLIST_CREATED_BEFORE";
+    let default_secs = CLAIMS
+        .iter()
+        .find(|claim| claim.name == function)
+        .unwrap()
+        .validity_secs;
+    // 2020-06-01, in milliseconds; playlist4 has a track added before it.
+    let inputs = vec![
+        hex_chars(KEY),
+        hex_chars("playlist4"),
+        hex_number(1_590_969_600_000),
+    ];
+
+    // The shortest window is a second; an answer is never issued expired.
+    let cases = [
+        ("1", TEST_NOW + 1),
+        ("0", TEST_NOW + default_secs),
+        ("-60", TEST_NOW + default_secs),
+        ("9223372036854775807", i64::MAX),
+    ];
+    for (secs, valid_until) in cases {
+        env::set_var(override_var, secs);
+        let response = resolve_at(TEST_NOW, function, inputs.clone()).await;
+        let result = &response["result"];
+        assert_eq!(result["values"], json!(["1"]), "{}", response);
+        assert_eq!(result["evaluated_at"], json!(TEST_NOW), "{}", secs);
+        assert_eq!(result["valid_until"], json!(valid_until), "{}", secs);
+    }
+    env::remove_var(override_var);
+
+    let response = resolve_at(TEST_NOW, function, inputs).await;
+    let answer = &response["result"];
+    let signing_key = parse_signing_key(SIGNING_KEY).unwrap();
+    assert!(verify(&signing_key, function, answer), "{}", response);
+    assert!(!verify(
+        &parse_encryption_key(ENCRYPTION_KEY).unwrap(),
+        function,
+        answer
+    ));
+    assert!(!verify(&signing_key, "can_claim_top_track", answer));
+
+    let mut stretched = answer.clone();
+    stretched["valid_until"] = json!(TEST_NOW + 2 * default_secs);
+    assert!(!verify(&signing_key, function, &stretched));
+    let mut backdated = answer.clone();
+    backdated["evaluated_at"] = json!(TEST_NOW - 1);
+    assert!(!verify(&signing_key, function, &backdated));
+
+    assert!(check_signing_config(None).is_ok());
+    assert!(check_signing_config(Some(SIGNING_KEY)).is_ok());
+    assert!(check_signing_config(Some("0123")).is_err());
+}
+
+#[tokio::test]
+async fn claim_with_unknown_key_is_an_invalid_params_error() {
+    mock_backends();
+
+    let inputs = vec![
+        hex_chars("nobody"),
+        hex_chars("track1"),
+        hex_number(0),
+        hex_number(20),
+    ];
+    let response = resolve("can_claim_top_track", inputs).await;
+    assert_eq!(response["error"]["code"], json!(-32602), "{}", response);
+}
+
+#[tokio::test]
+async fn unknown_function_is_rejected_as_invalid_method() {
+    mock_backends();
+
+    let inputs = vec![
+        hex_chars(KEY),
+        hex_chars("track1"),
+        hex_number(0),
+        hex_number(20),
+    ];
+    let response = resolve("unknown_function_name", inputs).await;
+
+    assert_eq!(
+        response["error"],
+        serde_json::to_value(Error::invalid_params("Invalid method")).unwrap(),
+        "{}",
+        response
+    );
+    assert_eq!(
+        response["error"]["code"],
+        json!(ErrorCode::InvalidParams.code()),
+        "{}",
+        response
+    );
+}
+
+#[tokio::test]
+async fn store_key_rejects_empty_id() {
+    mock_backends();
+
+    let response = call("store_key", json!(["", "some_token"])).await;
+    assert_eq!(
+        response["error"],
+        serde_json::to_value(Error::invalid_params("ID or token cannot be empty")).unwrap(),
+        "{}",
+        response
+    );
+}
+
+#[tokio::test]
+async fn store_key_rejects_empty_token() {
+    mock_backends();
+
+    let response = call("store_key", json!(["some_id", ""])).await;
+    assert_eq!(
+        response["error"],
+        serde_json::to_value(Error::invalid_params("ID or token cannot be empty")).unwrap(),
+        "{}",
+        response
+    );
+}
+
+#[tokio::test]
+async fn store_key_rejects_reserved_and_account_ids() {
+    mock_backends();
+
+    for id in [
+        "accounts:alice",
+        "event:alice",
+        "reference_list:top",
+        "playlist_snapshot:playlist1",
+        "provider_usage:spotify",
+        "alice:work",
+    ] {
+        let expected =
+            serde_json::to_value(Error::invalid_params("ID cannot contain `:`")).unwrap();
+        let response = call("store_key", json!([id, "some_token"])).await;
+        assert_eq!(response["error"], expected, "{}: {}", id, response);
+        let response = call("rotate_token", json!([id, "some_tok", "new_token"])).await;
+        assert_eq!(response["error"], expected, "{}: {}", id, response);
+    }
+}
+
+#[tokio::test]
+async fn delete_key_is_idempotent_for_unknown_keys() {
+    mock_backends();
+
+    let response = call("delete_key", json!(["nonexistent_key_12345"])).await;
+    assert_eq!(
+        response["result"],
+        json!("nonexistent_key_12345"),
+        "{}",
+        response
+    );
+}
+
+#[test]
+fn claim_ids_and_names_are_unique() {
+    let mut ids = HashSet::new();
+    let mut names = HashSet::new();
+    for claim in CLAIMS {
+        assert!(ids.insert(claim.id), "claim id {} is used twice", claim.id);
+        assert!(
+            names.insert(claim.name),
+            "claim {} is registered twice",
+            claim.name
+        );
+    }
+}
+
+#[tokio::test]
+async fn claims_can_be_called_by_numeric_id() {
+    mock_backends();
+    store_test_key().await;
+
+    let inputs = json!([
+        hex_chars(KEY),
+        hex_chars("track1"),
+        hex_number(0),
+        hex_number(20)
+    ]);
+    for function in [json!(1), json!("1"), json!("0x0001")] {
+        let response = call(
+            "resolve_foreign_call",
+            json!([{"function": function, "inputs": inputs}]),
+        )
+        .await;
+        assert_eq!(response["result"]["values"], json!(["1"]), "{}", response);
+    }
+}
+
+#[tokio::test]
+async fn list_claims_maps_ids_to_names() {
+    mock_backends();
+
+    let response = call("list_claims", json!([])).await;
+    let claims = response["result"].as_array().expect("a list of claims");
+    assert_eq!(claims.len(), CLAIMS.len());
+    assert_eq!(claims[0]["id"], json!(1));
+    assert_eq!(claims[0]["name"], json!("can_claim_top_track"));
+    assert_eq!(claims[0]["deprecated"], json!(false));
+}
+
+#[tokio::test]
+async fn played_during_event_resolves_the_stored_window() {
+    mock_backends();
+    store_test_key().await;
+
+    // 2024-05-01T09:00:00Z to 11:00:00Z; track1 was played at 10:00.
+    let response = call(
+        "upsert_event",
+        json!([
+            "listening-party",
+            "track1",
+            1714554000000_i64,
+            1714561200000_i64,
+            3600
+        ]),
+    )
+    .await;
+    assert_eq!(response["result"], json!("listening-party"), "{}", response);
+
+    let inputs = vec![
+        hex_chars(KEY),
+        hex_chars("listening-party"),
+        hex_number(0),
+        hex_number(0),
+    ];
+    let response = resolve("can_claim_played_during_event", inputs).await;
+    assert_eq!(response["result"]["values"], json!(["1"]), "{}", response);
+    assert_eq!(
+        response["result"]["event"]["track_id"],
+        json!("track1"),
+        "{}",
+        response
+    );
+}
+
+#[tokio::test]
+async fn played_during_unknown_event_is_rejected() {
+    mock_backends();
+    store_test_key().await;
+
+    // Without the admin token nobody can publish an event for claims to match.
+    let forged = json!(["forged-event", "track1", 0, i64::MAX, 3600]);
+    let response = call_as(None, "upsert_event", forged).await;
+    assert_eq!(response["error"]["code"], json!(-32001), "{}", response);
+
+    for event in ["no-such-event", "forged-event"] {
+        let inputs = vec![
+            hex_chars(KEY),
+            hex_chars(event),
+            hex_number(0),
+            hex_number(0),
+        ];
+        let response = resolve("can_claim_played_during_event", inputs).await;
+        assert_eq!(
+            response["error"]["message"],
+            json!("Invalid parameters: Unknown or expired event"),
+            "{}",
+            response
+        );
+    }
+}
+
+#[tokio::test]
+async fn storage_usage_reports_keys_per_feature() {
+    mock_backends();
+    store_test_key().await;
+    call(
+        "store_reference_list",
+        json!(["usage", ["track1", "track2"]]),
+    )
+    .await;
+
+    let response = call("storage_usage", json!([])).await;
+    let report = &response["result"];
+    for feature in ["tokens", "reference_lists", "events"] {
+        assert!(
+            report[feature]["keys"].is_u64(),
+            "{}: {}",
+            feature,
+            response
+        );
+    }
+    assert!(
+        report["tokens"]["keys"].as_u64().unwrap() >= 1,
+        "{}",
+        response
+    );
+    assert!(
+        report["reference_lists"]["bytes"].as_u64().unwrap() >= 12,
+        "{}",
+        response
+    );
+
+    let metrics = reqwest::get(format!("{}/metrics", oracle_url()))
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(
+        metrics.contains("oracle_storage_keys{feature=\"tokens\"}"),
+        "{}",
+        metrics
+    );
+}
+
+#[tokio::test]
+async fn storage_usage_sizes_a_bounded_sample_of_keys() {
+    mock_backends();
+    let client =
+        redis::Client::open(format!("redis://{}", env::var("REDIS_HOSTNAME").unwrap())).unwrap();
+    let mut conn = client.get_connection().unwrap();
+    let mut pipe = redis::pipe();
+    for i in 0..150 {
+        pipe.cmd("SET")
+            .arg(format!("event:sampled-{}", i))
+            .arg("0123456789")
+            .ignore();
+    }
+    let _: () = pipe.query(&mut conn).unwrap();
+
+    let response = call("storage_usage", json!([])).await;
+    let events = &response["result"]["events"];
+    let keys = events["keys"].as_u64().unwrap();
+    assert!(keys >= 150, "{}", response);
+    assert_eq!(events["sampled"], json!(100), "{}", response);
+    // Every event takes at least 10 bytes, so the extrapolation does too.
+    assert!(
+        events["bytes"].as_u64().unwrap() >= keys * 10,
+        "{}",
+        response
+    );
+}
+
+#[tokio::test]
+async fn artist_top_track_claim_rejects_unknown_artists() {
+    mock_backends();
+    store_test_key().await;
+
+    let inputs = vec![
+        hex_chars(KEY),
+        hex_chars("no-such-artist"),
+        hex_number(0),
+        hex_number(0),
+    ];
+    let response = resolve("can_claim_artist_top_track_in_user_history", inputs).await;
+    assert_eq!(
+        response["error"]["message"],
+        json!("Invalid parameters: Unknown artist: no-such-artist"),
+        "{}",
+        response
+    );
+}
+
+#[tokio::test]
+async fn music_video_answers_are_cached_per_track() {
+    mock_backends();
+    store_test_key().await;
+
+    let inputs = vec![
+        hex_chars(KEY),
+        hex_chars("video-cached"),
+        hex_number(0),
+        hex_number(0),
+    ];
+    for _ in 0..2 {
+        let response = resolve("can_claim_track_has_music_video", inputs.clone()).await;
+        assert_eq!(response["result"]["values"], json!(["0"]), "{}", response);
+    }
+    assert_eq!(CACHED_VIDEO_SEARCHES.load(Ordering::SeqCst), 1);
+
+    let mut unknown = inputs.clone();
+    unknown[1] = hex_chars("no-such-track");
+    let response = resolve("can_claim_track_has_music_video", unknown).await;
+    assert_eq!(
+        response["error"]["message"],
+        json!("Invalid parameters: Unknown track: no-such-track"),
+        "{}",
+        response
+    );
+
+    // A cached answer still needs a session Spotify accepts.
+    call("store_key", json!(["revoked", "Bearer revoked-token"])).await;
+    let mut inputs = inputs;
+    inputs[0] = hex_chars("revoked");
+    let response = resolve("can_claim_track_has_music_video", inputs).await;
+    assert!(response["result"].is_null(), "{}", response);
+    assert_eq!(CACHED_VIDEO_SEARCHES.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn provider_usage_counts_calls_per_claim_and_endpoint_template() {
+    mock_backends();
+    store_test_key().await;
+
+    // One top-tracks call per time range.
+    let inputs = vec![
+        hex_chars(KEY),
+        hex_chars("track1"),
+        hex_number(0),
+        hex_number(20),
+    ];
+    resolve("can_claim_track_in_all_top_ranges", inputs).await;
+    // Album tracks, then recently played.
+    let inputs = vec![
+        hex_chars(KEY),
+        hex_chars("album1"),
+        hex_number(0),
+        hex_number(5),
+    ];
+    resolve("can_claim_multi_track_ordered_playthrough", inputs).await;
+
+    let today = chrono::Utc::now().date_naive().to_string();
+    let response = call("provider_usage", json!([today, today])).await;
+    let records = response["result"].as_array().expect("usage records");
+    let count = |endpoint: &str, function: &str| -> u64 {
+        records
+            .iter()
+            .filter(|record| {
+                record["provider"] == "spotify"
+                    && record["endpoint"] == endpoint
+                    && record["function"] == function
+                    && record["outcome"] == "success"
+            })
+            .map(|record| record["count"].as_u64().unwrap())
+            .sum()
+    };
+
+    assert!(
+        count("/v1/me/top/tracks", "can_claim_track_in_all_top_ranges") >= 3,
+        "{}",
+        response
+    );
+    assert!(
+        count(
+            "/v1/albums/{id}/tracks",
+            "can_claim_multi_track_ordered_playthrough"
+        ) >= 1,
+        "{}",
+        response
+    );
+    assert!(
+        count(
+            "/v1/me/player/recently-played",
+            "can_claim_multi_track_ordered_playthrough"
+        ) >= 1,
+        "{}",
+        response
+    );
+    assert!(
+        records
+            .iter()
+            .all(|record| !record["endpoint"].as_str().unwrap().contains("album1")),
+        "{}",
+        response
+    );
+}
+
+#[test]
+fn window_errors_are_never_masked() {
+    let failed = || Err::<bool, _>("Request failed with status: 500".to_string());
+
+    assert_eq!(all_windows_hold([Ok(true), Ok(true)]), Ok(true));
+    assert_eq!(all_windows_hold([Ok(true), Ok(false)]), Ok(false));
+    // An error wins whether the other window answered true or false.
+    assert_eq!(all_windows_hold([Ok(true), failed()]), failed());
+    assert_eq!(all_windows_hold([failed(), Ok(true)]), failed());
+    assert_eq!(all_windows_hold([Ok(false), failed()]), failed());
+    assert_eq!(all_windows_hold([failed(), Ok(false)]), failed());
+}
+
+/// Starts the oracle's own HTTP server on a free port, once.
+fn oracle_url() -> String {
+    static URL: OnceLock<String> = OnceLock::new();
+    URL.get_or_init(|| {
+        let server = start_server(create_io(), &"127.0.0.1:0".parse().unwrap())
+            .expect("start oracle server");
+        let url = format!("http://{}", server.address());
+        // Keep serving for the rest of the test run.
+        std::mem::forget(server);
+        url
+    })
+    .clone()
+}
+
+#[tokio::test]
+async fn client_round_trips_against_the_server() {
+    mock_backends();
+    let client = OracleClient::new(oracle_url()).with_admin_token(ADMIN_TOKEN);
+
+    assert_eq!(client.store_key(KEY, TOKEN).await.unwrap(), KEY);
+
+    let claims = [
+        (
+            ClaimParams::TopTrack {
+                track_id: "track1".to_string(),
+                time_range: TimeRange::MediumTerm,
+                list_range: 20,
+            },
+            vec!["1"],
+        ),
+        (
+            ClaimParams::TrackAnalysisSummary {
+                track_id: "track1".to_string(),
+            },
+            vec!["3", "4", "2", "8"],
+        ),
+        (
+            ClaimParams::DistinctAlbumsInHistory {
+                after: 0,
+                threshold: 2,
+            },
+            vec!["0"],
+        ),
+        (
+            ClaimParams::TrackOnPlaylists {
+                track_id: "track2".to_string(),
+                threshold: 2,
+            },
+            vec!["1", "2"],
+        ),
+    ];
+    for (claim, expected) in claims {
+        let response = client.resolve_claim(KEY, &claim).await.unwrap();
+        assert_eq!(response.values, expected, "{:?}", claim);
+        assert!(response.valid_until > response.evaluated_at);
+    }
+
+    client
+        .upsert_event("client-event", "track2", 1714554000000, 1714561200000, 60)
+        .await
+        .unwrap();
+    let claim = ClaimParams::PlayedDuringEvent {
+        event_id: "client-event".to_string(),
+    };
+    let response = client.resolve_claim(KEY, &claim).await.unwrap();
+    assert_eq!(response.values, vec!["1"]);
+    assert_eq!(response.extra["event"]["track_id"], json!("track2"));
+
+    let claims = client.list_claims().await.unwrap();
+    assert_eq!(claims.len(), CLAIMS.len());
+
+    match client.resolve_claim("nobody", &claim).await {
+        Err(ClientError::Rpc(error)) => assert_eq!(error.code, ErrorCode::InvalidParams),
+        other => panic!("expected an RPC error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn playlist_follower_claim_errors_when_undeterminable_or_not_owned() {
+    mock_backends();
+    store_test_key().await;
+
+    let cases = [
+        (
+            "playlist2",
+            0,
+            "Invalid parameters: indeterminate: Spotify reports no follower count for playlist playlist2",
+        ),
+        (
+            "playlist3",
+            1,
+            "Invalid parameters: Playlist playlist3 is not owned by this user",
+        ),
+        (
+            "playlist9",
+            0,
+            "Invalid parameters: Playlist playlist9 is not visible to this user",
+        ),
+    ];
+    for (playlist, mode, message) in cases {
+        let inputs = vec![
+            hex_chars(KEY),
+            hex_chars(playlist),
+            hex_number(1),
+            hex_number(mode),
+        ];
+        let response = resolve("can_claim_playlist_follower_threshold", inputs).await;
+        assert_eq!(response["error"]["message"], json!(message), "{}", response);
+    }
+}
+
+#[tokio::test]
+async fn tv_show_claim_fails_on_an_unexpected_tunefind_response() {
+    mock_backends();
+    store_test_key().await;
+
+    let inputs = vec![
+        hex_chars(KEY),
+        hex_chars("track3"),
+        hex_chars("Grey's Anatomy"),
+    ];
+    let response = resolve("can_claim_track_in_tv_show", inputs).await;
+    assert!(response["result"].is_null(), "{}", response);
+    assert!(
+        response["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("decoding"),
+        "{}",
+        response
+    );
+}
+
+#[tokio::test]
+async fn tour_claim_requires_the_concert_artist_to_match() {
+    mock_backends();
+    store_test_key().await;
+
+    let inputs = vec![hex_chars(KEY), hex_chars("artist2"), hex_chars("510")];
+    let response = resolve("can_claim_artist_tour_announced", inputs).await;
+    assert_eq!(
+        response["error"]["message"],
+        json!("Invalid parameters: Concert artist 510 is Artist artist1, not Artist artist2"),
+        "{}",
+        response
+    );
+}
+
+#[tokio::test]
+async fn event_city_claim_fails_on_endless_calendars() {
+    mock_backends();
+    store_test_key().await;
+
+    let inputs = vec![hex_chars(KEY), hex_chars("512"), hex_chars("Austin")];
+    let response = resolve("can_claim_event_in_user_city", inputs).await;
+    assert_eq!(
+        response["error"]["message"],
+        json!(format!(
+            "Invalid parameters: Event calendar spans more than {} pages",
+            songkick::MAX_PAGES
+        )),
+        "{}",
+        response
+    );
+}
+
+#[tokio::test]
+async fn shared_top_track_rejects_repeated_keys_and_accounts() {
+    mock_backends();
+    store_test_key().await;
+    // Another key for the claimant's own account.
+    call("store_key", json!(["alias", TOKEN])).await;
+
+    let cases = [
+        (KEY, "Every key may only be given once"),
+        ("alias", "Every key must belong to a different account"),
+    ];
+    for (other_key, message) in cases {
+        let inputs = vec![hex_chars(KEY), hex_chars("track1"), hex_chars(other_key)];
+        let response = resolve("can_claim_shared_top_track", inputs).await;
+        assert_eq!(response["error"]["message"], json!(message), "{}", response);
+    }
+}
+
+#[tokio::test]
+async fn playlist_follower_counts_are_not_shared_between_sessions() {
+    mock_backends();
+    store_test_key().await;
+    call("store_key", json!([NO_ARTIST_KEY, NO_ARTIST_TOKEN])).await;
+
+    let inputs = |key: &str| {
+        vec![
+            hex_chars(key),
+            hex_chars("playlist1"),
+            hex_number(1),
+            hex_number(0),
+        ]
+    };
+    let response = resolve("can_claim_playlist_follower_threshold", inputs(KEY)).await;
+    assert_eq!(response["result"]["values"], json!(["1"]), "{}", response);
+
+    // The second account gets a 404 for the playlist, cached count or not.
+    let response = resolve(
+        "can_claim_playlist_follower_threshold",
+        inputs(NO_ARTIST_KEY),
+    )
+    .await;
+    assert_eq!(
+        response["error"]["message"],
+        json!("Invalid parameters: Playlist playlist1 is not visible to this user"),
+        "{}",
+        response
+    );
+}
+
+#[test]
+fn playlist_updates_are_bounded_by_snapshot_observations() {
+    let first = observe_playlist_snapshot(None, "snap1".to_string(), 1_000);
+    assert_eq!(first.changed_after, None);
+    assert!(can_claim_playlist_recently_updated(&first, "p", 500).is_err());
+    assert_eq!(
+        can_claim_playlist_recently_updated(&first, "p", 1_000).unwrap(),
+        "0"
+    );
+
+    let unchanged = observe_playlist_snapshot(Some(first), "snap1".to_string(), 5_000);
+    assert_eq!((unchanged.first_seen, unchanged.last_seen), (1_000, 5_000));
+    assert_eq!(
+        can_claim_playlist_recently_updated(&unchanged, "p", 2_000).unwrap(),
+        "0"
+    );
+
+    // Changed somewhere between 5_000 and 9_000.
+    let changed = observe_playlist_snapshot(Some(unchanged), "snap2".to_string(), 9_000);
+    assert_eq!(changed.changed_after, Some(5_000));
+    assert_eq!(
+        can_claim_playlist_recently_updated(&changed, "p", 4_000).unwrap(),
+        "1"
+    );
+    assert_eq!(
+        can_claim_playlist_recently_updated(&changed, "p", 9_000).unwrap(),
+        "0"
+    );
+    assert!(can_claim_playlist_recently_updated(&changed, "p", 7_000).is_err());
+}
+
+#[tokio::test]
+async fn playlist_recently_updated_claim_tracks_snapshots() {
+    mock_backends();
+    store_test_key().await;
+    let claim = |hours: u64| {
+        resolve(
+            "can_claim_playlist_recently_updated",
+            vec![hex_chars(KEY), hex_chars("playlist1"), hex_number(hours)],
+        )
+    };
+
+    // The first observation cannot tell when the playlist last changed.
+    let response = claim(24).await;
+    assert_eq!(
+        response["error"]["message"],
+        json!("Invalid parameters: indeterminate: playlist playlist1 was not observed before the start of the window"),
+        "{}",
+        response
+    );
+    let response = claim(0).await;
+    assert_eq!(response["result"]["values"], json!(["0"]), "{}", response);
+
+    // A different snapshot seen an hour ago means the playlist changed since.
+    let now = chrono::Utc::now().timestamp();
+    let previous = PlaylistSnapshot {
+        snapshot_id: "snap1".to_string(),
+        first_seen: now - 7200,
+        last_seen: now - 3600,
+        changed_after: None,
+    };
+    store_playlist_snapshot(
+        "playlist1".to_string(),
+        serde_json::to_string(&previous).unwrap(),
+        60,
+    )
+    .unwrap();
+    let response = claim(24).await;
+    assert_eq!(response["result"]["values"], json!(["1"]), "{}", response);
+}
+
+#[tokio::test]
+async fn playlist_created_before_claim_uses_earliest_added_track() {
+    mock_backends();
+    store_test_key().await;
+
+    let claim = |playlist: &str, before: u64| {
+        let inputs = vec![hex_chars(KEY), hex_chars(playlist), hex_number(before)];
+        resolve("can_claim_playlist_created_before", inputs)
+    };
+    // 2020-06-01 and 2020-01-01, in milliseconds.
+    let (june, january) = (1_590_969_600_000, 1_577_836_800_000);
+
+    let response = claim("playlist4", june).await;
+    assert_eq!(response["result"]["values"], json!(["1"]), "{}", response);
+
+    let response = claim("playlist4", january).await;
+    assert_eq!(
+        response["error"]["message"],
+        json!("Invalid parameters: indeterminate: no track of playlist playlist4 was added before the given time"),
+        "{}",
+        response
+    );
+
+    let response = claim("playlist5", june).await;
+    assert_eq!(
+        response["error"]["message"],
+        json!("Invalid parameters: indeterminate: playlist playlist5 has no tracks with a known added date"),
+        "{}",
+        response
+    );
+}
+
+#[tokio::test]
+async fn claims_from_blocked_markets_are_rejected() {
+    mock_backends();
+    let response = call(
+        "store_key",
+        json!([BLOCKED_MARKET_KEY, BLOCKED_MARKET_TOKEN]),
+    )
+    .await;
+    assert_eq!(
+        response["result"],
+        json!(BLOCKED_MARKET_KEY),
+        "{}",
+        response
+    );
+
+    let inputs = vec![
+        hex_chars(BLOCKED_MARKET_KEY),
+        hex_chars(""),
+        hex_number(1),
+        hex_number(0),
+    ];
+    let response = resolve("can_claim_user_has_n_saved_tracks", inputs).await;
+    assert_eq!(
+        response["error"],
+        json!({
+            "code": -32602,
+            "message": "Oracle access is not available in market CU",
+            "data": {"reason": "geo_restricted", "market": "CU"},
+        }),
+        "{}",
+        response
+    );
+}
+
+#[tokio::test]
+async fn first_saved_track_is_indeterminate_when_the_library_changes() {
+    mock_backends();
+    call("store_key", json!([FAN_KEY, FAN_TOKEN])).await;
+
+    let inputs = vec![
+        hex_chars(FAN_KEY),
+        hex_chars(""),
+        hex_number(1_559_347_200_000),
+        hex_number(0),
+    ];
+    let response = resolve("can_claim_first_saved_track_before", inputs).await;
+    assert_eq!(
+        response["error"]["message"],
+        json!("Invalid parameters: indeterminate: saved tracks changed while the oldest one was being read"),
+        "{}",
+        response
+    );
+}
+
+#[tokio::test]
+async fn every_user_of_a_claim_is_checked_against_blocked_markets() {
+    mock_backends();
+    store_test_key().await;
+    for (key, token) in [
+        (FAN_KEY, FAN_TOKEN),
+        (BLOCKED_MARKET_KEY, BLOCKED_MARKET_TOKEN),
+        (NO_COUNTRY_KEY, NO_COUNTRY_TOKEN),
+    ] {
+        let response = call("store_key", json!([key, token])).await;
+        assert_eq!(response["result"], json!(key), "{}", response);
+    }
+
+    // The other user of a comparison is checked, not just the claimant.
+    let inputs = vec![
+        hex_chars(KEY),
+        hex_chars(BLOCKED_MARKET_KEY),
+        hex_number(1),
+        hex_number(20),
+    ];
+    let response = resolve("can_claim_disjoint_top_artists", inputs).await;
+    assert_eq!(
+        response["error"]["data"],
+        json!({"reason": "geo_restricted", "market": "CU"}),
+        "{}",
+        response
+    );
+    let inputs = vec![
+        hex_chars(KEY),
+        hex_chars("track1"),
+        hex_chars(FAN_KEY),
+        hex_chars(BLOCKED_MARKET_KEY),
+    ];
+    let response = resolve("can_claim_shared_top_track", inputs).await;
+    assert_eq!(
+        response["error"]["data"]["reason"],
+        json!("geo_restricted"),
+        "{}",
+        response
+    );
+
+    // Without the user-read-private scope the market is unknown, and the claim
+    // fails closed.
+    let inputs = vec![
+        hex_chars(NO_COUNTRY_KEY),
+        hex_chars(""),
+        hex_number(1),
+        hex_number(0),
+    ];
+    let response = resolve("can_claim_user_has_n_saved_tracks", inputs).await;
+    assert_eq!(
+        response["error"],
+        json!({
+            "code": -32602,
+            "message": "The user's market is unknown; the user-read-private scope is required",
+            "data": {"reason": "market_unknown"},
+        }),
+        "{}",
+        response
+    );
+}
+
+async fn post_raw(body: &str) -> Value {
+    reqwest::Client::new()
+        .post(oracle_url())
+        .header("content-type", "application/json")
+        .bearer_auth(ADMIN_TOKEN)
+        .body(body.to_string())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn duplicate_keys_in_request_bodies_are_rejected() {
+    mock_backends();
+
+    let key = hex_chars(KEY);
+    let track = hex_chars("track1");
+    let bodies = [
+        // Top-level `params` given twice.
+        (
+            r#"{"jsonrpc":"2.0","id":1,"method":"resolve_foreign_call","params":[],"params":[]}"#
+                .to_string(),
+            "params",
+        ),
+        // Conflicting claim functions inside the foreign call.
+        (
+            format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"resolve_foreign_call","params":[{{"function":"can_claim_top_track","function":"can_claim_fade_in","inputs":[{key},{track},"0x01","0x14"]}}]}}"#,
+                key = json!(key),
+                track = json!(track),
+            ),
+            "function",
+        ),
+        // Nested inside admin method params.
+        (
+            r#"{"jsonrpc":"2.0","id":1,"method":"store_key","params":{"id":"a","id":"b","token":"t"}}"#
+                .to_string(),
+            "id",
+        ),
+    ];
+    for (body, duplicate) in bodies {
+        let response = post_raw(&body).await;
+        assert_eq!(response["error"]["code"], json!(-32600), "{}", response);
+        let message = response["error"]["message"].as_str().unwrap();
+        assert!(
+            message.starts_with(&format!("Invalid request: duplicate key `{}`", duplicate)),
+            "{}",
+            message
+        );
+    }
+
+    // The same request without the duplicate still goes through.
+    let response = post_raw(&format!(
+        r#"{{"jsonrpc":"2.0","id":1,"method":"store_key","params":[{},{}]}}"#,
+        json!(KEY),
+        json!(TOKEN)
+    ))
+    .await;
+    assert_eq!(response["result"], json!(KEY), "{}", response);
+}
+
+#[tokio::test]
+async fn request_bodies_are_limited_and_must_be_utf8_with_strict_parsing_off() {
+    let middleware = StrictJsonMiddleware::new(create_io(), LoggerMiddleware)
+        .strict(false)
+        .max_body_size(128);
+    let server = ServerBuilder::new(create_io())
+        .request_middleware(middleware)
+        .start_http(&"127.0.0.1:0".parse().unwrap())
+        .expect("start oracle server");
+    let url = format!("http://{}", server.address());
+    let post = |body: Vec<u8>| {
+        reqwest::Client::new()
+            .post(&url)
+            .header("content-type", "application/json")
+            .body(body)
+            .send()
+    };
+
+    // Duplicates pass through, and the request still gets an ID.
+    let body = br#"{"jsonrpc":"2.0","id":1,"method":"list_claims","params":{"a":1,"a":2}}"#;
+    let response = post(body.to_vec()).await.unwrap();
+    assert!(response.headers().contains_key("x-request-id"));
+    let reply: Value = response.json().await.unwrap();
+    assert!(reply["result"].is_array(), "{}", reply);
+
+    let oversized = format!(
+        r#"{{"jsonrpc":"2.0","id":1,"method":"list_claims","params":["{}"]}}"#,
+        "a".repeat(128)
+    );
+    let response = post(oversized.into_bytes()).await.unwrap();
+    assert_eq!(response.status(), 413);
+
+    let response = post(b"{\"jsonrpc\":\"2.0\",\"id\":\"\xff\"}".to_vec())
+        .await
+        .unwrap();
+    let reply: Value = response.json().await.unwrap();
+    assert_eq!(
+        reply["error"],
+        json!({"code": -32700, "message": "Parse error"}),
+        "{}",
+        reply
+    );
+    // The server owns a runtime, which may not be dropped from async code.
+    tokio::task::spawn_blocking(move || server.close())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn admin_methods_require_the_admin_token() {
+    mock_backends();
+
+    let admin_calls = [
+        ("store_key", json!([KEY, TOKEN])),
+        ("rotate_token", json!([KEY, "prefix12", "new_token"])),
+        ("store_key_account", json!([KEY, "work", TOKEN])),
+        ("delete_key", json!([KEY])),
+        ("upsert_event", json!(["event", "track1", 0, 1, 60])),
+        ("storage_usage", json!([])),
+        ("provider_usage", json!(["2024-01-01", "2024-01-01"])),
+        ("recent_provider_errors", json!([1])),
+        ("store_reference_list", json!(["list", ["track1"]])),
+    ];
+    let unauthorized = json!({
+        "code": -32001,
+        "message": "Unauthorized: this method requires the admin token",
+    });
+    for (method, params) in admin_calls {
+        for authorization in [
+            None,
+            Some(ADMIN_TOKEN.to_string()),
+            Some("Bearer wrong-admin-token".to_string()),
+            Some(format!("Bearer {}x", ADMIN_TOKEN)),
+        ] {
+            let response = call_as(authorization.clone(), method, params.clone()).await;
+            assert_eq!(
+                response["error"], unauthorized,
+                "{} with {:?}: {}",
+                method, authorization, response
+            );
+        }
+    }
+
+    // Over HTTP the header is taken from the request.
+    let response = reqwest::Client::new()
+        .post(oracle_url())
+        .json(&json!({"jsonrpc": "2.0", "id": 1, "method": "storage_usage", "params": []}))
+        .send()
+        .await
+        .unwrap()
+        .json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(response["error"], unauthorized, "{}", response);
+
+    // Claims and the public methods need no token.
+    for method in ["list_claims", "health"] {
+        let response = call_as(None, method, json!([])).await;
+        assert!(response["error"].is_null(), "{}: {}", method, response);
+    }
+}
+
+#[tokio::test]
+async fn health_summarizes_recent_provider_failures() {
+    mock_backends();
+    store_test_key().await;
+
+    let inputs = vec![
+        hex_chars(KEY),
+        hex_chars("unavailable"),
+        hex_number(1),
+        hex_number(0),
+    ];
+    resolve("can_claim_track_analysis_end_of_fade_in", inputs).await;
+
+    let response = call_as(None, "health", json!([])).await;
+    let health = &response["result"];
+    assert_eq!(health["status"], json!("degraded"), "{}", response);
+    let spotify = health["degraded_reasons"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|reason| reason["provider"] == "spotify")
+        .expect("a spotify reason");
+    assert!(
+        spotify["counts"]["server_error"].as_u64().unwrap() >= 1,
+        "{}",
+        response
+    );
+}
+
+#[tokio::test]
+async fn provider_calls_are_exported_to_prometheus() {
+    mock_backends();
+    store_test_key().await;
+    resolve(
+        "can_claim_track_analysis_end_of_fade_in",
+        vec![
+            hex_chars(KEY),
+            hex_chars("unavailable"),
+            hex_number(1),
+            hex_number(0),
+        ],
+    )
+    .await;
+
+    let response = reqwest::get(format!("{}/metrics", oracle_url()))
+        .await
+        .unwrap();
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/plain; version=0.0.4"
+    );
+    let body = response.text().await.unwrap();
+    assert!(
+        body.contains("# TYPE oracle_provider_calls_total counter"),
+        "{}",
+        body
+    );
+    assert!(
+        body.lines().any(|line| line
+            .starts_with("oracle_provider_calls_total{provider=\"spotify\"")
+            && line.contains("outcome=\"server_error\"")),
+        "{}",
+        body
+    );
+}
+
+#[tokio::test]
+async fn recent_provider_errors_are_classified_without_user_data() {
+    mock_backends();
+    let client = OracleClient::new(oracle_url()).with_admin_token(ADMIN_TOKEN);
+    client.store_key(KEY, TOKEN).await.unwrap();
+
+    for (claim, expected) in [
+        (
+            ClaimParams::TrackFadeIn {
+                track_id: "unavailable".to_string(),
+                max_seconds: 1,
+            },
+            (json!(503), "server_error", "/v1/audio-analysis/{id}"),
+        ),
+        (
+            ClaimParams::PlaylistFollowers {
+                playlist_id: "playlist9".to_string(),
+                threshold: 1,
+                require_owner: false,
+            },
+            (json!(404), "client_error", "/v1/playlists/{id}"),
+        ),
+    ] {
+        let function = claim.function();
+        assert!(client.resolve_claim(KEY, &claim).await.is_err());
+
+        let response = post_raw(
+            &json!({"jsonrpc": "2.0", "id": 1, "method": "recent_provider_errors", "params": [50]})
+                .to_string(),
+        )
+        .await;
+        let spotify = &response["result"]["spotify"];
+        let (status, outcome, endpoint) = expected;
+        let failure = spotify["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|failure| failure["function"] == function && failure["endpoint"] == endpoint)
+            .unwrap_or_else(|| panic!("no {} failure in {}", function, response));
+        assert_eq!(failure["status"], status);
+        assert_eq!(failure["outcome"], outcome);
+        assert!(failure["request_id"].is_string(), "{}", failure);
+        assert!(spotify["counts"][outcome].as_u64().unwrap() >= 1);
+
+        let text = response.to_string();
+        assert!(!text.contains(TOKEN) && !text.contains(KEY), "{}", text);
+    }
+}
+
+#[tokio::test]
+async fn artist_follower_claim_takes_three_inputs() {
+    mock_backends();
+    store_test_key().await;
+
+    let inputs = vec![
+        hex_chars(KEY),
+        hex_chars("artist1"),
+        hex_number(1),
+        hex_number(0),
+    ];
+    let response = resolve("can_claim_artist_has_n_followers", inputs).await;
+    assert_eq!(
+        response["error"]["message"],
+        json!("Invalid input; requires 3 distinct inputs"),
+        "{}",
+        response
+    );
+
+    let inputs = vec![hex_chars(KEY), hex_chars("artist9"), hex_number(1)];
+    let response = resolve("can_claim_artist_has_n_followers", inputs).await;
+    assert_eq!(
+        response["error"]["message"],
+        json!("Invalid parameters: Unknown artist: artist9"),
+        "{}",
+        response
+    );
+}
+
+async fn resolve_with_strict(function: &str, inputs: Vec<Vec<String>>, strict: bool) -> Value {
+    call(
+        "resolve_foreign_call",
+        json!([{"function": function, "inputs": inputs, "strict": strict}]),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn strict_mode_turns_each_leniency_into_an_error() {
+    mock_backends();
+    store_test_key().await;
+    let response = call("store_key", json!([NO_ARTIST_KEY, NO_ARTIST_TOKEN])).await;
+    assert_eq!(response["result"], json!(NO_ARTIST_KEY), "{}", response);
+    env::set_var(
+        "CLAIM_VALIDITY_SECS_CAN_CLAIM_USER_HAS_N_FOLLOWED_PODCASTS",
+        "a week",
+    );
+
+    let key = hex_chars(KEY);
+    // Each case: the leniency, a claim hitting it, and what it answers leniently.
+    let cases = [
+        (
+            Leniency::InvalidHexField,
+            "can_claim_user_has_n_saved_tracks",
+            vec![
+                key.clone(),
+                hex_chars(""),
+                vec!["12".to_string()],
+                hex_number(0),
+            ],
+            Ok(json!(["1"])),
+        ),
+        (
+            Leniency::DefaultPlaythroughGap,
+            "can_claim_multi_track_ordered_playthrough",
+            vec![
+                key.clone(),
+                hex_chars("album1"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            Ok(json!(["1"])),
+        ),
+        (
+            Leniency::EmptyReferenceEntries,
+            "can_claim_history_contains_full_reference_sequence",
+            vec![
+                key.clone(),
+                hex_chars("track1,,track2"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            Ok(json!(["1"])),
+        ),
+        (
+            Leniency::UnavailablePlaylistItems,
+            "can_claim_track_on_n_user_playlists",
+            vec![
+                key.clone(),
+                hex_chars("track2"),
+                hex_number(3),
+                hex_number(0),
+            ],
+            Ok(json!(["0", "2"])),
+        ),
+        (
+            Leniency::MissingPrimaryArtist,
+            "can_claim_consecutive_same_artist_plays",
+            vec![
+                hex_chars(NO_ARTIST_KEY),
+                hex_chars("artist1"),
+                hex_number(0),
+                hex_number(1),
+            ],
+            Ok(json!(["0"])),
+        ),
+        (
+            Leniency::ResponseFormatHeuristic,
+            "can_claim_track_analysis_summary",
+            vec![
+                key.clone(),
+                hex_chars("maintenance"),
+                hex_number(0),
+                hex_number(0),
+            ],
+            Err("Invalid parameters: provider returned non-API response, status 200"),
+        ),
+        (
+            Leniency::InvalidValidityOverride,
+            "can_claim_user_has_n_followed_podcasts",
+            vec![key.clone(), hex_chars(""), hex_number(1), hex_number(0)],
+            Ok(json!(["1"])),
+        ),
+        (
+            Leniency::UnreadableContextPlaylist,
+            "can_claim_track_in_workout_context",
+            vec![
+                hex_chars(NO_ARTIST_KEY),
+                hex_chars(""),
+                hex_number(0),
+                hex_number(0),
+            ],
+            Ok(json!(["0"])),
+        ),
+    ];
+    let covered: Vec<Leniency> = cases.iter().map(|case| case.0).collect();
+    assert_eq!(covered, LENIENCIES);
+
+    for (leniency, function, inputs, lenient) in cases {
+        let response = resolve_with_strict(function, inputs.clone(), false).await;
+        match lenient {
+            Ok(values) => assert_eq!(response["result"]["values"], values, "{}", response),
+            Err(message) => assert_eq!(response["error"]["message"], message, "{}", response),
+        }
+
+        let response = resolve_with_strict(function, inputs, true).await;
+        assert_eq!(
+            response["error"]["data"],
+            json!({"leniency": leniency.name()}),
+            "{}: {}",
+            function,
+            response
+        );
+        assert_eq!(
+            response["error"]["message"],
+            json!(format!(
+                "Strict mode: {} would have applied",
+                leniency.name()
+            )),
+        );
+    }
+}
+
+#[tokio::test]
+async fn html_pages_and_redirects_are_reported_without_markup() {
+    mock_backends();
+    store_test_key().await;
+
+    for (track, status) in [("error-page", 503), ("challenge", 302)] {
+        let inputs = vec![
+            hex_chars(KEY),
+            hex_chars(track),
+            hex_number(0),
+            hex_number(0),
+        ];
+        let response = resolve_with_strict("can_claim_track_analysis_summary", inputs, false).await;
+        let message = response["error"]["message"]
+            .as_str()
+            .expect("error message");
+        assert_eq!(
+            message,
+            format!(
+                "Invalid parameters: provider returned non-API response, status {}",
+                status
+            ),
+            "{}",
+            response
+        );
+        let error = response["error"].to_string();
+        assert!(
+            !error.contains('<') && !error.contains("challenge.spotify.com"),
+            "{}",
+            response
+        );
+    }
+}
+
+#[tokio::test]
+async fn interstitials_are_retried_and_counted() {
+    mock_backends();
+    store_test_key().await;
+
+    let inputs = vec![
+        hex_chars(KEY),
+        hex_chars("maintenance-once"),
+        hex_number(0),
+        hex_number(0),
+    ];
+    let response = resolve("can_claim_track_analysis_summary", inputs).await;
+    assert!(response["result"]["values"].is_array(), "{}", response);
+    assert_eq!(MAINTENANCE_ONCE_REQUESTS.load(Ordering::SeqCst), 2);
+
+    let metrics = reqwest::get(format!("{}/metrics", oracle_url()))
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(
+        metrics.lines().any(|line| line
+            .starts_with("oracle_provider_calls_total{provider=\"spotify\"")
+            && line.contains("endpoint=\"/v1/audio-analysis/{id}\"")
+            && line.contains("outcome=\"unavailable\"")),
+        "{}",
+        metrics
+    );
+}
+
+#[test]
+fn time_ranges_round_trip_through_their_api_names() {
+    for range in [
+        TimeRange::ShortTerm,
+        TimeRange::MediumTerm,
+        TimeRange::LongTerm,
+    ] {
+        assert_eq!(range.to_string().parse::<TimeRange>(), Ok(range));
+    }
+    assert_eq!(TimeRange::LongTerm.to_string(), "long_term");
+    assert!("all_time".parse::<TimeRange>().is_err());
+}
+
+#[test]
+fn strict_requests_need_the_configuration_to_allow_them() {
+    assert!(Policy::for_request(StrictMode::Off, false).is_ok());
+    assert!(Policy::for_request(StrictMode::Off, true).is_err());
+    assert!(Policy::for_request(StrictMode::Allow, false).is_ok());
+    assert!(Policy::for_request(StrictMode::Allow, true).is_ok());
+    assert!(Policy::for_request(StrictMode::On, false).is_ok());
+}
+
+#[tokio::test]
+async fn audiobook_claim_reports_unsupported_markets() {
+    mock_backends();
+    store_test_key().await;
+    // The second mock account has no audiobooks endpoint, as in markets without them.
+    let response = call("store_key", json!([NO_ARTIST_KEY, NO_ARTIST_TOKEN])).await;
+    assert_eq!(response["result"], json!(NO_ARTIST_KEY), "{}", response);
+
+    let inputs = vec![
+        hex_chars(NO_ARTIST_KEY),
+        hex_chars("audiobook1"),
+        hex_number(0),
+        hex_number(0),
+    ];
+    let response = resolve("can_claim_audiobook_saved", inputs).await;
+    assert_eq!(
+        response["error"]["message"],
+        json!("Invalid parameters: Audiobooks are not available in this user's market"),
+        "{}",
+        response
+    );
+
+    // An audiobook the catalog does not know is not blamed on the market.
+    let inputs = vec![
+        hex_chars(NO_ARTIST_KEY),
+        hex_chars("no-such-audiobook"),
+        hex_number(0),
+        hex_number(0),
+    ];
+    let response = resolve("can_claim_audiobook_saved", inputs).await;
+    assert_eq!(
+        response["error"]["message"],
+        json!("Invalid parameters: Unknown audiobook: no-such-audiobook"),
+        "{}",
+        response
+    );
+
+    // The same holds for chapters, whatever the user's market.
+    for key in [KEY, NO_ARTIST_KEY] {
+        let inputs = vec![
+            hex_chars(key),
+            hex_chars("no-such-audiobook"),
+            hex_chars("chapter1"),
+            hex_number(0),
+        ];
+        let response = resolve("can_claim_audiobook_chapter_listened", inputs).await;
+        assert_eq!(
+            response["error"]["message"],
+            json!("Invalid parameters: Unknown audiobook: no-such-audiobook"),
+            "{}",
+            response
+        );
+    }
+    let inputs = vec![
+        hex_chars(NO_ARTIST_KEY),
+        hex_chars("audiobook1"),
+        hex_chars("chapter1"),
+        hex_number(0),
+    ];
+    let response = resolve("can_claim_audiobook_chapter_listened", inputs).await;
+    assert_eq!(
+        response["error"]["message"],
+        json!("Invalid parameters: Audiobooks are not available in this user's market"),
+        "{}",
+        response
+    );
+}
+
+#[tokio::test]
+async fn claims_select_an_account_bound_to_a_key() {
+    mock_backends();
+    for (account, token) in [("personal", TOKEN), ("work", NO_ARTIST_TOKEN)] {
+        let response = call("store_key_account", json!(["multi", account, token])).await;
+        assert_eq!(response["result"], json!("multi"), "{}", response);
+    }
+
+    let audiobook_saved = |key: Value| {
+        let inputs = json!([key, hex_chars("audiobook1"), hex_number(0), hex_number(0)]);
+        call(
+            "resolve_foreign_call",
+            json!([{"function": "can_claim_audiobook_saved", "inputs": inputs}]),
+        )
+    };
+    // The second entry of the key input names the account.
+    let account_key = |account: &str| json!([hex_chars("multi"), hex_chars(account)]);
+    let response = audiobook_saved(account_key("personal")).await;
+    assert!(response["result"]["values"].is_array(), "{}", response);
+
+    let response = audiobook_saved(account_key("work")).await;
+    assert_eq!(
+        response["error"]["message"],
+        json!("Invalid parameters: Audiobooks are not available in this user's market"),
+        "{}",
+        response
+    );
+
+    // A key with only accounts has no token of its own, and the old `key:account`
+    // spelling does not select an account.
+    for key in [
+        account_key("unknown"),
+        json!(hex_chars("multi")),
+        json!(hex_chars("multi:personal")),
+    ] {
+        let response = audiobook_saved(key.clone()).await;
+        assert_eq!(
+            response["error"]["code"],
+            json!(-32602),
+            "{}: {}",
+            key,
+            response
+        );
+    }
+
+    let response = call("store_key_account", json!(["multi:x", "work", TOKEN])).await;
+    assert_eq!(response["error"]["code"], json!(-32602), "{}", response);
+
+    // The typed client encodes the account the same way.
+    let client = OracleClient::new(oracle_url()).with_admin_token(ADMIN_TOKEN);
+    let claim = ClaimParams::AudiobookSaved {
+        audiobook_id: "audiobook1".to_string(),
+    };
+    assert!(client
+        .resolve_account_claim("multi", "personal", &claim)
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn rotate_token_swaps_only_the_expected_token() {
+    mock_backends();
+    // Both tokens start with the same character.
+    let (old_token, new_token) = ("Bearer a1111111-old", "Bearer a2222222-new");
+    let response = call("store_key", json!(["rotating", old_token])).await;
+    assert_eq!(response["result"], json!("rotating"), "{}", response);
+
+    let mismatch = json!("Old token prefix does not match the stored token");
+    // The scheme is not part of the compared prefix, and neither is a lone
+    // matching first character.
+    for prefix in ["Bearer a", "a2222222"] {
+        let response = call("rotate_token", json!(["rotating", prefix, new_token])).await;
+        assert_eq!(response["error"]["message"], mismatch, "{}", response);
+    }
+    let response = call("rotate_token", json!(["rotating", "a111", new_token])).await;
+    assert_eq!(response["error"]["code"], json!(-32602), "{}", response);
+
+    let response = call("rotate_token", json!(["rotating", "a1111111", new_token])).await;
+    assert_eq!(
+        response["result"],
+        json!({"id": "rotating", "old_token_expires_in": null}),
+        "{}",
+        response
+    );
+
+    // The old prefix no longer matches once the token was rotated.
+    let response = call(
+        "rotate_token",
+        json!(["rotating", "a1111111", "Bearer a3333333-other"]),
+    )
+    .await;
+    assert_eq!(response["error"]["message"], mismatch, "{}", response);
+    let response = call(
+        "rotate_token",
+        json!(["rotating", "a2222222-new", "Bearer b"]),
+    )
+    .await;
+    assert!(response["result"].is_object(), "{}", response);
+
+    let response = call(
+        "rotate_token",
+        json!(["no-such-key", "a1111111", "Bearer new"]),
+    )
+    .await;
+    assert_eq!(
+        response["error"]["message"],
+        json!("Unknown key"),
+        "{}",
+        response
+    );
+}
+
+#[test]
+fn tokens_round_trip_through_encryption() {
+    let key = parse_encryption_key(ENCRYPTION_KEY).unwrap();
+    let sealed = encrypt_token(&key, TOKEN);
+    assert!(!sealed.contains("test-token"), "{}", sealed);
+    assert_eq!(decrypt_token(&key, &sealed).unwrap(), TOKEN);
+    // Every encryption uses a fresh nonce.
+    assert_ne!(encrypt_token(&key, TOKEN), sealed);
+
+    let mut wrong_key = key;
+    wrong_key[0] ^= 1;
+    assert!(decrypt_token(&wrong_key, &sealed).is_err());
+
+    let mut tampered = sealed.clone();
+    let last = if tampered.ends_with('0') { "1" } else { "0" };
+    tampered.replace_range(tampered.len() - 1.., last);
+    assert!(decrypt_token(&key, &tampered).is_err());
+
+    assert!(parse_encryption_key("0011").is_none());
+}
+
+#[test]
+fn plaintext_token_storage_must_be_opted_into() {
+    assert!(check_encryption_config(Some(ENCRYPTION_KEY), None).is_ok());
+    assert!(check_encryption_config(Some("0011"), Some("true")).is_err());
+    let error = check_encryption_config(None, None).unwrap_err();
+    assert!(
+        error.to_string().contains("ORACLE_ALLOW_PLAINTEXT"),
+        "{}",
+        error
+    );
+    assert!(check_encryption_config(None, Some("false")).is_err());
+    assert!(check_encryption_config(None, Some("true")).is_ok());
+    assert!(check_encryption_config(None, Some("1")).is_ok());
+}
+
+#[tokio::test]
+async fn plaintext_tokens_are_re_encrypted_when_read() {
+    mock_backends();
+    let client =
+        redis::Client::open(format!("redis://{}", env::var("REDIS_HOSTNAME").unwrap())).unwrap();
+    let mut conn = client.get_connection().unwrap();
+    let _: () = redis::cmd("SET")
+        .arg("legacy-key")
+        .arg(TOKEN)
+        .query(&mut conn)
+        .unwrap();
+    let _: () = redis::cmd("HSET")
+        .arg("accounts:legacy-key")
+        .arg("legacy-account")
+        .arg(TOKEN)
+        .query(&mut conn)
+        .unwrap();
+    let reads_before = plaintext_token_reads();
+
+    let top_artist = |key: Value| {
+        let inputs = json!([key, hex_chars("artist1"), hex_number(1), hex_number(20)]);
+        call(
+            "resolve_foreign_call",
+            json!([{"function": "can_claim_top_artist", "inputs": inputs}]),
+        )
+    };
+    let response = top_artist(json!(hex_chars("legacy-key"))).await;
+    assert_eq!(response["result"]["values"], json!(["1"]), "{}", response);
+    let response = top_artist(json!([
+        hex_chars("legacy-key"),
+        hex_chars("legacy-account")
+    ]))
+    .await;
+    assert_eq!(response["result"]["values"], json!(["1"]), "{}", response);
+
+    let stored: String = redis::cmd("GET")
+        .arg("legacy-key")
+        .query(&mut conn)
+        .unwrap();
+    assert!(stored.starts_with("enc:v1:"), "{}", stored);
+    let stored: String = redis::cmd("HGET")
+        .arg("accounts:legacy-key")
+        .arg("legacy-account")
+        .query(&mut conn)
+        .unwrap();
+    assert!(stored.starts_with("enc:v1:"), "{}", stored);
+    assert!(plaintext_token_reads() >= reads_before + 2);
+
+    // Once re-encrypted, the tokens still resolve claims.
+    let response = top_artist(json!(hex_chars("legacy-key"))).await;
+    assert_eq!(response["result"]["values"], json!(["1"]), "{}", response);
+}
+
+#[test]
+fn redis_cluster_addresses_are_rejected_at_startup() {
+    assert!(check_redis_hostname("localhost:6379").is_ok());
+    let error = check_redis_hostname("redis+cluster://node1:7000").unwrap_err();
+    assert!(error.to_string().contains("not supported"), "{}", error);
+}
+
+#[test]
+fn sentinel_config_is_validated_up_front() {
+    let config = SentinelConfig::parse(
+        Some("redis://sentinel1:26379, redis://sentinel2:26379,"),
+        Some("mymaster"),
+    )
+    .unwrap();
+    assert_eq!(
+        config.urls,
+        vec!["redis://sentinel1:26379", "redis://sentinel2:26379"]
+    );
+    assert_eq!(config.master_name, "mymaster");
+
+    for (urls, master_name, expected) in [
+        (None, Some("mymaster"), "REDIS_SENTINEL_URLS"),
+        (Some(" , "), Some("mymaster"), "no sentinels"),
+        (Some("redis://sentinel1:26379"), None, "REDIS_MASTER_NAME"),
+        (
+            Some("redis://sentinel1:26379"),
+            Some(" "),
+            "REDIS_MASTER_NAME",
+        ),
+    ] {
+        let error = SentinelConfig::parse(urls, master_name).unwrap_err();
+        assert!(error.to_string().contains(expected), "{}", error);
+    }
+    assert!(SentinelConfig::parse(Some("not a url"), Some("mymaster")).is_err());
+}
+
+#[test]
+fn sentinels_are_asked_for_the_master_in_order() {
+    let sentinel = format!("redis://{}", start_fake_redis());
+    // Nothing listens on port 1, so the first sentinel is skipped.
+    let sentinels = ["redis://127.0.0.1:1", sentinel.as_str()];
+
+    assert_eq!(
+        discover_master(&sentinels, "mymaster").unwrap(),
+        "10.0.0.5:6380"
+    );
+    assert!(discover_master(&sentinels, "othermaster").is_err());
+    assert!(discover_master(&[], "mymaster").is_err());
+}
+
+#[test]
+fn ttl_cache_is_bounded() {
+    let cache: TtlCache<&str, u32> = TtlCache::with_capacity(std::time::Duration::from_secs(60), 2);
+    cache.insert("track1", 1);
+    thread::sleep(std::time::Duration::from_millis(5));
+    cache.insert("track2", 2);
+    // Replacing an entry needs no room.
+    cache.insert("track2", 20);
+    assert_eq!(cache.len(), 2);
+
+    // When full, the oldest entry makes room.
+    cache.insert("track3", 3);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&"track1"), None);
+    assert_eq!(cache.get(&"track2"), Some(20));
+    assert_eq!(cache.get(&"track3"), Some(3));
+
+    // Expired entries are purged before anything live is evicted.
+    let cache: TtlCache<&str, u32> =
+        TtlCache::with_capacity(std::time::Duration::from_millis(20), 2);
+    cache.insert("track1", 1);
+    cache.insert("track2", 2);
+    thread::sleep(std::time::Duration::from_millis(30));
+    cache.insert("track3", 3);
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.get(&"track3"), Some(3));
+}
+
+#[tokio::test]
+async fn concurrent_identical_requests_share_one_run() {
+    let cache: PendingRequestCache<&str, u32> = PendingRequestCache::default();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let run = |value| {
+        let runs = runs.clone();
+        move || async move {
+            runs.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            value
+        }
+    };
+
+    let (first, second, other) = tokio::join!(
+        cache.get_or_run("track1", run(1)),
+        cache.get_or_run("track1", run(2)),
+        cache.get_or_run("track2", run(3)),
+    );
+    assert_eq!((first, second, other), (1, 1, 3));
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+    // Finished runs are not cached.
+    assert_eq!(cache.get_or_run("track1", run(4)).await, 4);
+    assert_eq!(runs.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn claim_spans_continue_the_incoming_trace() {
+    mock_backends();
+    store_test_key().await;
+
+    let trace_id = "4bf92f3577b34da6a3ce929d0e0e4736";
+    let parent_id = "00f067aa0ba902b7";
+    let inputs = vec![
+        hex_chars(KEY),
+        hex_chars("artist1"),
+        hex_number(1),
+        // A list range no other test uses, so no concurrent call is shared.
+        hex_number(7),
+    ];
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "resolve_foreign_call",
+        "params": [{"function": "can_claim_top_artist", "inputs": inputs}],
+    });
+    let response: Value = reqwest::Client::new()
+        .post(oracle_url())
+        .header("traceparent", format!("00-{}-{}-01", trace_id, parent_id))
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(response["result"]["values"], json!(["1"]), "{}", response);
+
+    // Spans are exported in the background.
+    let find = |name: &str| {
+        EXPORTED_SPANS
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|span| span["traceId"] == trace_id && span["name"] == name)
+            .cloned()
+    };
+    let mut spans = None;
+    for _ in 0..50 {
+        if let (Some(claim), Some(call)) =
+            (find("can_claim_top_artist"), find("GET /v1/me/top/artists"))
+        {
+            spans = Some((claim, call));
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let (claim, call) = spans.expect("claim and Spotify call spans are exported");
+    assert_eq!(claim["parentSpanId"], parent_id, "{}", claim);
+    assert_eq!(call["parentSpanId"], claim["spanId"], "{}", call);
+    assert_eq!(claim["status"]["code"], 1, "{}", claim);
+}
+
+#[tokio::test]
+async fn unsampled_traces_export_no_spans() {
+    mock_backends();
+    store_test_key().await;
+
+    let trace_id = "0af7651916cd43dd8448eb211c80319c";
+    let inputs = vec![
+        hex_chars(KEY),
+        hex_chars("artist1"),
+        hex_number(1),
+        // A list range no other test uses, so no concurrent call is shared.
+        hex_number(8),
+    ];
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "resolve_foreign_call",
+        "params": [{"function": "can_claim_top_artist", "inputs": inputs}],
+    });
+    let response: Value = reqwest::Client::new()
+        .post(oracle_url())
+        .header(
+            "traceparent",
+            format!("00-{}-b7ad6b7169203331-00", trace_id),
+        )
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(response["result"]["values"], json!(["1"]), "{}", response);
+
+    // Several export intervals pass without a span of the trace.
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    let exported = EXPORTED_SPANS
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|span| span["traceId"] == trace_id);
+    assert!(!exported);
+}